@@ -0,0 +1,22 @@
+# examples/v1.1_tests/any_test.rl
+
+var value: any = 42
+print(value)
+value = value + 8
+print(value)
+
+value = "now a string"
+print(value)
+print(f"interpolated: {value}")
+
+value = 2.5
+print(value * 2)
+
+val same: bool = value == 2.5
+print(same)
+
+try:
+    value = "text"
+    print(value - 1)
+catch e:
+    print("caught a runtime type error")
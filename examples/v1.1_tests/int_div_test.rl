@@ -0,0 +1,13 @@
+# examples/v1.1_tests/int_div_test.rl
+# Compile with `--int-div trunc` (the default) or `--int-div floor` to compare.
+
+val a: int = 0 - 7
+val b: int = 2
+val c: float = 0.0 - 7.0
+
+print("-7 / 2 is:")
+print(a / b) # trunc: -3, floor: -4
+print("7 / 2 is:")
+print(7 / b) # 3 in both modes
+print("-7.0 / 2.0 is:")
+print(c / 2.0) # -3.5 in both modes
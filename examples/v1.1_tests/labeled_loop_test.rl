@@ -0,0 +1,23 @@
+# examples/v1.1_tests/labeled_loop_test.rl
+
+print("1. Breaking the outer loop from the inner one:")
+outer: for i in 0..5:
+    for j in 0..5:
+        if j == 2:
+            continue outer
+        if i == 3:
+            break outer
+        print(i * 10 + j)
+
+print("2. Labeled while loop:")
+var n: int = 0
+search: while n < 100:
+    var m: int = 0
+    while m < 100:
+        if n * m == 12:
+            print(f"Found {n} * {m}")
+            break search
+        m = m + 1
+    n = n + 1
+
+print("Labeled loop test finished.")
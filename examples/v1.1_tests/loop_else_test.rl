@@ -0,0 +1,28 @@
+# examples/v1.1_tests/loop_else_test.rl
+# A loop's else block runs only when the loop finishes without a break.
+
+# Finds a divisor: the break skips the else.
+for i in 2..10:
+    if 15 - (15 / i) * i == 0:
+        print(f"15 is divisible by {i}")
+        break
+else:
+    print("15 is prime")
+
+# No divisor is found, so the loop completes and the else runs.
+for i in 2..7:
+    if 7 - (7 / i) * i == 0:
+        print(f"7 is divisible by {i}")
+        break
+else:
+    print("7 is prime")
+
+# continue doesn't count as leaving the loop early.
+var n: int = 0
+while n < 3:
+    n = n + 1
+    if n == 2:
+        continue
+    print(n)
+else:
+    print("while loop completed")
@@ -1,6 +1,25 @@
 //! The Abstract Syntax Tree (AST) for the REDLINE language.
 //! Each node in the tree represents a construct in the code, like a statement or an expression.
 use serde::Serialize;
+use std::fmt;
+
+/// The name `_`. Declaring or assigning to it evaluates the value and throws it away.
+pub const DISCARD: &str = "_";
+
+/// A location in the source file, used to point diagnostics at the code that caused them.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    /// Which of the files compiled together the location is in, by its position on the command line.
+    /// Always 0 when there's only one file, and left out of `--json-ast` then.
+    #[serde(skip_serializing_if = "is_first_file")]
+    pub file: usize,
+}
+
+fn is_first_file(file: &usize) -> bool {
+    *file == 0
+}
 
 /// Represents the fundamental data types in REDLINE.
 #[derive(Debug, PartialEq, Clone, Serialize)]
@@ -9,30 +28,70 @@ pub enum Type {
     Float,
     String,
     Bool,
+    /// A single Unicode character.
+    Char,
     Void, // Represents the absence of a return value
+    /// Dynamically typed value; operations on it are checked at runtime instead of compile time.
+    Any,
     List(Box<Type>),
     Dict(Box<Type>, Box<Type>), // Dictionary type: dict[Key, Value]
     Class(String), // Represents a user-defined class type
+    /// A user-defined `enum`.
+    Enum(String),
+    /// A value that may be missing, e.g. `int?`. `none` is the missing value.
+    Optional(Box<Type>),
+    /// A fixed number of values of the given types, e.g. `(int, string)`.
+    Tuple(Vec<Type>),
 }
 
-impl ToString for Type {
-    fn to_string(&self) -> String {
+impl Type {
+    /// Whether this is a single plain value that `to_string` can convert, like an `int` or an enum.
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, Type::Int | Type::Float | Type::Bool | Type::Char | Type::Enum(_))
+    }
+
+    /// The type as it's spelled in REDLINE source, e.g. `list[string]` (`Display` gives the C++ spelling).
+    pub fn source_name(&self) -> String {
         match self {
             Type::Int => "int".to_string(),
-            Type::Float => "double".to_string(),
-            Type::String => "std::string".to_string(),
+            Type::Float => "float".to_string(),
+            Type::String => "string".to_string(),
             Type::Bool => "bool".to_string(),
+            Type::Char => "char".to_string(),
             Type::Void => "void".to_string(),
+            Type::Any => "any".to_string(),
+            Type::List(inner) => format!("list[{}]", inner.source_name()),
+            Type::Dict(key, value) => format!("dict[{}, {}]", key.source_name(), value.source_name()),
+            Type::Class(name) | Type::Enum(name) => name.clone(),
+            Type::Optional(inner) => format!("{}?", inner.source_name()),
+            Type::Tuple(elements) => format!("({})", elements.iter().map(Type::source_name).collect::<Vec<_>>().join(", ")),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "double"),
+            Type::String => write!(f, "std::string"),
+            Type::Bool => write!(f, "bool"),
+            Type::Char => write!(f, "char32_t"),
+            Type::Void => write!(f, "void"),
+            Type::Any => write!(f, "rl::Any"),
             Type::List(inner) => {
                 // If the list contains class objects, it's a list of smart pointers.
                 if let Type::Class(class_name) = &**inner {
-                    format!("std::vector<std::shared_ptr<{}>>", class_name)
+                    write!(f, "std::vector<std::shared_ptr<{}>>", class_name)
                 } else {
-                    format!("std::vector<{}>", inner.to_string())
+                    write!(f, "std::vector<{}>", inner)
                 }
             },
-            Type::Dict(key, value) => format!("std::map<{}, {}>", key.to_string(), value.to_string()),
-            Type::Class(name) => format!("std::shared_ptr<{}>", name),
+            Type::Dict(key, value) => write!(f, "std::map<{}, {}>", key, value),
+            Type::Class(name) => write!(f, "std::shared_ptr<{}>", name),
+            Type::Enum(name) => write!(f, "{}", name),
+            Type::Optional(inner) => write!(f, "std::optional<{}>", inner),
+            Type::Tuple(elements) => write!(f, "std::tuple<{}>", elements.iter().map(Type::to_string).collect::<Vec<_>>().join(", ")),
         }
     }
 }
@@ -41,31 +100,84 @@ impl ToString for Type {
 #[derive(Debug, PartialEq, Clone, Serialize)]
 pub enum Literal {
     Int(i64),
-    Float(f64),
+    /// A float and, when it was written in the source, its spelling there, so `3.10` is emitted as `3.10`.
+    Float(f64, Option<String>),
     String(String),
     Bool(bool),
+    /// A single Unicode character, e.g. `'A'`.
+    Char(char),
+    /// `none`, the missing value of an optional type.
+    None,
+}
+
+impl Literal {
+    /// The type of the value, or `None` for `none`, which belongs to every optional type.
+    pub fn data_type(&self) -> Option<Type> {
+        match self {
+            Literal::Int(_) => Some(Type::Int),
+            Literal::Float(..) => Some(Type::Float),
+            Literal::String(_) => Some(Type::String),
+            Literal::Bool(_) => Some(Type::Bool),
+            Literal::Char(_) => Some(Type::Char),
+            Literal::None => None,
+        }
+    }
 }
 
 /// Represents a binary operator.
 #[derive(Debug, PartialEq, Clone, Serialize)]
 pub enum BinaryOperator {
     Add, Subtract, Multiply, Divide,
+    /// `a % b`: the remainder of dividing `int`s, which takes the sign of `a` (or of `b` under `--int-div floor`).
+    Modulo,
     Equal, NotEqual, GreaterThan, LessThan, GreaterThanEqual, LessThanEqual,
+    And, Or,
+    /// `x in xs`: whether a list holds an element, or a string holds a substring or character.
+    In,
+}
+
+impl BinaryOperator {
+    /// `+`, `-`, `*`, `/`, and `%`, the operators that compute a number.
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(self, BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo)
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+            BinaryOperator::GreaterThan => ">",
+            BinaryOperator::LessThan => "<",
+            BinaryOperator::GreaterThanEqual => ">=",
+            BinaryOperator::LessThanEqual => "<=",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Or => "||",
+            BinaryOperator::In => "in",
+        };
+        write!(f, "{}", op)
+    }
 }
 
-impl ToString for BinaryOperator {
-    fn to_string(&self) -> String {
+/// Represents a prefix (unary) operator.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub enum UnaryOperator {
+    Not,
+    /// `-x`. A `-` written directly before a number is part of the literal instead.
+    Negate,
+}
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            BinaryOperator::Add => "+".to_string(),
-            BinaryOperator::Subtract => "-".to_string(),
-            BinaryOperator::Multiply => "*".to_string(),
-            BinaryOperator::Divide => "/".to_string(),
-            BinaryOperator::Equal => "==".to_string(),
-            BinaryOperator::NotEqual => "!=".to_string(),
-            BinaryOperator::GreaterThan => ">".to_string(),
-            BinaryOperator::LessThan => "<".to_string(),
-            BinaryOperator::GreaterThanEqual => ">=".to_string(),
-            BinaryOperator::LessThanEqual => "<=".to_string(),
+            UnaryOperator::Not => write!(f, "!"),
+            UnaryOperator::Negate => write!(f, "-"),
         }
     }
 }
@@ -75,18 +187,118 @@ impl ToString for BinaryOperator {
 pub enum Expression {
     Literal(Literal),
     ListLiteral(Vec<Expression>),
+    /// A tuple of two or more values, e.g. `(1, "one")`.
+    TupleLiteral(Vec<Expression>),
     DictLiteral(Vec<(Expression, Expression)>), // Dictionary literal: { key: value, ... }
     Identifier(String),
     BinaryOp { op: BinaryOperator, left: Box<Expression>, right: Box<Expression> },
+    /// A prefix operator applied to one operand, e.g. `!done`.
+    Unary { op: UnaryOperator, operand: Box<Expression> },
     /// A function or method call. `callee` is the expression being called.
     Call { callee: Box<Expression>, args: Vec<Expression> },
     Index { list: Box<Expression>, index: Box<Expression> },
+    /// A copy of part of a list or string, e.g. `items[1..3]`. A missing bound runs to that end.
+    Slice { target: Box<Expression>, start: Option<Box<Expression>>, end: Option<Box<Expression>> },
     /// Member access, e.g., `my_object.member`.
     Get { object: Box<Expression>, name: String },
     /// The `this` keyword.
     This,
     /// Heap allocation, e.g., `new MyClass()`.
     New { class_name: String, args: Vec<Expression> },
+    /// An assignment used as a value, e.g. `(n = read_int())` in an `if` or `while` condition.
+    /// Its value is the value assigned.
+    Assign { target: Box<Expression>, value: Box<Expression> },
+    /// A value picked by comparing `subject` with each arm's pattern in turn, e.g.
+    /// `match c: 1 => "one", 2 => "two", _ => "many"`. `default` is the `_` arm, used when none match.
+    Match { subject: Box<Expression>, arms: Vec<(Expression, Expression)>, default: Box<Expression> },
+}
+
+/// The place an assignment stores its value, e.g. the `xs[i]` in `xs[i] = 0`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AssignTarget<'a> {
+    /// A variable (or, in a method, a field of `this`), e.g. `x = 1`.
+    Variable(&'a str),
+    /// An element of a list or dict, e.g. `xs[0] = 1` or `ages["Bob"] = 26`.
+    Element { container: &'a Expression, index: &'a Expression },
+    /// A field of an object, e.g. `p.x = 1`.
+    Field { object: &'a Expression, name: &'a str },
+}
+
+impl Expression {
+    /// Whether this is `_`, which throws away what's assigned to it.
+    pub fn is_discard(&self) -> bool {
+        matches!(self, Expression::Identifier(name) if name == DISCARD)
+    }
+
+    /// Where assigning to this expression stores the value, or `None` if it can't be assigned to,
+    /// like a call or `x + 1`.
+    pub fn assign_target(&self) -> Option<AssignTarget<'_>> {
+        match self {
+            Expression::Identifier(name) => Some(AssignTarget::Variable(name)),
+            Expression::Index { list, index } => Some(AssignTarget::Element { container: list, index }),
+            Expression::Get { object, name } => Some(AssignTarget::Field { object, name }),
+            _ => None,
+        }
+    }
+
+    /// The expressions directly inside this one, in evaluation order.
+    pub fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::BinaryOp { left, right, .. } => vec![left, right],
+            Expression::Unary { operand, .. } => vec![operand],
+            Expression::Call { callee, args } => std::iter::once(&**callee).chain(args).collect(),
+            Expression::New { args: elements, .. } | Expression::ListLiteral(elements) | Expression::TupleLiteral(elements) => elements.iter().collect(),
+            Expression::DictLiteral(entries) => entries.iter().flat_map(|(key, value)| [key, value]).collect(),
+            Expression::Index { list, index } => vec![list, index],
+            Expression::Slice { target, start, end } => std::iter::once(&**target).chain(start.as_deref()).chain(end.as_deref()).collect(),
+            Expression::Get { object, .. } => vec![object],
+            Expression::Assign { target, value } => vec![target, value],
+            Expression::Match { subject, arms, default } => {
+                std::iter::once(&**subject).chain(arms.iter().flat_map(|(pattern, value)| [pattern, value])).chain([&**default]).collect()
+            }
+            Expression::Literal(_) | Expression::Identifier(_) | Expression::This => vec![],
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            Expression::BinaryOp { left, right, .. } => vec![left, right],
+            Expression::Unary { operand, .. } => vec![operand],
+            Expression::Call { callee, args } => std::iter::once(&mut **callee).chain(args).collect(),
+            Expression::New { args: elements, .. } | Expression::ListLiteral(elements) | Expression::TupleLiteral(elements) => elements.iter_mut().collect(),
+            Expression::DictLiteral(entries) => entries.iter_mut().flat_map(|(key, value)| [key, value]).collect(),
+            Expression::Index { list, index } => vec![list, index],
+            Expression::Slice { target, start, end } => std::iter::once(&mut **target).chain(start.as_deref_mut()).chain(end.as_deref_mut()).collect(),
+            Expression::Get { object, .. } => vec![object],
+            Expression::Assign { target, value } => vec![target, value],
+            Expression::Match { subject, arms, default } => {
+                std::iter::once(&mut **subject).chain(arms.iter_mut().flat_map(|(pattern, value)| [pattern, value])).chain([&mut **default]).collect()
+            }
+            Expression::Literal(_) | Expression::Identifier(_) | Expression::This => vec![],
+        }
+    }
+}
+
+/// A function parameter. Parameters are immutable unless declared with `var`, e.g. `def f(var n: int)`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Param {
+    pub name: String,
+    /// For a variadic parameter, the type of each argument; the body sees a `list` of them.
+    pub data_type: Type,
+    pub is_mutable: bool,
+    /// Set on a trailing `name: T...` parameter, which collects any number of extra arguments.
+    pub is_variadic: bool,
+}
+
+impl Param {
+    /// The parameter's type as seen inside the function body.
+    pub fn body_type(&self) -> Type {
+        if self.is_variadic {
+            Type::List(Box::new(self.data_type.clone()))
+        } else {
+            self.data_type.clone()
+        }
+    }
 }
 
 /// Represents a single member of a class (either a variable or a function).
@@ -97,25 +309,65 @@ pub enum ClassMember {
     Constructor(Statement), // Represents the 'init' method
 }
 
+/// One variant of an `enum`, with the integer it stands for.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct EnumVariant {
+    pub name: String,
+    /// Written as `= n`, or else one more than the previous variant's (0 for the first).
+    pub value: i64,
+    pub span: Span,
+}
+
+/// Where `print` writes its output.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
 /// Represents a statement. A statement is a piece of code that performs an action.
 #[derive(Debug, PartialEq, Clone, Serialize)]
 pub enum Statement {
     Import(String),
-    Declaration { is_public: bool, is_mutable: bool, name: String, data_type: Type, initializer: Expression },
-    Assignment { target: Expression, value: Expression },
-    If { condition: Expression, consequence: Vec<Statement>, alternative: Option<Vec<Statement>> },
-    While { condition: Expression, body: Vec<Statement> },
-    For { iterator: String, start: Expression, end: Expression, body: Vec<Statement> },
-    Print(Expression),
-    Expression(Expression),
-    FunctionDefinition { is_public: bool, name: String, params: Vec<(String, Type)>, return_type: Type, body: Vec<Statement> },
-    Return(Option<Expression>),
-    /// A class definition.
-    Class { is_public: bool, name: String, members: Vec<ClassMember> },
+    /// `val`/`var` declaration. `span` points at the `val`/`var` keyword.
+    /// `initializer` is `None` for a `var` declared without a value, e.g. `var x: int`.
+    Declaration { is_public: bool, is_mutable: bool, name: String, data_type: Type, initializer: Option<Expression>, span: Span },
+    /// `val (a, b) = pair`: declares a name for each element of a tuple, with that element's type.
+    /// A `_` in `names` skips its element. `span` points at the `val`/`var` keyword.
+    Destructure { is_mutable: bool, names: Vec<String>, value: Expression, span: Span },
+    /// `target = value`. A chained `a = b = value` has the targets `a` and `b`, assigned right to left.
+    /// `op` is set for a compound assignment like `x += value`, which has a single target.
+    Assignment { targets: Vec<Expression>, op: Option<BinaryOperator>, value: Expression, span: Span },
+    /// An `if` statement. `span` points at `if` and `else_span` at `else`, when there is one.
+    If { condition: Expression, consequence: Vec<Statement>, alternative: Option<Vec<Statement>>, span: Span, else_span: Option<Span> },
+    /// A `while` loop. `label` is set for labeled loops, e.g. `outer: while ...:`.
+    /// `else_block` runs when the loop ends because its condition is false, but not after a `break`.
+    While { label: Option<String>, condition: Expression, body: Vec<Statement>, else_block: Option<Vec<Statement>>, span: Span },
+    For { label: Option<String>, iterator: String, start: Expression, end: Expression, body: Vec<Statement>, else_block: Option<Vec<Statement>>, span: Span },
+    /// A loop over the elements of a list or the characters of a string, e.g. `for c in "hello":`.
+    ForEach { label: Option<String>, iterator: String, iterable: Expression, body: Vec<Statement>, else_block: Option<Vec<Statement>>, span: Span },
+    /// `print(a, b, ...)` (to stdout) or `eprint(...)` (to stderr), which prints the values on one line.
+    /// `sep` is the string from `sep: "..."` put between them, when it's given; it's a space otherwise.
+    Print { values: Vec<Expression>, sep: Option<String>, stream: Stream, span: Span },
+    /// An expression evaluated for its effect, like a call. `span` points at its start.
+    Expression { expr: Expression, span: Span },
+    /// A function or method. `doc` holds the `#` comment lines written directly above the `def`, and `span` points at `def`.
+    FunctionDefinition { is_public: bool, name: String, params: Vec<Param>, return_type: Type, body: Vec<Statement>, doc: Option<String>, span: Span },
+    /// `return` or `return value`. `span` points at `return`.
+    Return { value: Option<Expression>, span: Span },
+    /// A class definition. `span` points at `class`.
+    Class { is_public: bool, name: String, members: Vec<ClassMember>, span: Span },
+    /// An enum definition, e.g. `enum Status: Ok = 0, Err = 1`. `span` points at `enum`.
+    Enum { is_public: bool, name: String, variants: Vec<EnumVariant>, span: Span },
     /// A try-catch block.
     TryCatch { try_block: Vec<Statement>, catch_var: String, catch_block: Vec<Statement> },
-    Break,
-    Continue,
+    /// `break` or `break label`.
+    Break { label: Option<String>, span: Span },
+    /// `continue` or `continue label`.
+    Continue { label: Option<String>, span: Span },
+    /// `static_assert condition, "message"`. The condition is checked while compiling, so it can
+    /// only use literals and constants. `span` points at `static_assert`.
+    StaticAssert { condition: Expression, message: Option<String>, span: Span },
 }
 
 /// The root of the AST, representing the entire program as a list of statements.
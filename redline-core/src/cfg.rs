@@ -0,0 +1,273 @@
+//! Control-flow graphs of function bodies, dumped as Graphviz with `--emit cfg`.
+//! A function body is split into basic blocks (straight-line runs of statements) at every `if`,
+//! loop, `return`, `break`, and `continue`, and the blocks are linked by the jumps between them.
+use crate::ast::{ClassMember, Expression, Program, Statement, Stream};
+
+/// The basic blocks of one function. Block 0 is where the function starts and block 1 is where it
+/// returns; every `return`, and falling off the end of the body, leads to block 1.
+#[derive(Debug, Clone)]
+pub struct Cfg {
+    /// The function's name, `Class.method` for a method, or `main` for the top-level code.
+    pub name: String,
+    pub blocks: Vec<BasicBlock>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BasicBlock {
+    /// A short description of each statement in the block, prefixed with its line. A block that
+    /// branches ends with the statement whose condition decides where to go.
+    pub statements: Vec<String>,
+    pub successors: Vec<Edge>,
+}
+
+/// A jump from one block to another. `label` says when it's taken, e.g. `true` or `false` for the two
+/// ways out of a condition; it's `None` for a block that can only go one way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub target: usize,
+    pub label: Option<&'static str>,
+}
+
+pub const ENTRY: usize = 0;
+pub const EXIT: usize = 1;
+
+impl Cfg {
+    /// The blocks that jump to `block`.
+    pub fn predecessors(&self, block: usize) -> Vec<usize> {
+        (0..self.blocks.len()).filter(|&i| self.blocks[i].successors.iter().any(|edge| edge.target == block)).collect()
+    }
+}
+
+/// Builds the control-flow graph of every function and method, and of the top-level code if it has any.
+pub fn build(program: &Program) -> Vec<Cfg> {
+    let mut cfgs = Vec::new();
+    if program.statements.iter().any(runs_at_top_level) {
+        cfgs.push(build_function("main", &program.statements));
+    }
+    for statement in &program.statements {
+        match statement {
+            Statement::FunctionDefinition { name, body, .. } => cfgs.push(build_function(name, body)),
+            Statement::Class { name: class_name, members, .. } => {
+                for member in members {
+                    if let ClassMember::Method(Statement::FunctionDefinition { name, body, .. })
+                        | ClassMember::Constructor(Statement::FunctionDefinition { name, body, .. }) = member {
+                        cfgs.push(build_function(&format!("{}.{}", class_name, name), body));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    cfgs
+}
+
+/// Renders the graphs as one Graphviz digraph, with a cluster for each function.
+pub fn to_dot(cfgs: &[Cfg]) -> String {
+    let mut out = String::from("digraph cfg {\n    node [shape=box, fontname=\"monospace\"];\n");
+    for cfg in cfgs {
+        out += &format!("    subgraph \"cluster_{}\" {{\n        label=\"{}\";\n", escape(&cfg.name), escape(&cfg.name));
+        for (i, block) in cfg.blocks.iter().enumerate() {
+            let title = match i {
+                ENTRY => "entry".to_string(),
+                EXIT => "exit".to_string(),
+                _ => format!("block {}", i),
+            };
+            let label: String = std::iter::once(title).chain(block.statements.iter().cloned())
+                .map(|line| format!("{}\\l", escape(&line)))
+                .collect();
+            out += &format!("        \"{}:{}\" [label=\"{}\"];\n", escape(&cfg.name), i, label);
+        }
+        for (i, block) in cfg.blocks.iter().enumerate() {
+            for edge in &block.successors {
+                let label = edge.label.map(|label| format!(" [label=\"{}\"]", label)).unwrap_or_default();
+                out += &format!("        \"{0}:{1}\" -> \"{0}:{2}\"{3};\n", escape(&cfg.name), i, edge.target, label);
+            }
+        }
+        out += "    }\n";
+    }
+    out += "}\n";
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Whether a top-level statement is code that runs, rather than a definition.
+fn runs_at_top_level(statement: &Statement) -> bool {
+    !matches!(statement, Statement::FunctionDefinition { .. } | Statement::Class { .. } | Statement::Enum { .. } | Statement::Import(_) | Statement::StaticAssert { .. })
+}
+
+fn build_function(name: &str, body: &[Statement]) -> Cfg {
+    let mut builder = Builder { blocks: vec![BasicBlock::default(), BasicBlock::default()], loops: Vec::new() };
+    if let Some(end) = builder.build_block(body, Some(ENTRY)) {
+        builder.edge(end, EXIT, None);
+    }
+    Cfg { name: name.to_string(), blocks: builder.blocks }
+}
+
+/// A loop that `break` and `continue` can jump out of.
+struct Loop {
+    label: Option<String>,
+    /// Where `continue` goes: the block that checks the condition.
+    header: usize,
+    /// Where `break` goes: the block after the loop (and after its `else` block).
+    after: usize,
+}
+
+struct Builder {
+    blocks: Vec<BasicBlock>,
+    loops: Vec<Loop>,
+}
+
+impl Builder {
+    fn new_block(&mut self) -> usize {
+        self.blocks.push(BasicBlock::default());
+        self.blocks.len() - 1
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: Option<&'static str>) {
+        self.blocks[from].successors.push(Edge { target: to, label });
+    }
+
+    /// Adds a block's statements starting in `current`, and returns the block control is in after
+    /// the last one, or `None` if the end can't be reached (e.g. it's after a `return`).
+    fn build_block(&mut self, statements: &[Statement], mut current: Option<usize>) -> Option<usize> {
+        for statement in statements {
+            // Code that can't be reached still gets a block, with nothing leading to it.
+            let block = match current {
+                Some(block) => block,
+                None => self.new_block(),
+            };
+            current = self.build_statement(statement, block);
+        }
+        current
+    }
+
+    fn build_statement(&mut self, statement: &Statement, current: usize) -> Option<usize> {
+        if let Some(description) = describe(statement) {
+            self.blocks[current].statements.push(description);
+        }
+        match statement {
+            Statement::If { consequence, alternative, .. } => {
+                let then_block = self.new_block();
+                self.edge(current, then_block, Some("true"));
+                let then_end = self.build_block(consequence, Some(then_block));
+                let else_end = match alternative {
+                    Some(alternative) => {
+                        let else_block = self.new_block();
+                        self.edge(current, else_block, Some("false"));
+                        self.build_block(alternative, Some(else_block))
+                    }
+                    None => None,
+                };
+                if then_end.is_none() && else_end.is_none() && alternative.is_some() {
+                    return None;
+                }
+                let merge = self.new_block();
+                if alternative.is_none() {
+                    self.edge(current, merge, Some("false"));
+                }
+                for end in [then_end, else_end].into_iter().flatten() {
+                    self.edge(end, merge, None);
+                }
+                Some(merge)
+            }
+            Statement::While { label, body, else_block, .. } | Statement::For { label, body, else_block, .. }
+            | Statement::ForEach { label, body, else_block, .. } => {
+                // The condition gets a block of its own, since every iteration jumps back to it.
+                let header = self.new_block();
+                let condition = self.blocks[current].statements.pop().expect("the loop was just described");
+                self.blocks[header].statements.push(condition);
+                self.edge(current, header, None);
+                let body_block = self.new_block();
+                self.edge(header, body_block, Some("true"));
+                let after = self.new_block();
+                self.loops.push(Loop { label: label.clone(), header, after });
+                if let Some(end) = self.build_block(body, Some(body_block)) {
+                    self.edge(end, header, None);
+                }
+                self.loops.pop();
+                match else_block {
+                    Some(else_block) => {
+                        let else_start = self.new_block();
+                        self.edge(header, else_start, Some("false"));
+                        if let Some(end) = self.build_block(else_block, Some(else_start)) {
+                            self.edge(end, after, None);
+                        }
+                    }
+                    None => self.edge(header, after, Some("false")),
+                }
+                Some(after)
+            }
+            // An exception can be thrown anywhere in the `try` block; the edge from its start stands for all of them.
+            Statement::TryCatch { try_block, catch_block, .. } => {
+                let try_start = self.new_block();
+                self.edge(current, try_start, None);
+                let try_end = self.build_block(try_block, Some(try_start));
+                let catch_start = self.new_block();
+                self.edge(try_start, catch_start, Some("throw"));
+                let catch_end = self.build_block(catch_block, Some(catch_start));
+                if try_end.is_none() && catch_end.is_none() {
+                    return None;
+                }
+                let merge = self.new_block();
+                for end in [try_end, catch_end].into_iter().flatten() {
+                    self.edge(end, merge, None);
+                }
+                Some(merge)
+            }
+            Statement::Return { .. } => {
+                self.edge(current, EXIT, None);
+                None
+            }
+            Statement::Break { label, .. } | Statement::Continue { label, .. } => {
+                // Semantic analysis has already checked that the loop exists.
+                let target = self.loops.iter().rev().find(|l| label.is_none() || l.label == *label).map(|l| {
+                    if matches!(statement, Statement::Break { .. }) { l.after } else { l.header }
+                });
+                if let Some(target) = target {
+                    self.edge(current, target, None);
+                }
+                None
+            }
+            _ => Some(current),
+        }
+    }
+}
+
+/// A one-line description of a statement for its block, like `3: var total` or `5: if`.
+fn describe(statement: &Statement) -> Option<String> {
+    let (span, text) = match statement {
+        Statement::Declaration { is_mutable, name, span, .. } => (span, format!("{} {}", if *is_mutable { "var" } else { "val" }, name)),
+        Statement::Destructure { is_mutable, names, span, .. } => (span, format!("{} ({})", if *is_mutable { "var" } else { "val" }, names.join(", "))),
+        Statement::Assignment { targets, op, span, .. } => {
+            let targets: Vec<String> = targets.iter().map(describe_target).collect();
+            let assign = op.as_ref().map_or("=".to_string(), |op| format!("{}=", op));
+            (span, format!("{} {} ...", targets.join(" = "), assign))
+        }
+        Statement::Print { stream, span, .. } => (span, match stream { Stream::Stdout => "print(...)", Stream::Stderr => "eprint(...)" }.to_string()),
+        Statement::Expression { expr: Expression::Call { callee, .. }, span } => (span, format!("{}(...)", describe_target(callee))),
+        Statement::Expression { span, .. } => (span, "expression".to_string()),
+        Statement::Return { value, span } => (span, if value.is_some() { "return ..." } else { "return" }.to_string()),
+        Statement::If { span, .. } => (span, "if".to_string()),
+        Statement::While { span, .. } => (span, "while".to_string()),
+        Statement::For { iterator, span, .. } | Statement::ForEach { iterator, span, .. } => (span, format!("for {}", iterator)),
+        Statement::Break { label, span } => (span, label.as_ref().map_or("break".to_string(), |label| format!("break {}", label))),
+        Statement::Continue { label, span } => (span, label.as_ref().map_or("continue".to_string(), |label| format!("continue {}", label))),
+        // `try` has no position of its own, and the rest don't run.
+        _ => return None,
+    };
+    Some(format!("{}: {}", span.line, text))
+}
+
+/// The source spelling of what's assigned to or called, when it's a name or a member of one.
+fn describe_target(expr: &Expression) -> String {
+    match expr {
+        Expression::Identifier(name) => name.clone(),
+        Expression::This => "this".to_string(),
+        Expression::Get { object, name } => format!("{}.{}", describe_target(object), name),
+        Expression::Index { list, .. } => format!("{}[...]", describe_target(list)),
+        _ => "...".to_string(),
+    }
+}
@@ -1,4 +1,7 @@
-use crate::ast::{Program, Statement, Expression, Literal, ClassMember, Type};
+use crate::ast::{Program, Statement, Expression, AssignTarget, Literal, ClassMember, EnumVariant, Type, BinaryOperator, UnaryOperator, Param, Stream, DISCARD};
+use crate::dialect::Features;
+use crate::lexer::is_keyword;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::path::Path;
 
@@ -8,6 +11,92 @@ pub enum GenMode {
     Cpp,
 }
 
+/// How the `/` operator behaves on integers.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum IntDivMode {
+    /// Truncate toward zero, like C++ (`-7 / 2 == -3`).
+    #[default]
+    Trunc,
+    /// Round toward negative infinity, like Python (`-7 / 2 == -4`).
+    Floor,
+}
+
+/// Options that change the shape of the generated code without changing the input program.
+#[derive(Debug, Clone, Default)]
+pub struct CodegenOptions {
+    pub int_div: IntDivMode,
+    /// Mark functions that only do pure arithmetic as `constexpr`.
+    pub constexpr_functions: bool,
+    /// Run the AST optimizer (`-O`) before generating code. See `optimizer::optimize`.
+    pub optimize: bool,
+    /// Emit `#` comments written above a `def` as Doxygen `/** ... */` comments.
+    pub emit_docs: bool,
+    /// Convert the other side of `+` between a string and a plain value with `to_string` (`--auto-stringify`).
+    pub auto_stringify: bool,
+    /// Mark functions and methods that return a value `[[nodiscard]]` (`--nodiscard`).
+    pub nodiscard: bool,
+    /// Mark functions that can't throw `noexcept` (`--noexcept`).
+    pub noexcept: bool,
+    /// Don't generate a `main` (`--no-main-wrapper`), for code that's linked into another program.
+    /// Top-level variables become globals, and any other top-level code is an error.
+    pub no_main_wrapper: bool,
+    /// The target named with `--target`, which `@if TARGET == "..."` tests; `cpp` when it's `None`.
+    /// The generated code is C++ either way.
+    pub target: Option<String>,
+    /// The syntax the program may use (`--dialect`). Code is generated the same way whatever it is.
+    pub features: Features,
+}
+
+/// Program-wide facts the generator needs while emitting individual statements.
+struct Context<'a> {
+    options: &'a CodegenOptions,
+    /// Functions that are emitted as `constexpr`.
+    constexpr_functions: HashSet<String>,
+    /// Functions that are emitted as `noexcept`.
+    noexcept_functions: HashSet<String>,
+    /// Enums defined in the program, whose variants are reached with `::` instead of `->`.
+    enums: HashSet<String>,
+    /// Locals of the function being generated, by source name, innermost block last.
+    locals: Vec<HashMap<String, Local>>,
+    /// Return type of the function being generated.
+    return_type: Option<Type>,
+    /// Names for the temporaries of the function being generated.
+    temps: TempAllocator,
+}
+
+/// What every temporary's name starts with. `cpp_name` keeps user names from starting with it.
+const TEMP_PREFIX: &str = "_rl_tmp";
+
+/// Hands out the names of the temporaries that lowering introduces, like the flag that tells a loop's
+/// `else` block whether the loop finished: `_rl_tmp0`, `_rl_tmp1`, and so on. Each function starts
+/// counting from 0, so the names only depend on the function itself.
+#[derive(Default)]
+struct TempAllocator {
+    next: usize,
+}
+
+impl TempAllocator {
+    fn fresh(&mut self) -> String {
+        let name = format!("{}{}", TEMP_PREFIX, self.next);
+        self.next += 1;
+        name
+    }
+}
+
+/// A local variable, parameter, or loop/`catch` variable of the function being generated.
+struct Local {
+    /// `None` for a name that doesn't have a REDLINE type, like a `catch` variable.
+    data_type: Option<Type>,
+    /// The name it's emitted as. This differs from the source name when it shadows another local.
+    cpp_name: String,
+}
+
+impl Local {
+    fn new(name: &str, data_type: Option<Type>) -> Self {
+        Self { data_type, cpp_name: cpp_name(name) }
+    }
+}
+
 #[derive(Debug)]
 pub struct CodegenError {
     pub message: String,
@@ -19,14 +108,34 @@ impl fmt::Display for CodegenError {
     }
 }
 
-pub fn generate(program: &Program, mode: GenMode, module_name: &str) -> Result<String, CodegenError> {
+pub fn generate(program: &Program, mode: GenMode, module_name: &str, options: &CodegenOptions) -> Result<String, CodegenError> {
+    let mut ctx = Context {
+        options,
+        constexpr_functions: if options.constexpr_functions { find_constexpr_functions(program) } else { HashSet::new() },
+        noexcept_functions: if options.noexcept { find_noexcept_functions(program) } else { HashSet::new() },
+        enums: program.statements.iter()
+            .filter_map(|s| match s { Statement::Enum { name, .. } => Some(name.clone()), _ => None })
+            .collect(),
+        locals: Vec::new(),
+        return_type: None,
+        temps: TempAllocator::default(),
+    };
     if mode == GenMode::Hpp {
-        return generate_hpp(program, module_name);
+        return generate_hpp(program, module_name, &ctx);
     }
 
     // --- C++ Generation ---
     let mut cpp_code = String::new();
-    let has_main = program.statements.iter().any(|s| !matches!(s, Statement::FunctionDefinition { .. } | Statement::Import(_) | Statement::Class { .. }));
+    let runs_at_top_level = |s: &&Statement| !matches!(s, Statement::FunctionDefinition { .. } | Statement::Import(_) | Statement::Class { .. } | Statement::Enum { .. } | Statement::StaticAssert { .. });
+    if options.no_main_wrapper {
+        if let Some(stray) = program.statements.iter().filter(runs_at_top_level).find(|s| !matches!(s, Statement::Declaration { .. } | Statement::Destructure { .. })) {
+            let at = statement_line(stray).map(|line| format!(" at line {}", line)).unwrap_or_default();
+            return Err(CodegenError {
+                message: format!("--no-main-wrapper leaves top-level code nowhere to run, but there's some{}; move it into a function", at),
+            });
+        }
+    }
+    let has_main = !options.no_main_wrapper && program.statements.iter().any(|s| runs_at_top_level(&s));
 
     // Includes
     let mut includes = format!("// Generated by REDLINE Core for module {}\n", module_name);
@@ -53,20 +162,28 @@ pub fn generate(program: &Program, mode: GenMode, module_name: &str) -> Result<S
     cpp_code.push_str("\nnamespace rl {\n\n");
     for stmt in &program.statements {
         match stmt {
-            Statement::FunctionDefinition { .. } => {
-                cpp_code.push_str(&generate_statement(stmt, 0, mode, None)?);
-                cpp_code.push_str("\n");
+            Statement::Declaration { .. } | Statement::Destructure { .. } if options.no_main_wrapper => {
+                cpp_code.push_str(&generate_statement(stmt, 0, mode, &mut ctx, None)?);
+                cpp_code.push('\n');
+            }
+            Statement::FunctionDefinition { is_public, name, params, return_type, .. } => {
+                cpp_code.push_str(&generate_statement(stmt, 0, mode, &mut ctx, None)?);
+                // Public functions get their variadic wrapper in the header instead.
+                if !is_public {
+                    cpp_code.push_str(&generate_variadic_wrapper(name, params, return_type, &ctx));
+                }
+                cpp_code.push('\n');
             }
             Statement::Class { name, members, .. } => {
                 for member in members {
                     match member {
                         ClassMember::Method(method_stmt) => {
-                            cpp_code.push_str(&generate_statement(method_stmt, 0, mode, Some(name))?);
-                            cpp_code.push_str("\n");
+                            cpp_code.push_str(&generate_statement(method_stmt, 0, mode, &mut ctx, Some(name))?);
+                            cpp_code.push('\n');
                         }
                         ClassMember::Constructor(constructor_stmt) => {
-                            cpp_code.push_str(&generate_statement(constructor_stmt, 0, mode, Some(name))?);
-                            cpp_code.push_str("\n");
+                            cpp_code.push_str(&generate_statement(constructor_stmt, 0, mode, &mut ctx, Some(name))?);
+                            cpp_code.push('\n');
                         }
                         _ => {}
                     }
@@ -84,7 +201,7 @@ pub fn generate(program: &Program, mode: GenMode, module_name: &str) -> Result<S
         cpp_code.push_str("    std::ios_base::sync_with_stdio(false);\n");
         cpp_code.push_str("    std::cin.tie(NULL);\n\n");
         cpp_code.push_str("    using namespace rl;\n");
-        let main_body = generate_block(&program.statements, 1, mode)?;
+        let main_body = generate_block(&program.statements, 1, mode, &mut ctx)?;
         cpp_code.push_str(&main_body);
         cpp_code.push_str("    return 0;\n}\n");
     }
@@ -92,7 +209,7 @@ pub fn generate(program: &Program, mode: GenMode, module_name: &str) -> Result<S
     Ok(cpp_code)
 }
 
-fn generate_hpp(program: &Program, module_name: &str) -> Result<String, CodegenError> {
+fn generate_hpp(program: &Program, module_name: &str, ctx: &Context) -> Result<String, CodegenError> {
     let mut hpp_code = String::new();
     let guard = format!("RL_{}_H", module_name.to_uppercase());
 
@@ -106,9 +223,17 @@ fn generate_hpp(program: &Program, module_name: &str) -> Result<String, CodegenE
     hpp_code.push_str("#include \"stdlib/rl_string.hpp\"\n");
     hpp_code.push_str("#include \"stdlib/rl_random.hpp\"\n");
     hpp_code.push_str("#include \"stdlib/rl_time.hpp\"\n");
+    hpp_code.push_str("#include \"stdlib/rl_any.hpp\"\n");
     hpp_code.push_str("#include <string>\n#include <vector>\n\n");
     hpp_code.push_str("namespace rl {\n\n");
 
+    // Enums come first, since classes and functions can use them.
+    for stmt in &program.statements {
+        if let Statement::Enum { name, variants, .. } = stmt {
+            hpp_code.push_str(&generate_enum(name, variants));
+        }
+    }
+
     for stmt in &program.statements {
         if let Statement::Class { name, members, .. } = stmt {
             hpp_code.push_str(&format!("class {} {{\n", name));
@@ -116,16 +241,21 @@ fn generate_hpp(program: &Program, module_name: &str) -> Result<String, CodegenE
             for member in members {
                 match member {
                     ClassMember::Variable(Statement::Declaration { name, data_type, initializer, .. }) => {
-                        let initial_value = generate_expression(initializer)?;
-                        hpp_code.push_str(&format!("    {} {} = {};\n", data_type.to_string(), name, initial_value));
+                        match initializer {
+                            Some(initializer) => {
+                                let initial_value = generate_expression(initializer, ctx)?;
+                                hpp_code.push_str(&format!("    {} {} = {};\n", data_type, cpp_name(name), initial_value));
+                            }
+                            None => hpp_code.push_str(&format!("    {} {}{{}};\n", data_type, cpp_name(name))),
+                        }
                     }
                     ClassMember::Method(Statement::FunctionDefinition { name, params, return_type, .. }) => {
-                        let param_str: Vec<String> = params.iter().map(|(n, t)| format!("{} {}", t.to_string(), n)).collect();
-                        hpp_code.push_str(&format!("    {} {}({});\n", return_type.to_string(), name, param_str.join(", ")));
+                        let param_str = generate_params(params);
+                        hpp_code.push_str(&format!("    {}{} {}({});\n", nodiscard(return_type, ctx), return_type, cpp_name(name), param_str));
                     }
                     ClassMember::Constructor(Statement::FunctionDefinition { params, .. }) => {
-                        let param_str: Vec<String> = params.iter().map(|(n, t)| format!("{} {}", t.to_string(), n)).collect();
-                        hpp_code.push_str(&format!("    {}({});\n", name, param_str.join(", ")));
+                        let param_str = generate_params(params);
+                        hpp_code.push_str(&format!("    {}({});\n", name, param_str));
                     }
                     _ => {}
                 }
@@ -133,8 +263,9 @@ fn generate_hpp(program: &Program, module_name: &str) -> Result<String, CodegenE
             hpp_code.push_str("};\n\n");
         }
         if let Statement::FunctionDefinition { is_public: true, name, params, return_type, .. } = stmt {
-            let param_str: Vec<String> = params.iter().map(|(n, t)| format!("{} {}", t.to_string(), n)).collect();
-            hpp_code.push_str(&format!("{} {}({});\n", return_type.to_string(), name, param_str.join(", ")));
+            let param_str = generate_params(params);
+            hpp_code.push_str(&format!("{}{} {}({}){};\n", nodiscard(return_type, ctx), return_type, cpp_name(name), param_str, noexcept(name, ctx)));
+            hpp_code.push_str(&generate_variadic_wrapper(name, params, return_type, ctx));
         }
     }
 
@@ -143,114 +274,741 @@ fn generate_hpp(program: &Program, module_name: &str) -> Result<String, CodegenE
     Ok(hpp_code)
 }
 
-fn generate_block(statements: &[Statement], indent_level: usize, mode: GenMode) -> Result<String, CodegenError> {
+/// The line a statement starts on. A `try` doesn't record its own, so it's the line of its first statement.
+fn statement_line(statement: &Statement) -> Option<usize> {
+    match statement {
+        Statement::Declaration { span, .. } | Statement::Destructure { span, .. } | Statement::Assignment { span, .. } | Statement::If { span, .. }
+        | Statement::While { span, .. } | Statement::For { span, .. } | Statement::ForEach { span, .. } | Statement::Print { span, .. }
+        | Statement::Expression { span, .. } | Statement::FunctionDefinition { span, .. } | Statement::Return { span, .. }
+        | Statement::Class { span, .. } | Statement::Enum { span, .. } | Statement::Break { span, .. }
+        | Statement::Continue { span, .. } | Statement::StaticAssert { span, .. } => Some(span.line),
+        Statement::TryCatch { try_block, .. } => try_block.first().and_then(statement_line),
+        Statement::Import(_) => None,
+    }
+}
+
+/// Generates an `enum class` with each variant's value, plus `to_string` and `print` overloads that
+/// give the variant's name.
+fn generate_enum(name: &str, variants: &[EnumVariant]) -> String {
+    let mut code = format!("enum class {} {{\n", name);
+    for variant in variants {
+        code.push_str(&format!("    {} = {},\n", cpp_name(&variant.name), variant.value));
+    }
+    code.push_str("};\n\n");
+    code.push_str(&format!("inline std::string to_string({} value) {{\n    switch (value) {{\n", name));
+    for variant in variants {
+        code.push_str(&format!("        case {}::{}: return \"{}\";\n", name, cpp_name(&variant.name), variant.name));
+    }
+    code.push_str("    }\n    return std::to_string(static_cast<int>(value));\n}\n\n");
+    code.push_str(&format!(
+        "inline void print({} value, std::ostream& out = std::cout) {{\n    print(to_string(value), out);\n}}\n\n",
+        name
+    ));
+    code
+}
+
+/// C++ keywords (including the alternative operator spellings) that can't be used as names.
+const CPP_KEYWORDS: &[&str] = &[
+    "alignas", "alignof", "and", "and_eq", "asm", "auto", "bitand", "bitor", "bool", "break", "case",
+    "catch", "char", "char16_t", "char32_t", "class", "compl", "const", "const_cast", "constexpr",
+    "continue", "decltype", "default", "delete", "do", "double", "dynamic_cast", "else", "enum",
+    "explicit", "export", "extern", "false", "float", "for", "friend", "goto", "if", "inline", "int",
+    "long", "mutable", "namespace", "new", "noexcept", "not", "not_eq", "nullptr", "operator", "or",
+    "or_eq", "private", "protected", "public", "register", "reinterpret_cast", "return", "short",
+    "signed", "sizeof", "static", "static_assert", "static_cast", "struct", "switch", "template",
+    "this", "thread_local", "throw", "true", "try", "typedef", "typeid", "typename", "union",
+    "unsigned", "using", "virtual", "void", "volatile", "wchar_t", "while", "xor", "xor_eq",
+];
+
+/// The C++ spelling of a variable, parameter, function, or member name.
+/// Names that are REDLINE keywords (only reachable as raw identifiers like `r#if`) or C++ keywords
+/// get an `rl_` prefix so they can't clash with the language or with REDLINE's own builtins, and so do
+/// names that look like the compiler's temporaries.
+fn cpp_name(name: &str) -> String {
+    if is_keyword(name) || CPP_KEYWORDS.contains(&name) || name.starts_with(TEMP_PREFIX) {
+        format!("rl_{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Spells a string as a C++ string literal, escaping anything that can't appear in one as-is.
+fn cpp_string_literal(s: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            // Octal escapes stop after three digits, unlike hex ones, so they can't swallow what follows.
+            c if c.is_ascii_control() => literal.push_str(&format!("\\{:03o}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Spells a character as a C++ `char32_t` literal, e.g. `U'A'`.
+fn cpp_char_literal(c: char) -> String {
+    match c {
+        '\'' => "U'\\''".to_string(),
+        '\\' => "U'\\\\'".to_string(),
+        c if c.is_ascii_graphic() || c == ' ' => format!("U'{}'", c),
+        c => format!("U'\\U{:08X}'", c as u32),
+    }
+}
+
+/// Formats a doc comment as a Doxygen block, e.g. `/**\n * Adds two numbers.\n */`.
+fn generate_doc_comment(doc: &str, indent: &str) -> String {
+    let mut code = format!("{}/**\n", indent);
+    for line in doc.lines() {
+        if line.is_empty() {
+            code.push_str(&format!("{} *\n", indent));
+        } else {
+            // A `*/` inside the comment would end it early.
+            code.push_str(&format!("{} * {}\n", indent, line.replace("*/", "* /")));
+        }
+    }
+    code.push_str(&format!("{} */\n", indent));
+    code
+}
+
+fn generate_params(params: &[Param]) -> String {
+    params.iter().map(|p| format!("{} {}", p.body_type(), cpp_name(&p.name))).collect::<Vec<_>>().join(", ")
+}
+
+/// For a function with a variadic parameter, generates a template overload that accepts the trailing
+/// arguments one by one, packs them into the `std::vector` the real function takes, and forwards the call.
+/// Returns an empty string for other functions.
+fn generate_variadic_wrapper(name: &str, params: &[Param], return_type: &Type, ctx: &Context) -> String {
+    let Some((variadic, fixed)) = params.split_last().filter(|(last, _)| last.is_variadic) else { return String::new() };
+    let name = cpp_name(name);
+    let pack = cpp_name(&variadic.name);
+    let mut wrapper_params: Vec<String> = fixed.iter().map(|p| format!("{} {}", p.data_type, cpp_name(&p.name))).collect();
+    wrapper_params.push(format!("Rest... {}", pack));
+    let mut args: Vec<String> = fixed.iter().map(|p| cpp_name(&p.name)).collect();
+    args.push(format!("{}{{{}...}}", variadic.body_type(), pack));
+    format!(
+        "template<typename... Rest>\n{}{} {}({}) {{\n    return {}({});\n}}\n",
+        nodiscard(return_type, ctx), return_type, name, wrapper_params.join(", "), name, args.join(", ")
+    )
+}
+
+/// The `[[nodiscard]]` that starts the declaration of a function returning `return_type`, if it gets one.
+fn nodiscard(return_type: &Type, ctx: &Context) -> &'static str {
+    if ctx.options.nodiscard && *return_type != Type::Void { "[[nodiscard]] " } else { "" }
+}
+
+fn noexcept(name: &str, ctx: &Context) -> &'static str {
+    if ctx.noexcept_functions.contains(name) { " noexcept" } else { "" }
+}
+
+fn generate_block(statements: &[Statement], indent_level: usize, mode: GenMode, ctx: &mut Context) -> Result<String, CodegenError> {
     let mut block_code = String::new();
+    ctx.locals.push(HashMap::new());
     for statement in statements {
-        if !matches!(statement, Statement::FunctionDefinition{..} | Statement::Import(_) | Statement::Class{..}) {
-            block_code.push_str(&generate_statement(statement, indent_level, mode, None)?);
+        if !matches!(statement, Statement::FunctionDefinition{..} | Statement::Import(_) | Statement::Class{..} | Statement::Enum{..}) {
+            block_code.push_str(&generate_statement(statement, indent_level, mode, ctx, None)?);
         }
     }
+    ctx.locals.pop();
     Ok(block_code)
 }
 
-fn generate_statement(statement: &Statement, indent_level: usize, mode: GenMode, class_scope: Option<&str>) -> Result<String, CodegenError> {
+/// Records a local of the function being generated in the innermost block and returns its C++ name.
+/// C++ doesn't allow redeclaring a name in the same block (or a parameter in the function's outermost
+/// block), and `int x = x + 1;` would read the new, uninitialized `x`. So a name that's already taken
+/// by a visible local gets a numbered suffix (`x_1`), and the outer one is used again once the block ends.
+fn declare_local(ctx: &mut Context, name: &str, data_type: Option<Type>) -> String {
+    let base = cpp_name(name);
+    let is_taken = |candidate: &str| ctx.locals.iter().flat_map(|scope| scope.values()).any(|local| local.cpp_name == candidate);
+    let mut renamed = base.clone();
+    let mut suffix = 1;
+    while is_taken(&renamed) {
+        renamed = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    if let Some(scope) = ctx.locals.last_mut() {
+        scope.insert(name.to_string(), Local { data_type, cpp_name: renamed.clone() });
+    }
+    renamed
+}
+
+/// The innermost local with this source name, if any.
+fn lookup_local<'c>(ctx: &'c Context, name: &str) -> Option<&'c Local> {
+    ctx.locals.iter().rev().find_map(|scope| scope.get(name))
+}
+
+/// Generates the list, string, or dict being indexed or sliced. A string literal is a `const char*` in
+/// C++, which has no `.at()` and doesn't match the `rl::` overloads, so it's made a `std::string` first.
+fn generate_container(expr: &Expression, ctx: &Context) -> Result<String, CodegenError> {
+    match expr {
+        Expression::Literal(Literal::String(s)) => Ok(format!("std::string({})", cpp_string_literal(s))),
+        _ => generate_expression(expr, ctx),
+    }
+}
+
+/// Generates the place an assignment stores into. Storing to a key a dict doesn't have yet adds it,
+/// where reading one throws, so an element of anything not known to be a list is stored through
+/// `rl::store_at` rather than read with `rl::index_at`.
+fn generate_assign_target(target: &Expression, ctx: &Context) -> Result<String, CodegenError> {
+    match target.assign_target() {
+        Some(AssignTarget::Element { container, index }) if !matches!(known_type(container, ctx), Some(Type::List(_))) => {
+            Ok(format!("rl::store_at({}, {})", generate_container(container, ctx)?, generate_expression(index, ctx)?))
+        }
+        _ => generate_expression(target, ctx),
+    }
+}
+
+/// Generates the value of a `return`. A returned local is about to go out of scope, so nothing can
+/// observe it afterwards and it's safe to move from. C++ already moves a returned local whose type
+/// matches the return type, but when it has to be converted (a `string` returned as `any`) it is
+/// copied instead, so those get an explicit `std::move`. Anything else (globals, members, other
+/// expressions, and cheap types like `int`) is returned by value as written.
+fn generate_return_value(expr: &Expression, ctx: &Context) -> Result<String, CodegenError> {
+    let value = generate_expression(expr, ctx)?;
+    let Expression::Identifier(name) = expr else { return Ok(value) };
+    let local_type = lookup_local(ctx, name).and_then(|local| local.data_type.as_ref());
+    match (local_type, &ctx.return_type) {
+        (Some(local_type), Some(return_type)) if local_type != return_type && is_movable_type(local_type) => {
+            Ok(format!("std::move({})", value))
+        }
+        _ => Ok(value),
+    }
+}
+
+/// `target op= value`, using C++'s own compound assignment, so the target is only evaluated once. Under
+/// `--int-div floor`, `/=` and `%=` go through helpers that floor the same way `/` and `%` do.
+fn generate_compound_assignment(target: &Expression, op: &BinaryOperator, value: &Expression, ctx: &Context) -> Result<String, CodegenError> {
+    let target_code = generate_assign_target(target, ctx)?;
+    let value_code = match op {
+        BinaryOperator::Add => generate_concat_operand(value, known_type(value, ctx).as_ref(), known_type(target, ctx).as_ref(), ctx)?,
+        _ => generate_arithmetic_operand(value, ctx)?,
+    };
+    match op {
+        BinaryOperator::Divide if ctx.options.int_div == IntDivMode::Floor => Ok(format!("rl::floor_div_assign({}, {})", target_code, value_code)),
+        BinaryOperator::Modulo if ctx.options.int_div == IntDivMode::Floor => Ok(format!("rl::floor_mod_assign({}, {})", target_code, value_code)),
+        _ => Ok(format!("{} {}= {}", target_code, op, value_code)),
+    }
+}
+
+/// The single value a `print` passes to `rl::print`. Several values are each converted with `to_string`
+/// and joined with the separator; they're listed in braces, which C++ evaluates left to right.
+fn generate_print_value(values: &[Expression], sep: Option<&str>, ctx: &Context) -> Result<String, CodegenError> {
+    if let [value] = values {
+        return generate_expression(value, ctx);
+    }
+    let parts = values.iter()
+        .map(|value| Ok(format!("rl::to_string({})", generate_expression(value, ctx)?)))
+        .collect::<Result<Vec<_>, CodegenError>>()?;
+    Ok(format!("rl::join({{ {} }}, {})", parts.join(", "), cpp_string_literal(sep.unwrap_or(" "))))
+}
+
+/// An operand of `+` under `--auto-stringify`: a plain value added to a string is converted to one first.
+fn generate_concat_operand(expr: &Expression, own_type: Option<&Type>, other_type: Option<&Type>, ctx: &Context) -> Result<String, CodegenError> {
+    let value = generate_expression(expr, ctx)?;
+    match (own_type, other_type) {
+        (Some(own_type), Some(Type::String)) if own_type.is_scalar() => Ok(format!("rl::to_string({})", value)),
+        _ => generate_arithmetic_operand(expr, ctx),
+    }
+}
+
+/// An operand of `+`, `-`, `*`, or `/`. An `enum class` doesn't convert to `int` on its own, so an
+/// enum value used in arithmetic is cast to its discriminant.
+fn generate_arithmetic_operand(expr: &Expression, ctx: &Context) -> Result<String, CodegenError> {
+    let value = generate_expression(expr, ctx)?;
+    match known_type(expr, ctx) {
+        Some(Type::Enum(_)) => Ok(format!("static_cast<int>({})", value)),
+        _ => Ok(value),
+    }
+}
+
+/// The type of an expression when it's evident without a type checker: literals, typed locals,
+/// enum variants, `to_string` and `align_*` calls, string concatenations, and tuples of these.
+fn known_type(expr: &Expression, ctx: &Context) -> Option<Type> {
+    match expr {
+        Expression::Literal(literal) => literal.data_type(),
+        Expression::Identifier(name) => lookup_local(ctx, name).and_then(|local| local.data_type.clone()),
+        Expression::Get { object, .. } => match &**object {
+            Expression::Identifier(enum_name) if ctx.enums.contains(enum_name) && lookup_local(ctx, enum_name).is_none() => {
+                Some(Type::Enum(enum_name.clone()))
+            }
+            _ => None,
+        },
+        Expression::Call { callee, .. } if matches!(&**callee, Expression::Identifier(name) if matches!(name.as_str(), "to_string" | "align_left" | "align_right")) => {
+            Some(Type::String)
+        }
+        Expression::Call { callee, .. } if matches!(&**callee, Expression::Identifier(name) if name == "range") => Some(Type::List(Box::new(Type::Int))),
+        Expression::Unary { op: UnaryOperator::Negate, operand } => known_type(operand, ctx),
+        Expression::TupleLiteral(elements) => elements.iter().map(|element| known_type(element, ctx)).collect::<Option<_>>().map(Type::Tuple),
+        Expression::BinaryOp { op: BinaryOperator::Add, left, right }
+            if known_type(left, ctx) == Some(Type::String) || known_type(right, ctx) == Some(Type::String) => Some(Type::String),
+        _ => None,
+    }
+}
+
+/// The strings joined by a chain of at least `MIN_CONCAT_PARTS` strings added together, like an f-string
+/// with a few `{}`s, or `None` if it's shorter or not every piece is known to be a `string`. Each `+`
+/// in C++ makes a new string, so a long chain is generated as one `rl::concat` of its pieces instead.
+fn string_chain<'e>(expr: &'e Expression, ctx: &Context) -> Option<Vec<&'e Expression>> {
+    fn collect<'e>(expr: &'e Expression, parts: &mut Vec<&'e Expression>) {
+        match expr {
+            Expression::BinaryOp { op: BinaryOperator::Add, left, right } => {
+                collect(left, parts);
+                collect(right, parts);
+            }
+            _ => parts.push(expr),
+        }
+    }
+    let mut parts = Vec::new();
+    collect(expr, &mut parts);
+    (parts.len() >= MIN_CONCAT_PARTS && parts.iter().all(|part| known_type(part, ctx) == Some(Type::String))).then_some(parts)
+}
+
+const MIN_CONCAT_PARTS: usize = 3;
+
+/// Types that own heap storage, where a move is cheaper than a copy.
+fn is_movable_type(data_type: &Type) -> bool {
+    matches!(data_type, Type::String | Type::List(_) | Type::Dict(_, _) | Type::Any | Type::Class(_))
+}
+
+fn generate_statement(statement: &Statement, indent_level: usize, mode: GenMode, ctx: &mut Context, class_scope: Option<&str>) -> Result<String, CodegenError> {
     let indent = "    ".repeat(indent_level);
     match statement {
+        Statement::Declaration { name, initializer: Some(initializer), .. } if name == DISCARD => {
+            Ok(format!("{}(void)({});\n", indent, generate_expression(initializer, ctx)?))
+        },
+        Statement::Declaration { name, initializer: None, .. } if name == DISCARD => Ok(String::new()),
         Statement::Declaration { name, data_type, initializer, .. } => {
             let type_str = match data_type {
                 Type::Class(class_name) => format!("std::shared_ptr<{}>", class_name),
-                Type::Dict(key, value) => format!("std::map<{}, {}>", key.to_string(), value.to_string()),
+                Type::Dict(key, value) => format!("std::map<{}, {}>", key, value),
                 _ => data_type.to_string(),
             };
-            Ok(format!("{}{} {} = {};\n", indent, type_str, name, generate_expression(initializer)?))
+            let value = initializer.as_ref().map(|initializer| generate_expression(initializer, ctx)).transpose()?;
+            let name = declare_local(ctx, name, Some(data_type.clone()));
+            match value {
+                Some(value) => Ok(format!("{}{} {} = {};\n", indent, type_str, name, value)),
+                // A `var` declared without a value starts out zero or empty rather than holding garbage.
+                None => Ok(format!("{}{} {}{{}};\n", indent, type_str, name)),
+            }
+        },
+        // A C++17 structured binding. It has to name every element, so a `_` gets a temporary that's never read.
+        Statement::Destructure { names, value, .. } => {
+            let element_types = match known_type(value, ctx) {
+                Some(Type::Tuple(types)) => types.into_iter().map(Some).collect(),
+                _ => vec![None; names.len()],
+            };
+            let value = generate_expression(value, ctx)?;
+            let unused = if names.iter().any(|name| name == DISCARD) { "[[maybe_unused]] " } else { "" };
+            let bindings: Vec<String> = names.iter().zip(element_types)
+                .map(|(name, data_type)| if name == DISCARD { ctx.temps.fresh() } else { declare_local(ctx, name, data_type) })
+                .collect();
+            Ok(format!("{}{}auto [{}] = {};\n", indent, unused, bindings.join(", "), value))
         },
-        Statement::FunctionDefinition { name, params, return_type, body, .. } => {
-            let param_str: Vec<String> = params.iter().map(|(p_name, p_type)| format!("{} {}", p_type.to_string(), p_name)).collect();
+        Statement::FunctionDefinition { name, params, return_type, body, doc, .. } => {
+            let param_str = generate_params(params);
             let mut func_def = String::new();
+            if let (true, Some(doc)) = (ctx.options.emit_docs, doc) {
+                func_def.push_str(&generate_doc_comment(doc, &indent));
+            }
             if let Some(class_name) = class_scope {
                 if name == "init" {
-                    func_def.push_str(&format!("{}::{}({}) {{\n", class_name, class_name, param_str.join(", ")));
+                    func_def.push_str(&format!("{}::{}({}) {{\n", class_name, class_name, param_str));
                 } else {
-                    func_def.push_str(&format!("{} {}::{}({}) {{\n", return_type.to_string(), class_name, name, param_str.join(", ")));
+                    func_def.push_str(&format!("{} {}::{}({}) {{\n", return_type, class_name, cpp_name(name), param_str));
                 }
             } else {
-                func_def.push_str(&format!("{} {}({}) {{\n", return_type.to_string(), name, param_str.join(", ")));
+                let constexpr = if ctx.constexpr_functions.contains(name) { "constexpr " } else { "" };
+                func_def.push_str(&format!("{}{}{} {}({}){} {{\n", nodiscard(return_type, ctx), constexpr, return_type, cpp_name(name), param_str, noexcept(name, ctx)));
             }
-            func_def.push_str(&generate_block(body, indent_level + 1, mode)?);
+            // The body only sees its own parameters and locals.
+            let params_scope = params.iter().map(|p| (p.name.clone(), Local::new(&p.name, Some(p.body_type())))).collect();
+            let outer_locals = std::mem::replace(&mut ctx.locals, vec![params_scope]);
+            let outer_return_type = ctx.return_type.replace(return_type.clone());
+            let outer_temps = std::mem::take(&mut ctx.temps);
+            func_def.push_str(&generate_block(body, indent_level + 1, mode, ctx)?);
+            ctx.locals = outer_locals;
+            ctx.return_type = outer_return_type;
+            ctx.temps = outer_temps;
             func_def.push_str(&format!("{}}}\n", indent));
             Ok(func_def)
         },
-        Statement::Assignment { target, value } => Ok(format!("{}{} = {};\n", indent, generate_expression(target)?, generate_expression(value)?)),
-        Statement::Print(expr) => Ok(format!("{}print({});\n", indent, generate_expression(expr)?)),
-        Statement::Expression(expr) => Ok(format!("{}{};\n", indent, generate_expression(expr)?)),
-        Statement::Return(expr) => {
+        Statement::Assignment { targets, value, .. } if targets.iter().all(Expression::is_discard) => {
+            Ok(format!("{}(void)({});\n", indent, generate_expression(value, ctx)?))
+        },
+        Statement::Assignment { targets, op: Some(op), value, .. } => {
+            Ok(format!("{}{};\n", indent, generate_compound_assignment(&targets[0], op, value, ctx)?))
+        },
+        // A chain becomes nested C++ assignments, `a = b = value`; `_` in it is just left out.
+        Statement::Assignment { targets, value, .. } => {
+            let mut assignment = generate_expression(value, ctx)?;
+            for target in targets.iter().rev().filter(|target| !target.is_discard()) {
+                assignment = format!("{} = {}", generate_assign_target(target, ctx)?, assignment);
+            }
+            Ok(format!("{}{};\n", indent, assignment))
+        },
+        Statement::Print { values, sep, stream, .. } => {
+            let value = generate_print_value(values, sep.as_deref(), ctx)?;
+            match stream {
+                Stream::Stdout => Ok(format!("{}print({});\n", indent, value)),
+                Stream::Stderr => Ok(format!("{}print({}, std::cerr);\n", indent, value)),
+            }
+        },
+        Statement::Expression { expr, .. } => Ok(format!("{}{};\n", indent, generate_expression(expr, ctx)?)),
+        Statement::Return { value: expr, .. } => {
             if let Some(e) = expr {
-                Ok(format!("{}return {};\n", indent, generate_expression(e)?))
+                Ok(format!("{}return {};\n", indent, generate_return_value(e, ctx)?))
+            } else if ctx.return_type.is_none() {
+                // A bare `return` in the top-level code ends the program successfully.
+                Ok(format!("{}return 0;\n", indent))
             } else {
                 Ok(format!("{}return;\n", indent))
             }
         },
-        Statement::If { condition, consequence, alternative } => {
-            let cond_str = generate_expression(condition)?;
+        Statement::If { condition, consequence, alternative, .. } => {
+            let cond_str = generate_expression(condition, ctx)?;
             let mut code = format!("{}if ({}) {{\n", indent, cond_str);
-            code.push_str(&generate_block(consequence, indent_level + 1, mode)?);
+            code.push_str(&generate_block(consequence, indent_level + 1, mode, ctx)?);
             code.push_str(&format!("{}}}\n", indent));
-            if let Some(alt) = alternative {
-                code.push_str(&format!("{}else {{\n", indent));
-                code.push_str(&generate_block(alt, indent_level + 1, mode)?);
-                code.push_str(&format!("{}}}\n", indent));
+            match alternative.as_deref() {
+                // An `elif` is an `else` block holding just the next `if`, which is written as `else if`
+                // rather than nesting another level.
+                Some([chained @ Statement::If { .. }]) => {
+                    ctx.locals.push(HashMap::new());
+                    let chained_code = generate_statement(chained, indent_level, mode, ctx, None)?;
+                    ctx.locals.pop();
+                    code.push_str(&format!("{}else {}", indent, chained_code.trim_start()));
+                },
+                Some(alt) => {
+                    code.push_str(&format!("{}else {{\n", indent));
+                    code.push_str(&generate_block(alt, indent_level + 1, mode, ctx)?);
+                    code.push_str(&format!("{}}}\n", indent));
+                },
+                None => {},
             }
             Ok(code)
         },
-        Statement::While { condition, body } => {
-            let cond_str = generate_expression(condition)?;
-            let mut code = format!("{}while ({}) {{\n", indent, cond_str);
-            code.push_str(&generate_block(body, indent_level + 1, mode)?);
-            code.push_str(&format!("{}}}\n", indent));
+        Statement::While { label, condition, body, else_block, .. } => {
+            let cond_str = generate_expression(condition, ctx)?;
+            let header = |cond: &str| format!("while ({})", cond);
+            generate_loop(header, &cond_str, None, label, body, else_block, indent_level, mode, ctx)
+        },
+        Statement::For { label, iterator, start, end, body, else_block, .. } => {
+            let start_str = generate_expression(start, ctx)?;
+            let end_str = generate_expression(end, ctx)?;
+            ctx.locals.push(HashMap::from([(iterator.clone(), Local::new(iterator, Some(Type::Int)))]));
+            let iterator = cpp_name(iterator);
+            let header = |cond: &str| format!("for (int {} = {}; {}; ++{})", iterator, start_str, cond, iterator);
+            let code = generate_loop(header, &format!("{} < {}", iterator, end_str), None, label, body, else_block, indent_level, mode, ctx)?;
+            ctx.locals.pop();
             Ok(code)
         },
-        Statement::For { iterator, start, end, body } => {
-            let start_str = generate_expression(start)?;
-            let end_str = generate_expression(end)?;
-            let mut code = format!("{}for (int {} = {}; {} < {}; ++{}) {{\n", indent, iterator, start_str, iterator, end_str, iterator);
-            code.push_str(&generate_block(body, indent_level + 1, mode)?);
-            code.push_str(&format!("{}}}\n", indent));
+        Statement::ForEach { label, iterator, iterable, body, else_block, .. } => {
+            let (elements, element_type) = match (iterable, known_type(iterable, ctx)) {
+                // A braced list has no type of its own, so it's given the type of its first element.
+                (Expression::ListLiteral(items), _) => {
+                    let element_type = items.first().and_then(|item| known_type(item, ctx));
+                    let vector = element_type.as_ref().map_or("std::vector".to_string(), |t| format!("std::vector<{}>", t));
+                    (format!("{}{}", vector, generate_expression(iterable, ctx)?), element_type)
+                }
+                (_, Some(Type::String)) => (format!("rl::elements({})", generate_container(iterable, ctx)?), Some(Type::Char)),
+                (_, Some(Type::List(element_type))) => (format!("rl::elements({})", generate_expression(iterable, ctx)?), Some(*element_type)),
+                _ => (format!("rl::elements({})", generate_container(iterable, ctx)?), None),
+            };
+            ctx.locals.push(HashMap::from([(iterator.clone(), Local::new(iterator, element_type))]));
+            let iterator = cpp_name(iterator);
+            let code = match else_block {
+                None => {
+                    let mut code = format!("{}for (const auto& {} : {}) {{\n", indent, iterator, elements);
+                    code.push_str(&generate_loop_body(label, body, indent_level, mode, ctx)?);
+                    code.push_str(&format!("{}}}\n", indent));
+                    code.push_str(&generate_loop_exit_label(label, body, indent_level));
+                    code
+                }
+                // A range-for can't tell running out of elements from a `break`, so with an `else` the
+                // loop steps an iterator through the elements instead, which the condition can test.
+                Some(_) => {
+                    let (range, position) = (ctx.temps.fresh(), ctx.temps.fresh());
+                    let header = |cond: &str| format!("for (auto {} = std::begin({}); {}; ++{})", position, range, cond, position);
+                    let condition = format!("{} != std::end({})", position, range);
+                    let element = format!("const auto& {} = *{};", iterator, position);
+                    let mut code = format!("{}{{\n{}    auto&& {} = {};\n", indent, indent, range, elements);
+                    code.push_str(&generate_loop(header, &condition, Some(&element), label, body, else_block, indent_level + 1, mode, ctx)?);
+                    code.push_str(&format!("{}}}\n", indent));
+                    code
+                }
+            };
+            ctx.locals.pop();
             Ok(code)
         },
         Statement::TryCatch { try_block, catch_var, catch_block } => {
             let mut code = format!("{}try {{\n", indent);
-            code.push_str(&generate_block(try_block, indent_level + 1, mode)?);
-            code.push_str(&format!("{}}} catch (const std::exception& {}) {{\n", indent, catch_var));
-            code.push_str(&generate_block(catch_block, indent_level + 1, mode)?);
+            code.push_str(&generate_block(try_block, indent_level + 1, mode, ctx)?);
+            code.push_str(&format!("{}}} catch (const std::exception& {}) {{\n", indent, cpp_name(catch_var)));
+            ctx.locals.push(HashMap::from([(catch_var.clone(), Local::new(catch_var, None))]));
+            code.push_str(&generate_block(catch_block, indent_level + 1, mode, ctx)?);
+            ctx.locals.pop();
             code.push_str(&format!("{}}}\n", indent));
             Ok(code)
         },
-        Statement::Break => Ok(format!("{}break;\n", indent)),
-        Statement::Continue => Ok(format!("{}continue;\n", indent)),
+        Statement::Break { label: None, .. } => Ok(format!("{}break;\n", indent)),
+        Statement::Break { label: Some(label), .. } => Ok(format!("{}goto {}_break;\n", indent, label)),
+        Statement::Continue { label: None, .. } => Ok(format!("{}continue;\n", indent)),
+        Statement::Continue { label: Some(label), .. } => Ok(format!("{}goto {}_continue;\n", indent, label)),
         _ => Ok("".to_string())
     }
 }
 
-fn generate_expression(expr: &Expression) -> Result<String, CodegenError> {
+/// Finds the top-level functions that can be emitted as `constexpr`: private functions over `int`, `float`,
+/// and `bool` whose bodies only do arithmetic and control flow, and only call other eligible functions
+/// (including themselves). Public functions are left out because their header declaration would have to
+/// change too, and a `constexpr` function must be defined in every file that calls it.
+fn find_constexpr_functions(program: &Program) -> HashSet<String> {
+    find_eligible_functions(program, |is_public| !is_public)
+}
+
+/// Finds the top-level functions that can be emitted as `noexcept`. A body that passes the `constexpr`
+/// checks can't throw either: it has no I/O, no parsing builtins, no indexing, no strings or `any`
+/// values, no `throw`, and only calls other eligible functions. Unlike `constexpr`, public functions
+/// qualify, since their header declaration gets `noexcept` too.
+fn find_noexcept_functions(program: &Program) -> HashSet<String> {
+    find_eligible_functions(program, |_| true)
+}
+
+/// The top-level functions whose signatures and bodies pass the `constexpr` checks, among those whose
+/// visibility `visible` accepts.
+fn find_eligible_functions(program: &Program, visible: impl Fn(bool) -> bool) -> HashSet<String> {
+    let functions: Vec<&Statement> = program.statements.iter()
+        .filter(|stmt| matches!(stmt, Statement::FunctionDefinition { .. }))
+        .collect();
+
+    // Start by assuming every function is eligible, then drop the ones that call ineligible functions until nothing changes.
+    let mut eligible: HashSet<String> = functions.iter()
+        .filter_map(|stmt| match stmt { Statement::FunctionDefinition { name, .. } => Some(name.clone()), _ => None })
+        .collect();
+    loop {
+        let next: HashSet<String> = eligible.iter()
+            .filter(|name| functions.iter().all(|stmt| match stmt {
+                // Every overload of a name has to qualify, since calls are matched by name.
+                Statement::FunctionDefinition { is_public, name: fn_name, params, return_type, body, .. } if fn_name == *name => {
+                    let mut locals: Vec<String> = params.iter().map(|p| p.name.clone()).collect();
+                    visible(*is_public)
+                        && params.iter().all(|p| !p.is_variadic && is_constexpr_type(&p.data_type))
+                        && (is_constexpr_type(return_type) || *return_type == Type::Void)
+                        && is_constexpr_block(body, &mut locals, &eligible)
+                }
+                _ => true,
+            }))
+            .cloned()
+            .collect();
+        if next.len() == eligible.len() {
+            return next;
+        }
+        eligible = next;
+    }
+}
+
+fn is_constexpr_type(data_type: &Type) -> bool {
+    matches!(data_type, Type::Int | Type::Float | Type::Bool)
+}
+
+/// Checks a block whose declarations go into `locals`. Nested blocks are checked against a copy, so
+/// the names they declare go out of scope with them.
+fn is_constexpr_block(statements: &[Statement], locals: &mut Vec<String>, eligible: &HashSet<String>) -> bool {
+    statements.iter().all(|stmt| match stmt {
+        Statement::Declaration { name, data_type, initializer, .. } => {
+            let ok = is_constexpr_type(data_type) && initializer.as_ref().is_none_or(|initializer| is_constexpr_expr(initializer, locals, eligible));
+            locals.push(name.clone());
+            ok
+        }
+        Statement::Assignment { targets, value, .. } => {
+            targets.iter().all(|target| matches!(target, Expression::Identifier(name) if locals.contains(name)))
+                && is_constexpr_expr(value, locals, eligible)
+        }
+        Statement::If { condition, consequence, alternative, .. } => {
+            is_constexpr_expr(condition, locals, eligible)
+                && is_constexpr_block(consequence, &mut locals.clone(), eligible)
+                && alternative.as_ref().is_none_or(|alt| is_constexpr_block(alt, &mut locals.clone(), eligible))
+        }
+        Statement::While { condition, body, else_block, .. } => {
+            is_constexpr_expr(condition, locals, eligible)
+                && is_constexpr_block(body, &mut locals.clone(), eligible)
+                && else_block.as_ref().is_none_or(|block| is_constexpr_block(block, &mut locals.clone(), eligible))
+        }
+        Statement::For { iterator, start, end, body, else_block, .. } => {
+            let mut body_locals = locals.clone();
+            body_locals.push(iterator.clone());
+            is_constexpr_expr(start, locals, eligible)
+                && is_constexpr_expr(end, locals, eligible)
+                && is_constexpr_block(body, &mut body_locals, eligible)
+                && else_block.as_ref().is_none_or(|block| is_constexpr_block(block, &mut locals.clone(), eligible))
+        }
+        Statement::Return { value: expr, .. } => expr.as_ref().is_none_or(|e| is_constexpr_expr(e, locals, eligible)),
+        Statement::Expression { expr, .. } => is_constexpr_expr(expr, locals, eligible),
+        Statement::Break { .. } | Statement::Continue { .. } => true,
+        // Printing, exceptions, and nested definitions can't happen at compile time.
+        _ => false,
+    })
+}
+
+fn is_constexpr_expr(expr: &Expression, locals: &[String], eligible: &HashSet<String>) -> bool {
+    match expr {
+        Expression::Literal(Literal::String(_)) => false,
+        Expression::Literal(_) => true,
+        Expression::Identifier(name) => locals.contains(name),
+        Expression::BinaryOp { left, right, .. } => {
+            is_constexpr_expr(left, locals, eligible) && is_constexpr_expr(right, locals, eligible)
+        }
+        Expression::Unary { operand, .. } => is_constexpr_expr(operand, locals, eligible),
+        Expression::Call { callee, args } => match &**callee {
+            Expression::Identifier(name) => {
+                eligible.contains(name) && args.iter().all(|arg| is_constexpr_expr(arg, locals, eligible))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Generates a `while` or `for` loop. `header` builds the loop's C++ header around a condition, and
+/// `element`, if given, is a declaration that starts every iteration once the condition has passed.
+/// With an `else` block, the condition moves into the body so the loop can record that it ended
+/// because the condition failed; a `break` leaves without setting that flag, which skips the `else`.
+#[allow(clippy::too_many_arguments)]
+fn generate_loop(header: impl Fn(&str) -> String, condition: &str, element: Option<&str>, label: &Option<String>, body: &[Statement], else_block: &Option<Vec<Statement>>, indent_level: usize, mode: GenMode, ctx: &mut Context) -> Result<String, CodegenError> {
+    let indent = "    ".repeat(indent_level);
+    let mut code = String::new();
+    match else_block {
+        None => {
+            code.push_str(&format!("{}{} {{\n", indent, header(condition)));
+            if let Some(element) = element {
+                code.push_str(&format!("{}    {}\n", indent, element));
+            }
+            code.push_str(&generate_loop_body(label, body, indent_level, mode, ctx)?);
+            code.push_str(&format!("{}}}\n", indent));
+        }
+        Some(else_block) => {
+            let inner_indent = "    ".repeat(indent_level + 1);
+            let completed = ctx.temps.fresh();
+            code.push_str(&format!("{}{{\n", indent));
+            code.push_str(&format!("{}bool {} = false;\n", inner_indent, completed));
+            code.push_str(&format!("{}{} {{\n", inner_indent, header("true")));
+            code.push_str(&format!("{}    if (!({})) {{ {} = true; break; }}\n", inner_indent, condition, completed));
+            if let Some(element) = element {
+                code.push_str(&format!("{}    {}\n", inner_indent, element));
+            }
+            code.push_str(&generate_loop_body(label, body, indent_level + 1, mode, ctx)?);
+            code.push_str(&format!("{}}}\n", inner_indent));
+            code.push_str(&format!("{}if ({}) {{\n", inner_indent, completed));
+            code.push_str(&generate_block(else_block, indent_level + 2, mode, ctx)?);
+            code.push_str(&format!("{}}}\n", inner_indent));
+            code.push_str(&format!("{}}}\n", indent));
+        }
+    }
+    code.push_str(&generate_loop_exit_label(label, body, indent_level));
+    Ok(code)
+}
+
+/// Generates the body of a loop. C++ has no labeled `continue`, so when the body contains a
+/// `continue label` aimed at this loop, it is wrapped in its own scope followed by a `label_continue:`
+/// target. The extra scope keeps the `goto` from jumping over any of the body's declarations.
+fn generate_loop_body(label: &Option<String>, body: &[Statement], indent_level: usize, mode: GenMode, ctx: &mut Context) -> Result<String, CodegenError> {
+    match label {
+        Some(label) if targets_label(body, label, true) => {
+            let inner_indent = "    ".repeat(indent_level + 1);
+            let mut code = format!("{}{{\n", inner_indent);
+            code.push_str(&generate_block(body, indent_level + 2, mode, ctx)?);
+            code.push_str(&format!("{}}}\n", inner_indent));
+            code.push_str(&format!("{}{}_continue:;\n", inner_indent, label));
+            Ok(code)
+        }
+        _ => generate_block(body, indent_level + 1, mode, ctx),
+    }
+}
+
+/// Generates the `label_break:` target placed right after a loop that is exited by a `break label`.
+fn generate_loop_exit_label(label: &Option<String>, body: &[Statement], indent_level: usize) -> String {
+    match label {
+        Some(label) if targets_label(body, label, false) => format!("{}{}_break:;\n", "    ".repeat(indent_level), label),
+        _ => String::new(),
+    }
+}
+
+/// Returns true if a `break label` (or `continue label` when `is_continue` is set) in `statements` refers to `label`.
+fn targets_label(statements: &[Statement], label: &str, is_continue: bool) -> bool {
+    statements.iter().any(|stmt| match stmt {
+        Statement::Break { label: Some(target), .. } => !is_continue && target == label,
+        Statement::Continue { label: Some(target), .. } => is_continue && target == label,
+        Statement::If { consequence, alternative, .. } => {
+            targets_label(consequence, label, is_continue)
+                || alternative.as_ref().is_some_and(|alt| targets_label(alt, label, is_continue))
+        }
+        Statement::While { body, else_block, .. } | Statement::For { body, else_block, .. } | Statement::ForEach { body, else_block, .. } => {
+            targets_label(body, label, is_continue)
+                || else_block.as_ref().is_some_and(|block| targets_label(block, label, is_continue))
+        }
+        Statement::TryCatch { try_block, catch_block, .. } => {
+            targets_label(try_block, label, is_continue) || targets_label(catch_block, label, is_continue)
+        }
+        _ => false,
+    })
+}
+
+/// Generates a `match` as a chain of `?:` testing the subject against each pattern in turn. A subject
+/// that isn't a plain name is evaluated once, as the parameter of an immediately invoked lambda.
+fn generate_match(subject: &Expression, arms: &[(Expression, Expression)], default: &Expression, ctx: &Context) -> Result<String, CodegenError> {
+    let tested = match subject {
+        Expression::Identifier(_) => generate_expression(subject, ctx)?,
+        _ => format!("{}_subject", TEMP_PREFIX),
+    };
+    let mut chain = String::new();
+    for (pattern, value) in arms {
+        chain.push_str(&format!("({} == {}) ? {} : ", tested, generate_expression(pattern, ctx)?, generate_expression(value, ctx)?));
+    }
+    chain = format!("({}{})", chain, generate_expression(default, ctx)?);
+    match subject {
+        Expression::Identifier(_) => Ok(chain),
+        _ => Ok(format!("[&](const auto& {}) {{ return {}; }}({})", tested, chain, generate_container(subject, ctx)?)),
+    }
+}
+
+fn generate_expression(expr: &Expression, ctx: &Context) -> Result<String, CodegenError> {
     match expr {
         Expression::New { class_name, args } => {
-            let args_str: Result<Vec<String>, _> = args.iter().map(generate_expression).collect();
+            let args_str: Result<Vec<String>, _> = args.iter().map(|arg| generate_expression(arg, ctx)).collect();
             Ok(format!("std::make_shared<{}>({})", class_name, args_str?.join(", ")))
         },
         Expression::This => Ok("this".to_string()),
-        Expression::Get { object, name } => {
-            Ok(format!("{}->{}", generate_expression(object)?, name))
-        }
+        Expression::Get { object, name } => match &**object {
+            Expression::Identifier(enum_name) if ctx.enums.contains(enum_name) && lookup_local(ctx, enum_name).is_none() => {
+                Ok(format!("{}::{}", enum_name, cpp_name(name)))
+            }
+            _ => Ok(format!("{}->{}", generate_expression(object, ctx)?, cpp_name(name))),
+        },
         Expression::Identifier(name) => {
+            if let Some(local) = lookup_local(ctx, name) {
+                return Ok(local.cpp_name.clone());
+            }
             match name.as_str() {
                 "to_string" => Ok("rl::to_string".to_string()),
                 "to_int" => Ok("std::stoi".to_string()),
                 "to_float" => Ok("std::stod".to_string()),
+                "parse_int" => Ok("rl::parse_int".to_string()),
+                "parse_float" => Ok("rl::parse_float".to_string()),
+                "unwrap" => Ok("rl::unwrap".to_string()),
+                "unwrap_or" => Ok("rl::unwrap_or".to_string()),
                 "read_file" => Ok("rl::read_file".to_string()),
                 "write_file" => Ok("rl::write_file".to_string()),
                 "split" => Ok("rl::split".to_string()),
                 "join" => Ok("rl::join".to_string()),
+                "align_left" => Ok("rl::align_left".to_string()),
+                "align_right" => Ok("rl::align_right".to_string()),
                 "contains" => Ok("rl::contains".to_string()),
+                "range" => Ok("rl::range".to_string()),
                 "args" => Ok("rl::args".to_string()),
                 "exists" => Ok("rl::exists".to_string()),
                 "remove" => Ok("rl::remove".to_string()),
@@ -260,28 +1018,89 @@ fn generate_expression(expr: &Expression) -> Result<String, CodegenError> {
                 "random_float" => Ok("rl::random_float".to_string()),
                 "time" => Ok("rl::time".to_string()),
                 "sleep" => Ok("rl::sleep".to_string()),
-                _ => Ok(name.clone()),
+                _ => Ok(cpp_name(name)),
             }
         }
         Expression::Call { callee, args } => {
-            let callee_str = generate_expression(callee)?;
-            let args_str: Result<Vec<String>, _> = args.iter().map(generate_expression).collect();
+            let callee_str = generate_expression(callee, ctx)?;
+            let args_str: Result<Vec<String>, _> = args.iter().map(|arg| generate_expression(arg, ctx)).collect();
             Ok(format!("{}({})", callee_str, args_str?.join(", ")))
         },
         Expression::Literal(Literal::Int(n)) => Ok(n.to_string()),
-        Expression::Literal(Literal::Float(n)) => Ok(n.to_string()),
-        Expression::Literal(Literal::String(s)) => Ok(format!("\"{}\"", s)),
+        // `{:?}` always keeps a decimal point (`2.0`, not `2`), so the C++ literal stays a double.
+        Expression::Literal(Literal::Float(_, Some(text))) => Ok(text.clone()),
+        // `{:?}` is the shortest spelling that reads back as the same value, and always has a `.` or exponent.
+        Expression::Literal(Literal::Float(n, None)) => Ok(format!("{:?}", n)),
+        Expression::Literal(Literal::String(s)) => Ok(cpp_string_literal(s)),
+        Expression::Literal(Literal::Char(c)) => Ok(cpp_char_literal(*c)),
         Expression::Literal(Literal::Bool(b)) => Ok(if *b { "true".to_string() } else { "false".to_string() }),
-        Expression::Index { list, index } => Ok(format!("{}.at({})", generate_expression(list)?, generate_expression(index)?)),
-        Expression::BinaryOp { op, left, right } => Ok(format!("({} {} {})", generate_expression(left)?, op.to_string(), generate_expression(right)?)),
+        Expression::Literal(Literal::None) => Ok("std::nullopt".to_string()),
+        // A literal index that can't be negative indexes directly; anything else may count from the end.
+        Expression::Index { list, index } => match &**index {
+            Expression::Literal(Literal::Int(n)) if *n >= 0 => Ok(format!("{}.at({})", generate_container(list, ctx)?, n)),
+            _ => Ok(format!("rl::index_at({}, {})", generate_container(list, ctx)?, generate_expression(index, ctx)?)),
+        },
+        Expression::Slice { target, start, end } => {
+            let start = match start {
+                Some(start) => generate_expression(start, ctx)?,
+                None => "0".to_string(),
+            };
+            match end {
+                Some(end) => Ok(format!("rl::slice({}, {}, {})", generate_container(target, ctx)?, start, generate_expression(end, ctx)?)),
+                None => Ok(format!("rl::slice({}, {})", generate_container(target, ctx)?, start)),
+            }
+        },
+        Expression::BinaryOp { op: BinaryOperator::Divide, left, right } if ctx.options.int_div == IntDivMode::Floor => {
+            Ok(format!("rl::floor_div({}, {})", generate_arithmetic_operand(left, ctx)?, generate_arithmetic_operand(right, ctx)?))
+        },
+        Expression::BinaryOp { op: BinaryOperator::Modulo, left, right } if ctx.options.int_div == IntDivMode::Floor => {
+            Ok(format!("rl::floor_mod({}, {})", generate_arithmetic_operand(left, ctx)?, generate_arithmetic_operand(right, ctx)?))
+        },
+        Expression::BinaryOp { op: BinaryOperator::Add, .. } if string_chain(expr, ctx).is_some() => {
+            let parts = string_chain(expr, ctx).unwrap_or_default();
+            let parts: Result<Vec<String>, _> = parts.into_iter().map(|part| generate_expression(part, ctx)).collect();
+            Ok(format!("rl::concat({})", parts?.join(", ")))
+        },
+        Expression::BinaryOp { op: BinaryOperator::Add, left, right } if ctx.options.auto_stringify => {
+            let (left_type, right_type) = (known_type(left, ctx), known_type(right, ctx));
+            let left = generate_concat_operand(left, left_type.as_ref(), right_type.as_ref(), ctx)?;
+            let right = generate_concat_operand(right, right_type.as_ref(), left_type.as_ref(), ctx)?;
+            Ok(format!("({} + {})", left, right))
+        },
+        Expression::BinaryOp { op: BinaryOperator::In, left, right } => {
+            let container = match (&**right, known_type(left, ctx)) {
+                // A braced list has no type of its own, so it's given the type of what's looked for.
+                (Expression::ListLiteral(_), Some(element_type)) => format!("std::vector<{}>{}", element_type, generate_expression(right, ctx)?),
+                (Expression::ListLiteral(_), None) => format!("std::vector{}", generate_expression(right, ctx)?),
+                _ => generate_container(right, ctx)?,
+            };
+            Ok(format!("rl::contains({}, {})", container, generate_expression(left, ctx)?))
+        },
+        Expression::BinaryOp { op, left, right } if op.is_arithmetic() => {
+            Ok(format!("({} {} {})", generate_arithmetic_operand(left, ctx)?, op, generate_arithmetic_operand(right, ctx)?))
+        },
+        Expression::BinaryOp { op, left, right } => Ok(format!("({} {} {})", generate_expression(left, ctx)?, op, generate_expression(right, ctx)?)),
+        Expression::Unary { op, operand } => {
+            let operand = generate_expression(operand, ctx)?;
+            // A negative literal keeps a space after `-`, since `--` is a decrement in C++.
+            let gap = if operand.starts_with('-') { " " } else { "" };
+            Ok(format!("({}{}{})", op, gap, operand))
+        }
+        Expression::Assign { target, value } => Ok(format!("({} = {})", generate_assign_target(target, ctx)?, generate_expression(value, ctx)?)),
+        Expression::Match { subject, arms, default } => generate_match(subject, arms, default, ctx),
         Expression::ListLiteral(elements) => {
-            let elems: Result<Vec<String>, _> = elements.iter().map(generate_expression).collect();
+            let elems: Result<Vec<String>, _> = elements.iter().map(|elem| generate_expression(elem, ctx)).collect();
             Ok(format!("{{ {} }}", elems?.join(", ")))
         },
+        // A string literal is made a `std::string`, so the tuple holds one rather than a `const char*`.
+        Expression::TupleLiteral(elements) => {
+            let elems: Result<Vec<String>, _> = elements.iter().map(|elem| generate_container(elem, ctx)).collect();
+            Ok(format!("std::make_tuple({})", elems?.join(", ")))
+        },
         Expression::DictLiteral(entries) => {
             let mut entry_strs = Vec::new();
             for (key, value) in entries {
-                entry_strs.push(format!("{{{}, {}}}", generate_expression(key)?, generate_expression(value)?));
+                entry_strs.push(format!("{{{}, {}}}", generate_expression(key, ctx)?, generate_expression(value, ctx)?));
             }
             Ok(format!("{{ {} }}", entry_strs.join(", ")))
         }
@@ -0,0 +1,96 @@
+//! Dialects (`--dialect`), which pin the syntax a file is compiled against. Newer syntax is grouped into
+//! `Features`, and the lexer, parser, and semantic checks each turn down the features a dialect leaves out.
+
+/// A named set of features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// The core language, without the newer syntax below.
+    Conservative,
+    /// Everything the compiler supports.
+    #[default]
+    Latest,
+}
+
+impl Dialect {
+    /// The dialect spelled `name` on the command line.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "conservative" => Some(Dialect::Conservative),
+            "latest" => Some(Dialect::Latest),
+            _ => None,
+        }
+    }
+
+    pub fn features(self) -> Features {
+        let enabled = self == Dialect::Latest;
+        Features {
+            braced_blocks: enabled,
+            assignment_expressions: enabled,
+            implicit_return: enabled,
+            for_each: enabled,
+            top_level_return: enabled,
+            elif_and_unless: enabled,
+            loop_labels: enabled,
+            loop_else: enabled,
+            infinite_loop: enabled,
+            compound_assignment: enabled,
+            radix_ints: enabled,
+            digit_separators: enabled,
+            match_expressions: enabled,
+            enums: enabled,
+            conditional_compilation: enabled,
+            static_assert: enabled,
+            tuples: enabled,
+        }
+    }
+}
+
+/// Which groups of newer syntax are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Features {
+    /// Blocks written in braces when indentation isn't significant. See `Lexer::disable_indentation`.
+    pub braced_blocks: bool,
+    /// `(n = value)`, which assigns inside an expression.
+    pub assignment_expressions: bool,
+    /// A function that returns a value ending with a bare expression instead of a `return`.
+    pub implicit_return: bool,
+    /// `for x in` a string or a list, rather than only a range.
+    pub for_each: bool,
+    /// `return` in the top-level code, which ends the program with an exit code.
+    pub top_level_return: bool,
+    /// `elif`, `else if`, and `unless`.
+    pub elif_and_unless: bool,
+    /// `outer: while cond:`, and `break outer` or `continue outer` naming the loop.
+    pub loop_labels: bool,
+    /// An `else:` block after a `while` or `for`, run when the loop ends without a `break`.
+    pub loop_else: bool,
+    /// `loop:`, which runs until a `break`.
+    pub infinite_loop: bool,
+    /// `x += value` and the other `op=` forms, including `&&=` and `||=`.
+    pub compound_assignment: bool,
+    /// Integers written in hex, binary, or octal, as in `0xff`.
+    pub radix_ints: bool,
+    /// `_` between digits, as in `1_000_000`.
+    pub digit_separators: bool,
+    /// `match` expressions.
+    pub match_expressions: bool,
+    /// `enum` declarations.
+    pub enums: bool,
+    /// `@if` blocks picked by the build flags.
+    pub conditional_compilation: bool,
+    /// `static_assert`, checked at compile time.
+    pub static_assert: bool,
+    /// Tuple types and values, and `val (a, b) = pair`.
+    pub tuples: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Dialect::default().features()
+    }
+}
+
+/// The message for using `what` when the dialect doesn't include it.
+pub(crate) fn unavailable(what: &str) -> String {
+    format!("{} isn't part of the selected dialect; compile with '--dialect latest' to use it", what)
+}
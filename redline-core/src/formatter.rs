@@ -0,0 +1,189 @@
+//! The source formatter behind `--format`. It works on the token stream rather than the AST, so
+//! comments and blank lines survive, and prints the tokens back out in one canonical layout: blocks
+//! indented by four spaces, one statement per line, a space around binary operators and after commas
+//! and colons, and at most one blank line in a row.
+use crate::lexer::{is_keyword, Lexer, LexerError, Token, TokenType};
+
+/// A line of output: the tokens of one statement (or one line of a multi-line literal), and how deep it's nested.
+struct Line<'a> {
+    depth: usize,
+    tokens: Vec<&'a Token>,
+    /// Whether the source had a blank line above this one.
+    blank_before: bool,
+}
+
+/// Formats a REDLINE source file. Only lexing can fail, so a file with a parse error is still
+/// formatted, as far as its tokens go.
+pub fn format_source(source: &str) -> Result<String, LexerError> {
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.keep_comments();
+    let tokens = lexer.tokenize()?;
+
+    let mut out = String::new();
+    let mut previous_depth = 0;
+    for line in split_lines(&tokens) {
+        // A blank line right after a line that opens a block is dropped.
+        if line.blank_before && line.depth <= previous_depth {
+            out.push('\n');
+        }
+        out.push_str(&"    ".repeat(line.depth));
+        out.push_str(&render_line(&line.tokens));
+        out.push('\n');
+        previous_depth = line.depth;
+    }
+    Ok(out)
+}
+
+/// Splits the tokens into output lines. A `;` between statements becomes a line break, so each
+/// statement ends up on a line of its own.
+fn split_lines(tokens: &[Token]) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    let mut current = Vec::new();
+    let mut depth: usize = 0;
+    let mut line_depth = 0;
+    let mut brackets = 0;
+    let mut blank = false;
+    let mut after_semicolon = false;
+    for token in tokens {
+        match token.token_type {
+            TokenType::Indent => depth += 1,
+            TokenType::Dedent => depth = depth.saturating_sub(1),
+            TokenType::Eof => break,
+            TokenType::Newline | TokenType::Semicolon if current.is_empty() => {
+                // The newline that ends a line split at a `;` isn't a blank line.
+                blank |= token.token_type == TokenType::Newline && !after_semicolon && !lines.is_empty();
+                after_semicolon = false;
+            }
+            TokenType::Semicolon if brackets > 0 => current.push(token),
+            TokenType::Newline | TokenType::Semicolon => {
+                lines.push(Line { depth: line_depth, tokens: std::mem::take(&mut current), blank_before: blank });
+                blank = false;
+                after_semicolon = token.token_type == TokenType::Semicolon;
+            }
+            _ => {
+                if current.is_empty() {
+                    line_depth = depth;
+                }
+                match token.token_type {
+                    TokenType::LParen | TokenType::LBracket | TokenType::LBrace => brackets += 1,
+                    TokenType::RParen | TokenType::RBracket | TokenType::RBrace => brackets -= 1,
+                    _ => {}
+                }
+                current.push(token);
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(Line { depth: line_depth, tokens: current, blank_before: blank });
+    }
+    // The lexer doesn't let a comment on a line of its own open or close a block, so it's put at the depth
+    // of the code after it, unless it's indented further, as a comment at the end of a block is.
+    for i in (0..lines.len()).rev() {
+        let (next_depth, next_column) = lines.get(i + 1).map_or((0, 1), |next| (next.depth, next.tokens[0].column));
+        let line = &mut lines[i];
+        if matches!(line.tokens[..], [Token { token_type: TokenType::Comment(_), column, .. }] if *column <= next_column) {
+            line.depth = next_depth;
+        }
+    }
+    lines
+}
+
+fn render_line(tokens: &[&Token]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&TokenType> = None;
+    let mut previous_is_unary = false;
+    for token in tokens {
+        let token_type = &token.token_type;
+        if let TokenType::Comment(comment) = token_type {
+            // A comment after code is set off from it by two spaces.
+            if !text.is_empty() {
+                text.push_str("  ");
+            }
+            text.push_str(&format!("#{}", comment));
+            continue;
+        }
+        let minus = TokenType::Op("-".to_string());
+        // A negation is written against its operand, except that `- -x` keeps its space so it doesn't read as `--`.
+        let after_unary = previous_is_unary && !(previous == Some(&minus) && *token_type == minus);
+        if previous.is_some_and(|previous| !after_unary && space_between(previous, token_type)) {
+            text.push(' ');
+        }
+        // `-` is a negation when it doesn't follow a value, as in `x = -1` or `f(-y)`.
+        previous_is_unary = *token_type == TokenType::Op("!".to_string())
+            || (*token_type == TokenType::Op("-".to_string()) && !previous.is_some_and(ends_operand));
+        text.push_str(&spelling(token_type));
+        previous = Some(token_type);
+    }
+    text
+}
+
+/// Whether a token can end an operand, so that a `-` after it subtracts and a `(` or `[` after it
+/// calls or indexes it.
+fn ends_operand(token_type: &TokenType) -> bool {
+    matches!(token_type,
+        TokenType::Ident(_) | TokenType::Int(_) | TokenType::Float(..) | TokenType::Str(_) | TokenType::FString(_)
+        | TokenType::Char(_) | TokenType::True | TokenType::False | TokenType::None | TokenType::This
+        | TokenType::RParen | TokenType::RBracket | TokenType::RBrace)
+}
+
+/// Whether two adjacent tokens on a line are separated by a space.
+fn space_between(previous: &TokenType, next: &TokenType) -> bool {
+    match next {
+        TokenType::RParen | TokenType::RBracket | TokenType::RBrace | TokenType::Comma | TokenType::Semicolon
+        | TokenType::Colon | TokenType::Dot | TokenType::Question | TokenType::Ellipsis | TokenType::Range => return false,
+        // A call, an index, or a type argument, as in `f(x)`, `items[0]`, `print(x)`, and `list[int]`.
+        TokenType::LParen | TokenType::LBracket
+            if ends_operand(previous) || matches!(previous, TokenType::Print | TokenType::Eprint | TokenType::Type(_)) => return false,
+        _ => {}
+    }
+    !matches!(previous, TokenType::LParen | TokenType::LBracket | TokenType::LBrace | TokenType::Dot | TokenType::Range | TokenType::At)
+}
+
+/// How a token is written. Literals are written in their plain form, so a raw string comes out as an
+/// ordinary string with escapes, `0xFF` comes out as `255`, and `and`, `or`, and `not` come out as `&&`, `||`, and `!`.
+pub(crate) fn spelling(token_type: &TokenType) -> String {
+    let text = match token_type {
+        TokenType::Ident(name) if is_keyword(name) => return format!("r#{}", name),
+        TokenType::Ident(name) | TokenType::Type(name) | TokenType::Op(name) => return name.clone(),
+        TokenType::OpAssign(op) => return format!("{}=", op),
+        TokenType::Int(n) => return n.to_string(),
+        TokenType::Float(_, text) => return text.clone(),
+        TokenType::Str(s) => return quote(s, '"'),
+        TokenType::FString(s) => return format!("f{}", quote(s, '"')),
+        TokenType::Char(c) => return quote(&c.to_string(), '\''),
+        TokenType::Comment(comment) => return format!("#{}", comment),
+        TokenType::Var => "var", TokenType::Val => "val", TokenType::Def => "def", TokenType::Pub => "pub",
+        TokenType::Print => "print", TokenType::Eprint => "eprint", TokenType::Return => "return",
+        TokenType::If => "if", TokenType::Unless => "unless", TokenType::Elif => "elif", TokenType::Else => "else", TokenType::True => "true", TokenType::False => "false",
+        TokenType::None => "none", TokenType::While => "while", TokenType::Loop => "loop", TokenType::For => "for", TokenType::In => "in",
+        TokenType::Import => "import", TokenType::Class => "class", TokenType::Enum => "enum", TokenType::This => "this",
+        TokenType::Try => "try", TokenType::Catch => "catch", TokenType::New => "new", TokenType::Break => "break",
+        TokenType::Continue => "continue", TokenType::StaticAssert => "static_assert", TokenType::Match => "match",
+        TokenType::Arrow => "->", TokenType::FatArrow => "=>", TokenType::Colon => ":", TokenType::Assign => "=",
+        TokenType::LParen => "(", TokenType::RParen => ")", TokenType::LBracket => "[", TokenType::RBracket => "]",
+        TokenType::LBrace => "{", TokenType::RBrace => "}", TokenType::Comma => ",", TokenType::Semicolon => ";",
+        TokenType::Range => "..", TokenType::Ellipsis => "...", TokenType::Dot => ".", TokenType::Question => "?",
+        TokenType::At => "@",
+        TokenType::Newline | TokenType::Indent | TokenType::Dedent | TokenType::Eof => "",
+    };
+    text.to_string()
+}
+
+/// Writes a string or char literal's text back out between `quote`s, escaping what has to be escaped.
+pub(crate) fn quote(text: &str, quote: char) -> String {
+    let mut out = String::from(quote);
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            _ if c == quote => out.push_str(&format!("\\{}", c)),
+            _ if c.is_control() => out.push_str(&format!("\\u{{{:X}}}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
@@ -0,0 +1,311 @@
+//! A tree-walking evaluator for REDLINE expressions, for embedding REDLINE as an expression or
+//! config language: the host supplies variables in an `Environment` and reads back a `Value`.
+//! It computes what the generated C++ would, so an `int` is 32 bits and `/` on ints rounds toward zero.
+use crate::ast::{BinaryOperator, Expression, Literal, Statement, UnaryOperator};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A value computed by the interpreter.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    /// Arithmetic that leaves the range of a C++ `int` is an error rather than wrapping.
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    String(String),
+    List(Vec<Value>),
+    /// The missing value of an optional.
+    None,
+}
+
+impl Value {
+    /// The REDLINE name of the value's type, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "bool",
+            Value::Char(_) => "char",
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::None => "none",
+        }
+    }
+
+    /// The type name with its article, like "an int" or "a string", for error messages.
+    fn described(&self) -> String {
+        match self {
+            Value::Int(_) => "an int".to_string(),
+            Value::None => "none".to_string(),
+            _ => format!("a {}", self.type_name()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::String(s) => write!(f, "{}", s),
+            Value::List(elements) => {
+                let elements: Vec<String> = elements.iter().map(|element| element.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Value::None => write!(f, "none"),
+        }
+    }
+}
+
+impl From<&Literal> for Value {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::Int(n) => Value::Int(*n),
+            Literal::Float(n, _) => Value::Float(*n),
+            Literal::Bool(b) => Value::Bool(*b),
+            Literal::Char(c) => Value::Char(*c),
+            Literal::String(s) => Value::String(s.clone()),
+            Literal::None => Value::None,
+        }
+    }
+}
+
+/// The variables an expression can refer to, supplied by the host.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    variables: HashMap<String, Value>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines `name`, replacing any earlier value.
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.variables.get(name)
+    }
+}
+
+/// An expression that couldn't be evaluated: it didn't parse, or it did something that fails at runtime,
+/// like dividing by zero or adding a string to a bool.
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub message: String,
+}
+
+impl RuntimeError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Runtime Error: {}", self.message)
+    }
+}
+
+/// Parses `src` as a single expression and evaluates it, looking its names up in `env`. Indentation
+/// isn't significant, so the expression can be indented to fit the text it's embedded in.
+pub fn eval_expression(src: &str, env: &Environment) -> Result<Value, RuntimeError> {
+    let mut lexer = Lexer::new(src.to_string());
+    lexer.disable_indentation();
+    let tokens = lexer.tokenize().map_err(|e| RuntimeError::new(e.message))?;
+    let program = Parser::new(&tokens).parse().map_err(|e| RuntimeError::new(e.message))?;
+    match program.statements.as_slice() {
+        [Statement::Expression { expr, .. }] => evaluate(expr, env),
+        _ => Err(RuntimeError::new("Expected a single expression".to_string())),
+    }
+}
+
+/// Evaluates an already parsed expression.
+pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeError> {
+    match expr {
+        Expression::Literal(literal) => Ok(Value::from(literal)),
+        Expression::Identifier(name) => env.get(name).cloned().ok_or_else(|| RuntimeError::new(format!("'{}' is not defined", name))),
+        Expression::ListLiteral(elements) => Ok(Value::List(elements.iter().map(|element| evaluate(element, env)).collect::<Result<_, _>>()?)),
+        // `&&` and `||` only evaluate their right side when the left doesn't decide the result.
+        Expression::BinaryOp { op: op @ (BinaryOperator::And | BinaryOperator::Or), left, right } => {
+            let operand = |value: Value| match value {
+                Value::Bool(b) => Ok(b),
+                other => Err(RuntimeError::new(format!("'{}' needs bool operands, but was given {}", op, other.described()))),
+            };
+            let decided = *op == BinaryOperator::Or;
+            if operand(evaluate(left, env)?)? == decided {
+                return Ok(Value::Bool(decided));
+            }
+            Ok(Value::Bool(operand(evaluate(right, env)?)?))
+        }
+        Expression::BinaryOp { op, left, right } => binary(op, evaluate(left, env)?, evaluate(right, env)?),
+        Expression::Unary { op: UnaryOperator::Not, operand } => match evaluate(operand, env)? {
+            Value::Bool(b) => Ok(Value::Bool(!b)),
+            other => Err(RuntimeError::new(format!("'!' needs a bool, but was given {}", other.described()))),
+        },
+        Expression::Unary { op: UnaryOperator::Negate, operand } => match evaluate(operand, env)? {
+            Value::Int(n) => n.checked_neg().filter(|n| i32::try_from(*n).is_ok())
+                .map(Value::Int)
+                .ok_or_else(|| RuntimeError::new(format!("-({}) overflows an int", n))),
+            Value::Float(x) => Ok(Value::Float(-x)),
+            other => Err(RuntimeError::new(format!("'-' needs an int or a float, but was given {}", other.described()))),
+        },
+        Expression::Index { list, index } => {
+            let (target, index) = (evaluate(list, env)?, evaluate(index, env)?);
+            let Value::Int(i) = index else {
+                return Err(RuntimeError::new(format!("An index must be an int, not {}", index.described())));
+            };
+            // Like `rl::index_at`, a negative index counts from the end.
+            let element = |len: usize| usize::try_from(if i < 0 { len as i64 + i } else { i }).ok().filter(|&i| i < len);
+            let out_of_range = || RuntimeError::new(format!("Index {} is out of range", i));
+            match target {
+                Value::List(elements) => element(elements.len()).map(|i| elements[i].clone()).ok_or_else(out_of_range),
+                Value::String(s) => {
+                    let chars: Vec<char> = s.chars().collect();
+                    element(chars.len()).map(|i| Value::Char(chars[i])).ok_or_else(out_of_range)
+                }
+                other => Err(RuntimeError::new(format!("Can't index {}", other.described()))),
+            }
+        }
+        Expression::Slice { target, start, end } => {
+            let target = evaluate(target, env)?;
+            let bound = |bound: &Option<Box<Expression>>| match bound.as_deref().map(|bound| evaluate(bound, env)).transpose()? {
+                Some(Value::Int(i)) => Ok(Some(i)),
+                Some(other) => Err(RuntimeError::new(format!("A slice bound must be an int, not {}", other.described()))),
+                None => Ok(None),
+            };
+            let (start, end) = (bound(start)?, bound(end)?);
+            // Like `rl::slice`, negative bounds count from the end, and bounds past either end are clamped.
+            let range = |len: usize| {
+                let position = |i: i64| (if i < 0 { len as i64 + i } else { i }).clamp(0, len as i64) as usize;
+                let (from, to) = (position(start.unwrap_or(0)), position(end.unwrap_or(len as i64)));
+                from..to.max(from)
+            };
+            match target {
+                Value::List(elements) => Ok(Value::List(elements[range(elements.len())].to_vec())),
+                Value::String(s) => {
+                    let chars: Vec<char> = s.chars().collect();
+                    Ok(Value::String(chars[range(chars.len())].iter().collect()))
+                }
+                other => Err(RuntimeError::new(format!("Can't slice {}", other.described()))),
+            }
+        }
+        Expression::Match { subject, arms, default } => {
+            let subject = evaluate(subject, env)?;
+            for (pattern, value) in arms {
+                if binary(&BinaryOperator::Equal, subject.clone(), evaluate(pattern, env)?)? == Value::Bool(true) {
+                    return evaluate(value, env);
+                }
+            }
+            evaluate(default, env)
+        }
+        Expression::Call { callee, .. } => match &**callee {
+            Expression::Identifier(name) => Err(RuntimeError::new(format!("Calling '{}' isn't supported when evaluating an expression", name))),
+            _ => Err(RuntimeError::new("Calls aren't supported when evaluating an expression".to_string())),
+        },
+        _ => Err(RuntimeError::new("This kind of expression isn't supported when evaluating an expression".to_string())),
+    }
+}
+
+/// Applies an operator other than `&&`/`||`. An `int` meeting a `float` is converted to a `float`, as in C++.
+fn binary(op: &BinaryOperator, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    use BinaryOperator::*;
+    match (&left, &right) {
+        (_, Value::List(elements)) if *op == In => {
+            for element in elements {
+                if binary(&Equal, left.clone(), element.clone())? == Value::Bool(true) {
+                    return Ok(Value::Bool(true));
+                }
+            }
+            Ok(Value::Bool(false))
+        }
+        (Value::String(a), Value::String(b)) if *op == In => Ok(Value::Bool(b.contains(a.as_str()))),
+        (Value::Char(a), Value::String(b)) if *op == In => Ok(Value::Bool(b.contains(*a))),
+        // An optional is compared with `none` to see whether it holds a value.
+        (Value::None, _) | (_, Value::None) if matches!(op, Equal | NotEqual) => {
+            Ok(Value::Bool((left == Value::None && right == Value::None) == (*op == Equal)))
+        }
+        (Value::List(a), Value::List(b)) if matches!(op, Equal | NotEqual) => {
+            let mut equal = a.len() == b.len();
+            for (a, b) in a.iter().zip(b) {
+                if !equal {
+                    break;
+                }
+                equal = binary(&Equal, a.clone(), b.clone())? == Value::Bool(true);
+            }
+            Ok(Value::Bool(equal == (*op == Equal)))
+        }
+        (Value::Int(a), Value::Int(b)) => {
+            let result = match op {
+                Add => a.checked_add(*b),
+                Subtract => a.checked_sub(*b),
+                Multiply => a.checked_mul(*b),
+                Divide if *b == 0 => return Err(RuntimeError::new("Division by zero".to_string())),
+                Divide => a.checked_div(*b),
+                Modulo if *b == 0 => return Err(RuntimeError::new("Division by zero".to_string())),
+                Modulo => a.checked_rem(*b),
+                _ => return compare(op, a, b).ok_or_else(|| operand_error(op, &left, &right)),
+            };
+            result.filter(|n| i32::try_from(*n).is_ok())
+                .map(Value::Int)
+                .ok_or_else(|| RuntimeError::new(format!("{} {} {} overflows an int", a, op, b)))
+        }
+        (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+            let (a, b) = (as_float(&left), as_float(&right));
+            match op {
+                Add => Ok(Value::Float(a + b)),
+                Subtract => Ok(Value::Float(a - b)),
+                Multiply => Ok(Value::Float(a * b)),
+                Divide => Ok(Value::Float(a / b)),
+                _ => compare(op, &a, &b).ok_or_else(|| operand_error(op, &left, &right)),
+            }
+        }
+        (Value::String(a), Value::String(b)) => match op {
+            Add => Ok(Value::String(format!("{}{}", a, b))),
+            _ => compare(op, a, b).ok_or_else(|| operand_error(op, &left, &right)),
+        },
+        (Value::String(a), Value::Char(b)) if *op == Add => Ok(Value::String(format!("{}{}", a, b))),
+        (Value::Char(a), Value::Char(b)) => compare(op, a, b).ok_or_else(|| operand_error(op, &left, &right)),
+        (Value::Bool(a), Value::Bool(b)) => match op {
+            Equal => Ok(Value::Bool(a == b)),
+            NotEqual => Ok(Value::Bool(a != b)),
+            _ => Err(operand_error(op, &left, &right)),
+        },
+        _ => Err(operand_error(op, &left, &right)),
+    }
+}
+
+/// Applies a comparison operator, or returns `None` for any other operator.
+fn compare<T: PartialOrd + ?Sized>(op: &BinaryOperator, a: &T, b: &T) -> Option<Value> {
+    use BinaryOperator::*;
+    let result = match op {
+        Equal => a == b,
+        NotEqual => a != b,
+        GreaterThan => a > b,
+        LessThan => a < b,
+        GreaterThanEqual => a >= b,
+        LessThanEqual => a <= b,
+        _ => return None,
+    };
+    Some(Value::Bool(result))
+}
+
+fn as_float(value: &Value) -> f64 {
+    match value {
+        Value::Int(n) => *n as f64,
+        Value::Float(n) => *n,
+        _ => unreachable!("only called on numbers"),
+    }
+}
+
+fn operand_error(op: &BinaryOperator, left: &Value, right: &Value) -> RuntimeError {
+    RuntimeError::new(format!("Can't apply '{}' to {} and {}", op, left.described(), right.described()))
+}
@@ -1,21 +1,28 @@
+use crate::dialect::{unavailable, Features};
+use crate::source_map::ColumnMode;
+use std::collections::HashMap;
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     // Keywords
-    Var, Val, Def, Pub, Print, Return, If, Else, True, False, While, For, In, Import, Class, This, Try, Catch, New, Break, Continue,
+    Var, Val, Def, Pub, Print, Eprint, Return, If, Unless, Elif, Else, True, False, None, While, Loop, For, In, Import, Class, Enum, This, Try, Catch, New, Break, Continue, StaticAssert, Match,
 
     // Literals and Identifiers
-    Ident(String), Int(i64), Float(f64), Str(String), FString(String), Type(String),
+    Ident(String), Int(i64), Float(f64, String), Str(String), FString(String), Char(char), Type(String),
 
     // Operators and Punctuation
-    Op(String), Arrow, Colon, Assign, LParen, RParen, LBracket, RBracket, LBrace, RBrace, Comma, Newline, Range, Dot,
+    // `OpAssign` is a compound assignment like `+=` or `&&=`, holding the operator before the `=`.
+    Op(String), OpAssign(String), Arrow, FatArrow, Colon, Assign, LParen, RParen, LBracket, RBracket, LBrace, RBrace, Comma, Newline, Semicolon, Range, Ellipsis, Dot, Question, At,
 
     // Indentation
     Indent, Dedent,
 
+    // Trivia, only kept when the lexer is asked to (see `keep_comments`)
+    Comment(String),
+
     // Special
-    EOF,
+    Eof,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -23,11 +30,34 @@ pub struct Token {
     pub token_type: TokenType,
     pub line: usize,
     pub column: usize,
+    /// The whole-line `#` comments directly above this token, if it's the first token on its line.
+    pub doc: Option<String>,
 }
 
 impl Token {
     pub fn new(token_type: TokenType, line: usize, column: usize) -> Self {
-        Self { token_type, line, column }
+        Self { token_type, line, column, doc: None }
+    }
+}
+
+/// Attaches each run of whole-line comments to the first token of the line right below it.
+fn attach_doc_comments(tokens: &mut [Token], comments: &HashMap<usize, String>) {
+    let mut last_line = 0;
+    for token in tokens.iter_mut() {
+        if token.line == last_line || matches!(token.token_type, TokenType::Indent | TokenType::Dedent | TokenType::Newline | TokenType::Eof) {
+            continue;
+        }
+        last_line = token.line;
+        let mut lines = Vec::new();
+        let mut line = token.line;
+        while let Some(text) = comments.get(&(line - 1)) {
+            lines.push(text.as_str());
+            line -= 1;
+        }
+        if !lines.is_empty() {
+            lines.reverse();
+            token.doc = Some(lines.join("\n"));
+        }
     }
 }
 
@@ -44,11 +74,52 @@ impl fmt::Display for LexerError {
     }
 }
 
+/// The token a reserved word lexes to, or `None` if the word is an ordinary identifier.
+fn keyword_token(word: &str) -> Option<TokenType> {
+    let token_type = match word {
+        "var" => TokenType::Var, "val" => TokenType::Val, "def" => TokenType::Def,
+        "if" => TokenType::If, "unless" => TokenType::Unless, "elif" => TokenType::Elif, "else" => TokenType::Else, "pub" => TokenType::Pub,
+        "return" => TokenType::Return, "print" => TokenType::Print, "eprint" => TokenType::Eprint,
+        "true" => TokenType::True, "false" => TokenType::False, "none" => TokenType::None,
+        "while" => TokenType::While, "loop" => TokenType::Loop, "for" => TokenType::For, "in" => TokenType::In,
+        "import" => TokenType::Import, "class" => TokenType::Class, "enum" => TokenType::Enum, "this" => TokenType::This,
+        "try" => TokenType::Try, "catch" => TokenType::Catch, "new" => TokenType::New,
+        "break" => TokenType::Break, "continue" => TokenType::Continue, "static_assert" => TokenType::StaticAssert,
+        "match" => TokenType::Match,
+        // Word forms of the logical operators lex to the same tokens as their symbols.
+        "not" => TokenType::Op("!".to_string()), "and" => TokenType::Op("&&".to_string()),
+        "or" => TokenType::Op("||".to_string()),
+        "int" | "float" | "string" | "bool" | "char" | "list" | "void" | "dict" | "any" => TokenType::Type(word.to_string()),
+        _ => return None,
+    };
+    Some(token_type)
+}
+
+/// Whether `word` is reserved, i.e. can only be used as a name by writing it as a raw identifier (`r#word`).
+pub fn is_keyword(word: &str) -> bool {
+    keyword_token(word).is_some()
+}
+
+/// How many indented blocks may be open at once before lexing gives up, unless changed with `set_max_indent_depth`.
+pub const DEFAULT_MAX_INDENT_DEPTH: usize = 256;
+
 pub struct Lexer {
     input: Vec<char>,
     pos: usize,
     line: usize,
     column: usize,
+    /// Indentation decisions recorded for `--emit dedent-debug`, if tracing is on.
+    indent_trace: Option<Vec<String>>,
+    /// Whether leading whitespace opens and closes blocks. See `disable_indentation`.
+    significant_indentation: bool,
+    /// Whether comments become `Comment` tokens. See `keep_comments`.
+    keep_comments: bool,
+    /// What `column` counts.
+    column_mode: ColumnMode,
+    /// The syntax being accepted. Braced blocks are the lexer's concern.
+    features: Features,
+    /// How many indentation levels may be open at once.
+    max_indent_depth: usize,
 }
 
 impl Lexer {
@@ -58,6 +129,59 @@ impl Lexer {
             pos: 0,
             line: 1,
             column: 1,
+            indent_trace: None,
+            significant_indentation: true,
+            keep_comments: false,
+            column_mode: ColumnMode::default(),
+            features: Features::default(),
+            max_indent_depth: DEFAULT_MAX_INDENT_DEPTH,
+        }
+    }
+
+    /// Makes leading whitespace insignificant, for snippets embedded in other text where lines don't
+    /// start at a meaningful column. Blocks are written in braces after their `:` instead, as in
+    /// `if x > 0: { print(x) } else: { print(0) }`, and lex to the same tokens an indented block would.
+    pub fn disable_indentation(&mut self) {
+        self.significant_indentation = false;
+    }
+
+    /// Keeps every comment as a `Comment` token holding the text after its `#`, for the formatter.
+    /// The parser doesn't expect them, so tokens lexed this way can only be printed back out.
+    pub fn keep_comments(&mut self) {
+        self.keep_comments = true;
+    }
+
+    /// Sets what the columns of tokens and errors count: characters by default, or UTF-16 code units or bytes.
+    pub fn set_column_mode(&mut self, column_mode: ColumnMode) {
+        self.column_mode = column_mode;
+    }
+
+    /// Sets which newer syntax is accepted. See `Dialect`.
+    pub fn set_features(&mut self, features: Features) {
+        self.features = features;
+    }
+
+    /// Sets how many indented blocks may be open at once.
+    pub fn set_max_indent_depth(&mut self, max_indent_depth: usize) {
+        self.max_indent_depth = max_indent_depth;
+    }
+
+    /// Turns on recording of every indentation change, to debug whitespace problems.
+    pub fn trace_indentation(&mut self) {
+        self.indent_trace = Some(Vec::new());
+    }
+
+    /// The recorded indentation trace, one entry per line that changed the indent stack.
+    /// Entries recorded before a lexer error are kept, so the trace shows where things went wrong.
+    pub fn indent_trace(&self) -> &[String] {
+        self.indent_trace.as_deref().unwrap_or(&[])
+    }
+
+    /// Records a trace entry for the current line. The closure only runs when tracing is on.
+    fn trace(&mut self, entry: impl FnOnce() -> String) {
+        let line = self.line;
+        if let Some(trace) = &mut self.indent_trace {
+            trace.push(format!("line {}: {}", line, entry()));
         }
     }
 
@@ -67,18 +191,183 @@ impl Lexer {
                 self.line += 1;
                 self.column = 1;
             } else {
-                self.column += 1;
+                self.column += self.column_mode.width(self.input[self.pos]);
             }
             self.pos += 1;
         }
     }
 
+    /// Reads an identifier or keyword starting at the current position.
+    fn read_word(&mut self) -> String {
+        let mut word = String::new();
+        while self.pos < self.input.len() && (self.input[self.pos].is_alphanumeric() || self.input[self.pos] == '_') {
+            word.push(self.input[self.pos]);
+            self.advance();
+        }
+        word
+    }
+
+    /// Checks the `_` at the current position, which separates digits of a number as in `1_000_000`. It
+    /// has to sit between two digits, so it can't start or end the digits or come twice in a row.
+    fn check_digit_separator(&self, is_digit: impl Fn(char) -> bool) -> Result<(), LexerError> {
+        if !self.features.digit_separators {
+            return Err(LexerError { message: unavailable("Separating digits with '_'"), line: self.line, column: self.column });
+        }
+        let before = self.pos.checked_sub(1).map(|i| self.input[i]);
+        let after = self.input.get(self.pos + 1).copied();
+        let message = if before == Some('_') || after == Some('_') {
+            "Invalid number: digit separators can't be doubled, as in '1__000'"
+        } else if !before.is_some_and(&is_digit) {
+            "Invalid number: a '_' separating digits has to come after a digit"
+        } else if !after.is_some_and(&is_digit) {
+            "Invalid number: a '_' separating digits has to be followed by a digit"
+        } else {
+            return Ok(());
+        };
+        Err(LexerError { message: message.to_string(), line: self.line, column: self.column })
+    }
+
+    /// Reads an integer written with a `0x`, `0b`, or `0o` prefix, starting at the `0`. Every letter and
+    /// digit after the prefix belongs to the literal, so one that isn't a digit in `radix` is an error
+    /// at that character.
+    fn read_radix_int(&mut self, radix: u32, start_col: usize) -> Result<i64, LexerError> {
+        if !self.features.radix_ints {
+            return Err(LexerError { message: unavailable("Writing an integer in hex, binary, or octal"), line: self.line, column: start_col });
+        }
+        let prefix: String = self.input[self.pos..self.pos + 2].iter().collect();
+        self.advance();
+        self.advance();
+        let mut digits = String::new();
+        while let Some(&c) = self.input.get(self.pos).filter(|c| c.is_alphanumeric() || **c == '_') {
+            if c == '_' {
+                self.check_digit_separator(|c| c.is_digit(radix))?;
+                self.advance();
+                continue;
+            }
+            if !c.is_digit(radix) {
+                return Err(LexerError { message: format!("Invalid digit '{}' in a base-{} number", c, radix), line: self.line, column: self.column });
+            }
+            digits.push(c);
+            self.advance();
+        }
+        if digits.is_empty() {
+            return Err(LexerError { message: format!("Invalid number '{}': expected digits after the prefix", prefix), line: self.line, column: start_col });
+        }
+        i64::from_str_radix(&digits, radix)
+            .map_err(|_| LexerError { message: format!("Invalid integer: {}{}", prefix, digits), line: self.line, column: start_col })
+    }
+
+    /// Reads the body of a string or char literal up to the closing `quote`, decoding escapes, and
+    /// consumes the quote. Returns `None` if the input ends first.
+    fn read_quoted(&mut self, quote: char) -> Result<Option<String>, LexerError> {
+        let mut s = String::new();
+        while self.pos < self.input.len() {
+            let c = self.input[self.pos];
+            if c == quote {
+                self.advance();
+                return Ok(Some(s));
+            }
+            if c == '\\' {
+                self.advance();
+                if self.pos >= self.input.len() { break; }
+                s.push(self.read_escape()?);
+            } else {
+                s.push(c);
+            }
+            self.advance();
+        }
+        Ok(None)
+    }
+
+    /// Whether the `r` at the current position starts a raw string: `r"`, or `r` and one or more `#`s then `"`.
+    fn is_raw_string_start(&self) -> bool {
+        let hashes = self.input[self.pos + 1..].iter().take_while(|c| **c == '#').count();
+        self.input.get(self.pos + 1 + hashes) == Some(&'"')
+    }
+
+    /// Reads a raw string starting at its `#`s or opening quote, and consumes the closing delimiter.
+    /// Nothing inside is an escape, and the string only ends at a `"` followed by as many `#`s as
+    /// it opened with, so `r#"..."#` can contain a plain `"`. Returns `None` if the input ends first.
+    fn read_raw_string(&mut self) -> Option<String> {
+        let mut hashes = 0;
+        while self.input.get(self.pos) == Some(&'#') {
+            hashes += 1;
+            self.advance();
+        }
+        self.advance(); // consume the opening '"'
+        let mut s = String::new();
+        while self.pos < self.input.len() {
+            let c = self.input[self.pos];
+            if c == '"' && self.input[self.pos + 1..].iter().take(hashes).filter(|c| **c == '#').count() == hashes {
+                for _ in 0..=hashes {
+                    self.advance();
+                }
+                return Some(s);
+            }
+            s.push(c);
+            self.advance();
+        }
+        None
+    }
+
+    /// Decodes the escape sequence whose first character (the one after the `\`) is at the current
+    /// position, leaving the position on its last character. Besides `\n`, `\t`, `\r`, `\0`, `\\`,
+    /// `\"`, and `\'`, this handles `\xHH` (up to `\x7F`) and `\u{...}` (1 to 6 hex digits).
+    /// Any other escaped character stands for itself.
+    fn read_escape(&mut self) -> Result<char, LexerError> {
+        let (line, column) = (self.line, self.column.saturating_sub(1));
+        let error = |message: String| LexerError { message, line, column };
+        let decoded = match self.input[self.pos] {
+            'n' => '\n', 't' => '\t', 'r' => '\r', '0' => '\0',
+            'x' => {
+                let digits: String = self.input.iter().skip(self.pos + 1).take(2).collect();
+                let value = u8::from_str_radix(&digits, 16)
+                    .ok()
+                    .filter(|_| digits.len() == 2)
+                    .ok_or_else(|| error("Expected two hex digits after '\\x'".to_string()))?;
+                if value > 0x7F {
+                    return Err(error(format!("'\\x{}' is out of range: '\\x' escapes only go up to \\x7F; use '\\u{{{:X}}}' for other characters", digits, value)));
+                }
+                self.advance();
+                self.advance();
+                value as char
+            }
+            'u' => {
+                if self.input.get(self.pos + 1) != Some(&'{') {
+                    return Err(error("Expected '{' after '\\u', as in '\\u{41}'".to_string()));
+                }
+                let digits: String = self.input.iter().skip(self.pos + 2).take_while(|c| **c != '}' && **c != '\n').collect();
+                if self.input.get(self.pos + 2 + digits.chars().count()) != Some(&'}') {
+                    return Err(error("Unterminated unicode escape; expected '}'".to_string()));
+                }
+                if digits.is_empty() || digits.len() > 6 {
+                    return Err(error("A unicode escape needs 1 to 6 hex digits".to_string()));
+                }
+                let decoded = u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| error(format!("'\\u{{{}}}' is not a valid unicode character", digits)))?;
+                for _ in 0..digits.chars().count() + 2 {
+                    self.advance();
+                }
+                decoded
+            }
+            other => other,
+        };
+        Ok(decoded)
+    }
+
     pub fn tokenize(&mut self) -> Result<Vec<Token>, LexerError> {
         let mut tokens = Vec::new();
         let mut indent_stack = vec![0];
+        // The text of every comment that has a line to itself, by line number.
+        let mut comments = HashMap::new();
+        // Each `(`, `[`, and `{` that hasn't been closed yet, with where it is, so a stray closer can be pointed
+        // out, and whether it's a `{` that opens a block rather than a dict.
+        let mut open_delimiters: Vec<(char, usize, usize, bool)> = Vec::new();
 
         while self.pos < self.input.len() {
-            if self.column == 1 {
+            if self.column == 1 && self.significant_indentation {
                 let mut spaces = 0;
                 let mut lookahead = self.pos;
                 let mut is_empty_line = false;
@@ -86,7 +375,8 @@ impl Lexer {
                     match self.input[lookahead] {
                         ' ' => spaces += 1,
                         '\t' => spaces += 4,
-                        '\n' | '\r' => {
+                        // A line with only a comment on it doesn't open or close a block, however it's indented.
+                        '\n' | '\r' | '#' => {
                             is_empty_line = true;
                             break;
                         }
@@ -94,18 +384,32 @@ impl Lexer {
                     }
                     lookahead += 1;
                 }
+                // Whitespace running to the end of the file is a blank line too, not an unindent.
+                if lookahead == self.input.len() {
+                    is_empty_line = true;
+                }
 
                 if !is_empty_line {
                     let last_indent = *indent_stack.last().unwrap();
                     if spaces > last_indent {
+                        // The stack starts with the top level, so it holds one more entry than there are open blocks.
+                        if indent_stack.len() > self.max_indent_depth {
+                            return Err(LexerError { message: "indentation nested too deeply".to_string(), line: self.line, column: spaces + 1 });
+                        }
                         indent_stack.push(spaces);
                         tokens.push(Token::new(TokenType::Indent, self.line, self.column));
+                        self.trace(|| format!("indent {} -> {}, emit Indent, stack {:?}", last_indent, spaces, indent_stack));
                     } else if spaces < last_indent {
+                        let mut dedents = 0;
+                        // The bottom of the stack is 0, which no line is indented less than, so this never empties it.
                         while spaces < *indent_stack.last().unwrap() {
                             indent_stack.pop();
                             tokens.push(Token::new(TokenType::Dedent, self.line, self.column));
+                            dedents += 1;
                         }
+                        self.trace(|| format!("dedent {} -> {}, emit Dedent x{}, stack {:?}", last_indent, spaces, dedents, indent_stack));
                         if spaces != *indent_stack.last().unwrap() {
+                            self.trace(|| format!("error: {} does not match any level in stack {:?}", spaces, indent_stack));
                             return Err(LexerError { message: "Unindent does not match any outer indentation level".to_string(), line: self.line, column: self.column });
                         }
                     }
@@ -123,24 +427,64 @@ impl Lexer {
                 ' ' | '\r' | '\t' => { self.advance(); },
                 '\n' => { tokens.push(Token::new(TokenType::Newline, self.line, start_col)); self.advance(); },
                 ':' => { tokens.push(Token::new(TokenType::Colon, self.line, start_col)); self.advance(); },
-                '(' => { tokens.push(Token::new(TokenType::LParen, self.line, start_col)); self.advance(); },
-                ')' => { tokens.push(Token::new(TokenType::RParen, self.line, start_col)); self.advance(); },
-                '[' => { tokens.push(Token::new(TokenType::LBracket, self.line, start_col)); self.advance(); },
-                ']' => { tokens.push(Token::new(TokenType::RBracket, self.line, start_col)); self.advance(); },
-                '{' => { tokens.push(Token::new(TokenType::LBrace, self.line, start_col)); self.advance(); },
-                '}' => { tokens.push(Token::new(TokenType::RBrace, self.line, start_col)); self.advance(); },
+                // Without significant indentation, a `{` right after a `:` opens a block, unless it's in a
+                // bracket or dict, where the `:` is a dict's.
+                '{' if !self.significant_indentation
+                    && self.features.braced_blocks
+                    && tokens.last().is_some_and(|t: &Token| t.token_type == TokenType::Colon)
+                    && open_delimiters.last().is_none_or(|&(.., is_block)| is_block) => {
+                    open_delimiters.push((c, self.line, start_col, true));
+                    tokens.push(Token::new(TokenType::Newline, self.line, start_col));
+                    tokens.push(Token::new(TokenType::Indent, self.line, start_col));
+                    self.advance();
+                },
+                '(' | '[' | '{' => {
+                    open_delimiters.push((c, self.line, start_col, false));
+                    let token_type = match c { '(' => TokenType::LParen, '[' => TokenType::LBracket, _ => TokenType::LBrace };
+                    tokens.push(Token::new(token_type, self.line, start_col));
+                    self.advance();
+                },
+                ')' | ']' | '}' => {
+                    let (opener, token_type) = match c { ')' => ('(', TokenType::RParen), ']' => ('[', TokenType::RBracket), _ => ('{', TokenType::RBrace) };
+                    match open_delimiters.pop() {
+                        Some((.., true)) if c == '}' => {
+                            tokens.push(Token::new(TokenType::Newline, self.line, start_col));
+                            tokens.push(Token::new(TokenType::Dedent, self.line, start_col));
+                            self.advance();
+                            continue;
+                        }
+                        Some((open, ..)) if open == opener => {}
+                        Some((open, line, column, _)) => return Err(LexerError {
+                            message: format!("Unexpected '{}': the '{}' at line {}, column {} has to be closed first", c, open, line, column),
+                            line: self.line,
+                            column: start_col,
+                        }),
+                        None => return Err(LexerError { message: format!("Unexpected '{}' with no '{}' before it to close", c, opener), line: self.line, column: start_col }),
+                    }
+                    tokens.push(Token::new(token_type, self.line, start_col));
+                    self.advance();
+                },
                 ',' => { tokens.push(Token::new(TokenType::Comma, self.line, start_col)); self.advance(); },
+                ';' => { tokens.push(Token::new(TokenType::Semicolon, self.line, start_col)); self.advance(); },
+                '?' => { tokens.push(Token::new(TokenType::Question, self.line, start_col)); self.advance(); },
+                '@' => { tokens.push(Token::new(TokenType::At, self.line, start_col)); self.advance(); },
                 '=' => {
                     if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == '=' {
                         tokens.push(Token::new(TokenType::Op("==".to_string()), self.line, start_col));
                         self.advance(); self.advance();
+                    } else if self.input.get(self.pos + 1) == Some(&'>') {
+                        tokens.push(Token::new(TokenType::FatArrow, self.line, start_col));
+                        self.advance(); self.advance();
                     } else {
                         tokens.push(Token::new(TokenType::Assign, self.line, start_col));
                         self.advance();
                     }
                 },
                 '.' => {
-                    if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == '.' {
+                    if self.input.get(self.pos + 1) == Some(&'.') && self.input.get(self.pos + 2) == Some(&'.') {
+                        tokens.push(Token::new(TokenType::Ellipsis, self.line, start_col));
+                        self.advance(); self.advance(); self.advance();
+                    } else if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == '.' {
                         tokens.push(Token::new(TokenType::Range, self.line, start_col));
                         self.advance(); self.advance();
                     } else {
@@ -157,7 +501,24 @@ impl Lexer {
                         self.advance();
                     }
                 },
-                '+' | '*' | '/' => { tokens.push(Token::new(TokenType::Op(c.to_string()), self.line, start_col)); self.advance(); },
+                '&' | '|' if self.input.get(self.pos + 1) == Some(&c) && self.input.get(self.pos + 2) == Some(&'=') => {
+                    tokens.push(Token::new(TokenType::OpAssign(format!("{}{}", c, c)), self.line, start_col));
+                    self.advance(); self.advance(); self.advance();
+                },
+                '&' | '|' => {
+                    if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == c {
+                        tokens.push(Token::new(TokenType::Op(format!("{}{}", c, c)), self.line, start_col));
+                        self.advance(); self.advance();
+                    } else {
+                        return Err(LexerError { message: format!("Unexpected '{}'; did you mean '{}{}'?", c, c, c), line: self.line, column: start_col });
+                    }
+                },
+                // Comments start with `#`, so `/=` is always a compound assignment.
+                '+' | '-' | '*' | '/' | '%' if self.input.get(self.pos + 1) == Some(&'=') => {
+                    tokens.push(Token::new(TokenType::OpAssign(c.to_string()), self.line, start_col));
+                    self.advance(); self.advance();
+                },
+                '+' | '*' | '/' | '%' => { tokens.push(Token::new(TokenType::Op(c.to_string()), self.line, start_col)); self.advance(); },
                 '-' => {
                     if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == '>' {
                         tokens.push(Token::new(TokenType::Arrow, self.line, start_col));
@@ -167,96 +528,104 @@ impl Lexer {
                         self.advance();
                     }
                 },
-                '#' => { while self.pos < self.input.len() && self.input[self.pos] != '\n' { self.advance(); } },
+                '#' => {
+                    let whole_line = tokens.last().is_none_or(|t: &Token| t.line < self.line || matches!(t.token_type, TokenType::Indent | TokenType::Dedent));
+                    self.advance(); // consume '#'
+                    let mut text = String::new();
+                    while self.pos < self.input.len() && self.input[self.pos] != '\n' {
+                        text.push(self.input[self.pos]);
+                        self.advance();
+                    }
+                    if self.keep_comments {
+                        tokens.push(Token::new(TokenType::Comment(text.trim_end().to_string()), self.line, start_col));
+                    }
+                    if whole_line {
+                        let text = text.trim_end();
+                        comments.insert(self.line, text.strip_prefix(' ').unwrap_or(text).to_string());
+                    }
+                },
                 '"' => {
                     self.advance();
-                    let mut s = String::new();
-                    while self.pos < self.input.len() {
-                        if self.input[self.pos] == '"' { break; }
-                        if self.input[self.pos] == '\\' {
-                            self.advance();
-                            if self.pos < self.input.len() {
-                                match self.input[self.pos] {
-                                    'n' => s.push('\n'), 't' => s.push('\t'), 'r' => s.push('\r'),
-                                    '\\' => s.push('\\'), '"' => s.push('"'),
-                                    _ => s.push(self.input[self.pos]),
-                                }
-                            }
-                        } else { s.push(self.input[self.pos]); }
-                        self.advance();
+                    match self.read_quoted('"')? {
+                        Some(s) => tokens.push(Token::new(TokenType::Str(s), self.line, start_col)),
+                        None => return Err(LexerError { message: "Unterminated string literal".to_string(), line: self.line, column: start_col }),
                     }
-                    if self.pos < self.input.len() && self.input[self.pos] == '"' {
-                        tokens.push(Token::new(TokenType::Str(s), self.line, start_col));
-                        self.advance();
-                    } else {
-                        return Err(LexerError { message: "Unterminated string literal".to_string(), line: self.line, column: start_col });
+                },
+                '\'' => {
+                    let start_line = self.line;
+                    self.advance();
+                    let Some(s) = self.read_quoted('\'')? else {
+                        return Err(LexerError { message: "Unterminated char literal".to_string(), line: start_line, column: start_col });
+                    };
+                    let mut chars = s.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => tokens.push(Token::new(TokenType::Char(c), start_line, start_col)),
+                        (None, _) => return Err(LexerError { message: "Empty char literal; a char holds exactly one character".to_string(), line: start_line, column: start_col }),
+                        (Some(_), Some(_)) => return Err(LexerError {
+                            message: format!("Char literal '{}' has {} characters, but a char holds exactly one; use \"...\" for a string", s, s.chars().count()),
+                            line: start_line,
+                            column: start_col,
+                        }),
                     }
                 },
                 'f' => {
                     if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == '"' {
                         self.advance(); // consume 'f'
                         self.advance(); // consume '"'
-                        let mut s = String::new();
-                        while self.pos < self.input.len() {
-                            if self.input[self.pos] == '"' { break; }
-                            if self.input[self.pos] == '\\' {
-                                self.advance();
-                                if self.pos < self.input.len() {
-                                    match self.input[self.pos] {
-                                        'n' => s.push('\n'), 't' => s.push('\t'), 'r' => s.push('\r'),
-                                        '\\' => s.push('\\'), '"' => s.push('"'),
-                                        _ => s.push(self.input[self.pos]),
-                                    }
-                                }
-                            } else { s.push(self.input[self.pos]); }
-                            self.advance();
-                        }
-                        if self.pos < self.input.len() && self.input[self.pos] == '"' {
-                            tokens.push(Token::new(TokenType::FString(s), self.line, start_col));
-                            self.advance();
-                        } else {
-                            return Err(LexerError { message: "Unterminated f-string literal".to_string(), line: self.line, column: start_col });
+                        match self.read_quoted('"')? {
+                            Some(s) => tokens.push(Token::new(TokenType::FString(s), self.line, start_col)),
+                            None => return Err(LexerError { message: "Unterminated f-string literal".to_string(), line: self.line, column: start_col }),
                         }
                     } else {
                         // Normal identifier starting with 'f'
-                        let mut ident = String::new();
-                        while self.pos < self.input.len() && (self.input[self.pos].is_alphanumeric() || self.input[self.pos] == '_') {
-                            ident.push(self.input[self.pos]);
-                            self.advance();
-                        }
-                        let token_type = match ident.as_str() {
-                            "false" => TokenType::False,
-                            "for" => TokenType::For,
-                            "float" => TokenType::Type(ident),
-                            _ => TokenType::Ident(ident),
-                        };
+                        let ident = self.read_word();
+                        let token_type = keyword_token(&ident).unwrap_or(TokenType::Ident(ident));
                         tokens.push(Token::new(token_type, self.line, start_col));
                     }
                 },
-                _ if c.is_alphabetic() => {
-                    let mut ident = String::new();
-                    while self.pos < self.input.len() && (self.input[self.pos].is_alphanumeric() || self.input[self.pos] == '_') {
-                        ident.push(self.input[self.pos]);
-                        self.advance();
+                // A raw string, e.g. `r"C:\temp"` or `r#"say "hi""#`: backslashes are kept as written.
+                'r' if self.is_raw_string_start() => {
+                    let start_line = self.line;
+                    self.advance(); // consume 'r'
+                    match self.read_raw_string() {
+                        Some(s) => tokens.push(Token::new(TokenType::Str(s), self.line, start_col)),
+                        None => return Err(LexerError { message: "Unterminated raw string literal".to_string(), line: start_line, column: start_col }),
                     }
-                    let token_type = match ident.as_str() {
-                        "var" => TokenType::Var, "val" => TokenType::Val, "def" => TokenType::Def,
-                        "if" => TokenType::If, "else" => TokenType::Else, "pub" => TokenType::Pub,
-                        "return" => TokenType::Return, "print" => TokenType::Print,
-                        "true" => TokenType::True, "false" => TokenType::False,
-                        "while" => TokenType::While, "for" => TokenType::For, "in" => TokenType::In,
-                        "import" => TokenType::Import, "class" => TokenType::Class, "this" => TokenType::This,
-                        "try" => TokenType::Try, "catch" => TokenType::Catch, "new" => TokenType::New,
-                        "break" => TokenType::Break, "continue" => TokenType::Continue,
-                        "int" | "float" | "string" | "bool" | "list" | "void" | "dict" => TokenType::Type(ident),
-                        _ => TokenType::Ident(ident),
-                    };
+                },
+                // A raw identifier, e.g. `r#if`: a keyword used as an ordinary name.
+                'r' if self.input.get(self.pos + 1) == Some(&'#') => {
+                    self.advance(); // consume 'r'
+                    self.advance(); // consume '#'
+                    if !self.input.get(self.pos).is_some_and(|c| c.is_alphabetic() || *c == '_') {
+                        return Err(LexerError { message: "Expected an identifier after 'r#'".to_string(), line: self.line, column: self.column });
+                    }
+                    let ident = self.read_word();
+                    tokens.push(Token::new(TokenType::Ident(ident), self.line, start_col));
+                },
+                // `_5` reads as a number with a leading separator, so it's rejected rather than taken as a name.
+                '_' if self.input.get(self.pos + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                    return Err(LexerError { message: "Invalid number: a '_' separating digits has to come after a digit".to_string(), line: self.line, column: self.column });
+                },
+                _ if c.is_alphabetic() || c == '_' => {
+                    let ident = self.read_word();
+                    let token_type = keyword_token(&ident).unwrap_or(TokenType::Ident(ident));
                     tokens.push(Token::new(token_type, self.line, start_col));
                 },
+                '0' if matches!(self.input.get(self.pos + 1), Some('x' | 'b' | 'o')) => {
+                    let radix = match self.input[self.pos + 1] { 'x' => 16, 'b' => 2, _ => 8 };
+                    let n = self.read_radix_int(radix, start_col)?;
+                    tokens.push(Token::new(TokenType::Int(n), self.line, start_col));
+                },
                 _ if c.is_numeric() => {
                     let mut num = String::new();
                     let mut is_float = false;
-                    while self.pos < self.input.len() && (self.input[self.pos].is_numeric() || self.input[self.pos] == '.') {
+                    while self.pos < self.input.len() && (self.input[self.pos].is_numeric() || matches!(self.input[self.pos], '.' | '_')) {
+                        // `1_000` is `1000`; the separators aren't part of the value (or the spelling C++ sees).
+                        if self.input[self.pos] == '_' {
+                            self.check_digit_separator(|c| c.is_ascii_digit())?;
+                            self.advance();
+                            continue;
+                        }
                         if self.input[self.pos] == '.' {
                             if self.pos + 1 < self.input.len() && self.input[self.pos + 1] == '.' { break; }
                             if is_float { return Err(LexerError { message: "Invalid number: multiple decimal points".to_string(), line: self.line, column: self.column }); }
@@ -265,9 +634,34 @@ impl Lexer {
                         num.push(self.input[self.pos]);
                         self.advance();
                     }
+                    if matches!(self.input.get(self.pos), Some('e' | 'E')) {
+                        // `1e5` and `1e-5` have an exponent, but in `1 else` or `1example` the `e` starts a word.
+                        let sign = usize::from(matches!(self.input.get(self.pos + 1), Some('+' | '-')));
+                        if self.input.get(self.pos + 1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                            is_float = true;
+                            for _ in 0..=sign {
+                                num.push(self.input[self.pos]);
+                                self.advance();
+                            }
+                            while self.input.get(self.pos).is_some_and(|c| c.is_ascii_digit() || *c == '_') {
+                                if self.input[self.pos] == '_' {
+                                    self.check_digit_separator(|c| c.is_ascii_digit())?;
+                                } else {
+                                    num.push(self.input[self.pos]);
+                                }
+                                self.advance();
+                            }
+                        } else if !self.input.get(self.pos + 1).is_some_and(|c| c.is_alphanumeric() || *c == '_') {
+                            return Err(LexerError {
+                                message: format!("Invalid number '{}{}': an exponent needs digits after the '{}'", num, self.input[self.pos], self.input[self.pos]),
+                                line: self.line,
+                                column: start_col,
+                            });
+                        }
+                    }
                     let token_type = if is_float {
                         match num.parse() {
-                            Ok(n) => TokenType::Float(n),
+                            Ok(n) => TokenType::Float(n, num),
                             Err(_) => return Err(LexerError { message: format!("Invalid float: {}", num), line: self.line, column: start_col }),
                         }
                     } else {
@@ -282,11 +676,15 @@ impl Lexer {
             }
         }
 
+        if indent_stack.len() > 1 {
+            self.trace(|| format!("end of file, emit Dedent x{}, stack [0]", indent_stack.len() - 1));
+        }
         while indent_stack.len() > 1 {
             indent_stack.pop();
             tokens.push(Token::new(TokenType::Dedent, self.line, self.column));
         }
-        tokens.push(Token::new(TokenType::EOF, self.line, self.column));
+        tokens.push(Token::new(TokenType::Eof, self.line, self.column));
+        attach_doc_comments(&mut tokens, &comments);
         Ok(tokens)
     }
 }
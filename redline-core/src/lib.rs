@@ -0,0 +1,114 @@
+//! REDLINE Core: the lexer, parser, semantic checks, and C++ code generator behind the `redline-core` binary.
+pub mod ast;
+pub mod cfg;
+pub mod codegen;
+pub mod dialect;
+pub mod formatter;
+pub mod interpreter;
+pub mod lexer;
+pub mod lint;
+pub mod optimizer;
+pub mod parser;
+pub mod semantic;
+pub mod source_map;
+pub mod unparser;
+
+use ast::Program;
+use codegen::{generate, CodegenOptions, GenMode};
+use lexer::Lexer;
+use parser::{BuildFlags, Parser};
+use semantic::{SemanticOptions, Warning};
+use std::fmt;
+
+/// An error from any stage of the compiler, with the location it was reported at (0 when unknown).
+#[derive(Debug)]
+pub struct CompileError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.message, self.line, self.column)
+    }
+}
+
+impl From<lexer::LexerError> for CompileError {
+    fn from(e: lexer::LexerError) -> Self {
+        Self { message: e.message, line: e.line, column: e.column }
+    }
+}
+
+impl From<parser::ParserError> for CompileError {
+    fn from(e: parser::ParserError) -> Self {
+        Self { message: e.message, line: e.line, column: e.column }
+    }
+}
+
+impl From<semantic::SemanticError> for CompileError {
+    fn from(e: semantic::SemanticError) -> Self {
+        Self { message: e.message, line: e.line, column: e.column }
+    }
+}
+
+impl From<codegen::CodegenError> for CompileError {
+    fn from(e: codegen::CodegenError) -> Self {
+        Self { message: e.message, line: 0, column: 0 }
+    }
+}
+
+/// Lexes, parses, and semantically checks a source file, returning the program and its warnings.
+pub fn check_source(source: &str) -> Result<(Program, Vec<Warning>), CompileError> {
+    check_source_with(source, &SemanticOptions::default())
+}
+
+/// `check_source` with non-default semantic settings.
+pub fn check_source_with(source: &str, options: &SemanticOptions) -> Result<(Program, Vec<Warning>), CompileError> {
+    check_source_for(source, options, BuildFlags::default())
+}
+
+/// `check_source_with`, picking the `@if` blocks that apply to `flags`.
+fn check_source_for(source: &str, options: &SemanticOptions, flags: BuildFlags) -> Result<(Program, Vec<Warning>), CompileError> {
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.set_features(options.features);
+    let tokens = lexer.tokenize()?;
+    let mut parser = Parser::new(&tokens);
+    parser.set_flags(flags);
+    parser.set_features(options.features);
+    let program = parser.parse()?;
+    let warnings = semantic::analyze_with(&program, options)?;
+    Ok((program, warnings))
+}
+
+/// The compiler's version, as given in its `Cargo.toml`.
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Lexes, parses, and semantically checks a source file.
+pub fn parse_source(source: &str) -> Result<Program, CompileError> {
+    check_source(source).map(|(program, _)| program)
+}
+
+/// The build flags that `@if` conditions see when compiling with `options`.
+pub fn build_flags(options: &CodegenOptions) -> BuildFlags {
+    BuildFlags {
+        target: options.target.clone().unwrap_or_else(|| BuildFlags::default().target),
+        optimize: i64::from(options.optimize),
+    }
+}
+
+/// The semantic settings that go with compiling with `options`.
+pub fn semantic_options(options: &CodegenOptions) -> SemanticOptions {
+    SemanticOptions { auto_stringify: options.auto_stringify, features: options.features }
+}
+
+/// Runs the whole pipeline on a source file and returns the generated C++.
+pub fn compile(source: &str, mode: GenMode, module_name: &str, options: &CodegenOptions) -> Result<String, CompileError> {
+    let (mut program, _) = check_source_for(source, &semantic_options(options), build_flags(options))?;
+    if options.optimize {
+        optimizer::optimize(&mut program);
+    }
+    Ok(generate(&program, mode, module_name, options)?)
+}
@@ -0,0 +1,578 @@
+//! Lints: checks for code that is valid but probably not what was meant.
+//! Each lint has a name that `--allow`/`--deny` refer to, and reports through `semantic::Warning`.
+use crate::ast::{BinaryOperator, ClassMember, Expression, Literal, Param, Program, Span, Statement, DISCARD};
+use crate::optimizer::evaluate_constant;
+use crate::semantic::Warning;
+use std::collections::{HashMap, HashSet};
+
+/// An `if` whose condition is literally `true` or `false`.
+pub const CONSTANT_CONDITION: &str = "constant-condition";
+/// A comparison whose two sides are the same expression, like `x == x`.
+pub const SELF_COMPARISON: &str = "self-comparison";
+/// A comparison with a `bool` literal, like `done == true`.
+pub const BOOL_COMPARISON: &str = "bool-comparison";
+/// A `var` declared without a value, like `var x: int`, read where some path to the read hasn't assigned it.
+pub const UNINITIALIZED: &str = "uninitialized";
+/// A function, variable, or parameter with the name of a builtin, like `def len(...)`.
+pub const SHADOW_BUILTIN: &str = "shadow-builtin";
+/// `==` or `!=` with a `float` on either side, which rounding makes unreliable.
+pub const FLOAT_EQUALITY: &str = "float-equality";
+/// A loop whose body never runs: a `while` whose condition is always `false`, or a `for` over an empty range.
+pub const DEAD_LOOP: &str = "dead-loop";
+
+/// Every lint, by name.
+pub const LINTS: &[&str] = &[CONSTANT_CONDITION, SELF_COMPARISON, BOOL_COMPARISON, UNINITIALIZED, SHADOW_BUILTIN, FLOAT_EQUALITY, DEAD_LOOP];
+
+/// The functions and globals every program can use without importing anything.
+const BUILTINS: &[&str] = &[
+    "args", "len", "append", "sort", "reverse", "find", "contains", "to_string", "to_int", "to_float",
+    "range", "parse_int", "parse_float", "unwrap", "unwrap_or", "align_left", "align_right", "split", "join", "input",
+    "read_file", "write_file", "exists", "mkdir", "remove", "list_dir", "time", "sleep", "random_int", "random_float",
+    "abs", "min", "max", "pow", "sqrt", "floor", "ceil", "round", "sin", "cos", "tan", "exp", "log", "approx_eq",
+];
+
+/// How the findings of a lint are reported.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LintLevel {
+    /// Not reported at all.
+    Allow,
+    /// Reported as a warning. The default for every lint.
+    Warn,
+    /// Reported as an error that stops compilation.
+    Deny,
+}
+
+/// The level chosen for each lint, e.g. from `--allow self-comparison` or `--deny warnings`.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    levels: HashMap<String, LintLevel>,
+    /// Set by `warnings`, which stands for every lint that doesn't have a level of its own.
+    all: Option<LintLevel>,
+}
+
+impl LintLevels {
+    /// Sets the level of a lint, or of every lint when `name` is `warnings`.
+    /// Returns an error naming the lint if there's no such lint.
+    pub fn set(&mut self, name: &str, level: LintLevel) -> Result<(), String> {
+        if name == "warnings" {
+            self.all = Some(level);
+        } else if LINTS.contains(&name) {
+            self.levels.insert(name.to_string(), level);
+        } else {
+            return Err(format!("Unknown lint '{}'. Known lints: {}, or 'warnings' for all of them", name, LINTS.join(", ")));
+        }
+        Ok(())
+    }
+
+    pub fn level(&self, lint: &str) -> LintLevel {
+        self.levels.get(lint).copied().or(self.all).unwrap_or(LintLevel::Warn)
+    }
+}
+
+/// Runs every lint over the whole program.
+pub fn lint(program: &Program) -> Vec<Warning> {
+    let enums = program.statements.iter()
+        .filter_map(|s| match s {
+            Statement::Enum { name, variants, .. } => Some((name.clone(), variants.iter().map(|v| (v.name.clone(), v.value)).collect())),
+            _ => None,
+        })
+        .collect();
+    let mut linter = Linter { scopes: Vec::new(), enums, warnings: Vec::new() };
+    linter.check_block(&program.statements, Span::default());
+
+    let mut checker = InitChecker { scopes: vec![HashMap::new()], declared: 0, reported: HashSet::new(), warnings: linter.warnings };
+    checker.check_block(&program.statements, &mut Some(HashSet::new()), Span::default());
+    checker.warnings
+}
+
+struct Linter {
+    /// The names in scope, innermost last. A `val` whose value is a constant of its declared type maps
+    /// to that value, and every other name to `None`, so a shadowing declaration hides the outer one.
+    scopes: Vec<HashMap<String, Option<Literal>>>,
+    /// The value of each variant of each enum, by enum name.
+    enums: HashMap<String, HashMap<String, i64>>,
+    warnings: Vec<Warning>,
+}
+
+impl Linter {
+    /// Checks a block. Expressions don't have spans of their own, so findings point at the statement
+    /// they're in, or at `span` (the enclosing statement) for statements without one.
+    fn check_block(&mut self, statements: &[Statement], span: Span) {
+        self.check_scoped_block(statements, span, HashMap::new());
+    }
+
+    /// Checks a block in a scope that starts out holding `names`, like a loop variable.
+    fn check_scoped_block(&mut self, statements: &[Statement], span: Span, names: HashMap<String, Option<Literal>>) {
+        self.scopes.push(names);
+        for statement in statements {
+            self.check_shadowing(statement);
+            self.check_statement(statement, span);
+        }
+        self.scopes.pop();
+    }
+
+    /// Warns about a function, variable, or parameter named after a builtin. Class members aren't checked,
+    /// since they're always reached through an object and so can't be mistaken for the builtin.
+    fn check_shadowing(&mut self, statement: &Statement) {
+        match statement {
+            Statement::FunctionDefinition { name, params, span, .. } => {
+                self.check_builtin_name(name, "function", *span);
+                for param in params {
+                    self.check_builtin_name(&param.name, "parameter", *span);
+                }
+            }
+            Statement::Declaration { name, span, .. } => self.check_builtin_name(name, "variable", *span),
+            Statement::Destructure { names, span, .. } => {
+                for name in names {
+                    self.check_builtin_name(name, "variable", *span);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_builtin_name(&mut self, name: &str, kind: &str, span: Span) {
+        if BUILTINS.contains(&name) {
+            self.warnings.push(Warning::new(
+                SHADOW_BUILTIN,
+                format!("The {} '{}' has the same name as a builtin, which it hides wherever it's in scope", kind, name),
+                span,
+            ));
+        }
+    }
+
+    fn check_statement(&mut self, statement: &Statement, enclosing: Span) {
+        match statement {
+            Statement::Declaration { is_mutable, name, data_type, initializer, span, .. } => {
+                if let Some(initializer) = initializer {
+                    self.check_expression(initializer, *span);
+                }
+                if name != DISCARD {
+                    // As in `static_assert`, only a `val` whose value already has the declared type is a constant.
+                    let constant = initializer.as_ref()
+                        .filter(|_| !*is_mutable)
+                        .and_then(|initializer| self.evaluate_constant(initializer))
+                        .filter(|value| value.data_type().as_ref() == Some(data_type));
+                    if let Some(scope) = self.scopes.last_mut() {
+                        scope.insert(name.clone(), constant);
+                    }
+                }
+            }
+            Statement::Destructure { names, value, span, .. } => {
+                self.check_expression(value, *span);
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.extend(names.iter().filter(|name| *name != DISCARD).map(|name| (name.clone(), None)));
+                }
+            }
+            Statement::Assignment { targets, value, span, .. } => {
+                for target in targets {
+                    self.check_expression(target, *span);
+                }
+                self.check_expression(value, *span);
+            }
+            Statement::If { condition, consequence, alternative, span, .. } => {
+                self.check_expression(condition, *span);
+                self.check_block(consequence, *span);
+                if let Some(alt) = alternative {
+                    self.check_block(alt, *span);
+                }
+            }
+            Statement::While { condition, body, else_block, span, .. } => {
+                self.check_expression(condition, *span);
+                self.check_dead_loop(statement);
+                self.check_block(body, *span);
+                if let Some(block) = else_block {
+                    self.check_block(block, *span);
+                }
+            }
+            Statement::For { iterator, start, end, body, else_block, span, .. } => {
+                self.check_expression(start, *span);
+                self.check_expression(end, *span);
+                self.check_dead_loop(statement);
+                self.check_scoped_block(body, *span, HashMap::from([(iterator.clone(), None)]));
+                if let Some(block) = else_block {
+                    self.check_block(block, *span);
+                }
+            }
+            Statement::ForEach { iterator, iterable, body, else_block, span, .. } => {
+                self.check_expression(iterable, *span);
+                self.check_scoped_block(body, *span, HashMap::from([(iterator.clone(), None)]));
+                if let Some(block) = else_block {
+                    self.check_block(block, *span);
+                }
+            }
+            Statement::Print { values, span, .. } => {
+                for value in values {
+                    self.check_expression(value, *span);
+                }
+            }
+            Statement::Expression { expr, span } | Statement::Return { value: Some(expr), span } => self.check_expression(expr, *span),
+            // A function can't see the enclosing code's locals, so it starts from just its parameters.
+            Statement::FunctionDefinition { params, body, span, .. } => {
+                let params = params.iter().map(|param| (param.name.clone(), None)).collect();
+                let outer_scopes = std::mem::take(&mut self.scopes);
+                self.check_scoped_block(body, *span, params);
+                self.scopes = outer_scopes;
+            }
+            Statement::Class { members, span, .. } => {
+                // Fields are reached through the object, so they don't go into the enclosing scope.
+                self.scopes.push(HashMap::new());
+                for member in members {
+                    match member {
+                        ClassMember::Variable(declaration) => self.check_statement(declaration, *span),
+                        ClassMember::Method(method) | ClassMember::Constructor(method) => self.check_statement(method, *span),
+                    }
+                }
+                self.scopes.pop();
+            }
+            Statement::TryCatch { try_block, catch_var, catch_block } => {
+                self.check_block(try_block, enclosing);
+                self.check_scoped_block(catch_block, enclosing, HashMap::from([(catch_var.clone(), None)]));
+            }
+            _ => {}
+        }
+    }
+
+    /// Warns about a loop that can be seen not to run even once: a `while` whose condition is always
+    /// `false`, or a `for` whose range is empty, like `5..5`.
+    fn check_dead_loop(&mut self, statement: &Statement) {
+        let (reason, span) = match statement {
+            Statement::While { condition, span, .. } if self.evaluate_constant(condition) == Some(Literal::Bool(false)) => {
+                ("its condition is always false".to_string(), span)
+            }
+            Statement::For { start, end, span, .. } => match (self.evaluate_constant(start), self.evaluate_constant(end)) {
+                (Some(Literal::Int(start)), Some(Literal::Int(end))) if start >= end => (format!("the range {}..{} is empty", start, end), span),
+                _ => return,
+            },
+            _ => return,
+        };
+        self.warnings.push(Warning::new(DEAD_LOOP, format!("This loop body never executes because {}", reason), *span));
+    }
+
+    /// The value of an expression made of literals, constant `val`s in scope, and enum variants.
+    fn evaluate_constant(&self, expr: &Expression) -> Option<Literal> {
+        let lookup = |name: &str| self.scopes.iter().rev().find_map(|scope| scope.get(name));
+        evaluate_constant(expr, &|name| match name.split_once('.') {
+            Some((enum_name, variant)) if lookup(enum_name).is_none() => {
+                self.enums.get(enum_name)?.get(variant).map(|value| Literal::Int(*value))
+            }
+            Some(_) => None,
+            None => lookup(name).cloned().flatten(),
+        })
+    }
+
+    fn check_expression(&mut self, expr: &Expression, span: Span) {
+        match expr {
+            Expression::BinaryOp { op, left, right } => {
+                self.check_comparison(op, left, right, span);
+                self.check_expression(left, span);
+                self.check_expression(right, span);
+            }
+            Expression::Unary { operand, .. } => self.check_expression(operand, span),
+            Expression::Call { callee, args } => {
+                self.check_expression(callee, span);
+                for arg in args {
+                    self.check_expression(arg, span);
+                }
+            }
+            Expression::New { args, .. } | Expression::ListLiteral(args) | Expression::TupleLiteral(args) => {
+                for arg in args {
+                    self.check_expression(arg, span);
+                }
+            }
+            Expression::DictLiteral(entries) => {
+                for (key, value) in entries {
+                    self.check_expression(key, span);
+                    self.check_expression(value, span);
+                }
+            }
+            Expression::Index { list, index } => {
+                self.check_expression(list, span);
+                self.check_expression(index, span);
+            }
+            Expression::Slice { target, start, end } => {
+                self.check_expression(target, span);
+                for bound in [start, end].into_iter().flatten() {
+                    self.check_expression(bound, span);
+                }
+            }
+            Expression::Get { object, .. } => self.check_expression(object, span),
+            Expression::Assign { target, value } => {
+                self.check_expression(target, span);
+                self.check_expression(value, span);
+            }
+            Expression::Match { .. } => {
+                for child in expr.children() {
+                    self.check_expression(child, span);
+                }
+            }
+            Expression::Literal(_) | Expression::Identifier(_) | Expression::This => {}
+        }
+    }
+
+    fn check_comparison(&mut self, op: &BinaryOperator, left: &Expression, right: &Expression, span: Span) {
+        use BinaryOperator::*;
+        let always = match op {
+            Equal | LessThanEqual | GreaterThanEqual => true,
+            NotEqual | LessThan | GreaterThan => false,
+            _ => return,
+        };
+
+        // Calls are left alone: `roll() == roll()` compares two different results.
+        if left == right && is_pure(left) {
+            self.warnings.push(Warning::new(
+                SELF_COMPARISON,
+                format!("Comparing {} with itself is always {}", describe(left), always),
+                span,
+            ));
+            return;
+        }
+
+        if !matches!(op, Equal | NotEqual) {
+            return;
+        }
+        let (value, literal) = match (left, right) {
+            (Expression::Literal(_), Expression::Literal(_)) => return,
+            (value, Expression::Literal(Literal::Bool(b))) | (Expression::Literal(Literal::Bool(b)), value) => (value, *b),
+            _ => return,
+        };
+        // `x == true` and `x != false` are just `x`; the other two are `!x`.
+        let suggestion = match (describe_name(value), (*op == Equal) == literal) {
+            (Some(name), true) => format!("use '{}' directly", name),
+            (Some(name), false) => format!("use '!{}' instead", name),
+            (None, true) => "use the condition directly".to_string(),
+            (None, false) => "negate the condition with '!' instead".to_string(),
+        };
+        self.warnings.push(Warning::new(
+            BOOL_COMPARISON,
+            format!("Comparing with '{}' is redundant; {}", literal, suggestion),
+            span,
+        ));
+    }
+}
+
+/// The `var`s declared without a value that have definitely been assigned one by this point, by
+/// declaration number. `None` means this point can't be reached, e.g. because it's after a `return`.
+type Assigned = Option<HashSet<usize>>;
+
+/// Follows every path through each function (and the top-level code) to find reads of a `var`
+/// declared without a value that can happen before it's assigned one.
+struct InitChecker {
+    /// The names in scope, innermost last. Each `var` declared without a value maps to its declaration
+    /// number, and every other name to `None`, so a shadowing declaration hides the outer one.
+    scopes: Vec<HashMap<String, Option<usize>>>,
+    /// How many `var`s without a value have been declared, for numbering the next one.
+    declared: usize,
+    /// Declarations that have already been warned about, so each is only reported once.
+    reported: HashSet<usize>,
+    warnings: Vec<Warning>,
+}
+
+impl InitChecker {
+    fn check_block(&mut self, statements: &[Statement], assigned: &mut Assigned, span: Span) {
+        self.check_scoped_block(statements, assigned, span, HashMap::new());
+    }
+
+    /// Checks a block in a scope that starts out holding `names`, like a loop variable.
+    fn check_scoped_block(&mut self, statements: &[Statement], assigned: &mut Assigned, span: Span, names: HashMap<String, Option<usize>>) {
+        self.scopes.push(names);
+        for statement in statements {
+            self.check_statement(statement, assigned, span);
+        }
+        self.scopes.pop();
+    }
+
+    fn check_statement(&mut self, statement: &Statement, assigned: &mut Assigned, enclosing: Span) {
+        match statement {
+            Statement::Declaration { name, initializer, span, .. } => {
+                if let Some(initializer) = initializer {
+                    self.check_reads(initializer, assigned, *span);
+                }
+                if name != DISCARD {
+                    let number = initializer.is_none().then(|| {
+                        self.declared += 1;
+                        self.declared
+                    });
+                    self.scopes.last_mut().expect("a block is always in scope").insert(name.clone(), number);
+                }
+            }
+            Statement::Destructure { names, value, span, .. } => {
+                self.check_reads(value, assigned, *span);
+                let scope = self.scopes.last_mut().expect("a block is always in scope");
+                scope.extend(names.iter().filter(|name| *name != DISCARD).map(|name| (name.clone(), None)));
+            }
+            Statement::Assignment { targets, op, value, span } => {
+                self.check_reads(value, assigned, *span);
+                // Assigning to a name doesn't read it, but `items[i] = x` reads `items` and `i`, and `n += 1` reads `n`.
+                for target in targets.iter().filter(|target| op.is_some() || !matches!(target, Expression::Identifier(_))) {
+                    self.check_reads(target, assigned, *span);
+                }
+                for target in targets {
+                    if let (Expression::Identifier(name), Some(assigned)) = (target, assigned.as_mut()) {
+                        assigned.extend(self.lookup(name));
+                    }
+                }
+            }
+            Statement::If { condition, consequence, alternative, span, .. } => {
+                self.check_reads(condition, assigned, *span);
+                let mut then_assigned = assigned.clone();
+                self.check_block(consequence, &mut then_assigned, *span);
+                let mut else_assigned = assigned.clone();
+                if let Some(alt) = alternative {
+                    self.check_block(alt, &mut else_assigned, *span);
+                }
+                *assigned = merge(then_assigned, else_assigned);
+            }
+            // A loop body might not run at all, so nothing it assigns counts after the loop, and the
+            // same goes for an `else` block, which a `break` skips. The condition always runs, though.
+            Statement::While { condition, body, else_block, span, .. } => {
+                self.check_reads(condition, assigned, *span);
+                self.check_block(body, &mut assigned.clone(), *span);
+                if let Some(block) = else_block {
+                    self.check_block(block, &mut assigned.clone(), *span);
+                }
+            }
+            Statement::For { iterator, start, end, body, else_block, span, .. } => {
+                self.check_reads(start, assigned, *span);
+                self.check_reads(end, assigned, *span);
+                self.check_scoped_block(body, &mut assigned.clone(), *span, HashMap::from([(iterator.clone(), None)]));
+                if let Some(block) = else_block {
+                    self.check_block(block, &mut assigned.clone(), *span);
+                }
+            }
+            Statement::ForEach { iterator, iterable, body, else_block, span, .. } => {
+                self.check_reads(iterable, assigned, *span);
+                self.check_scoped_block(body, &mut assigned.clone(), *span, HashMap::from([(iterator.clone(), None)]));
+                if let Some(block) = else_block {
+                    self.check_block(block, &mut assigned.clone(), *span);
+                }
+            }
+            Statement::Print { values, span, .. } => {
+                for value in values {
+                    self.check_reads(value, assigned, *span);
+                }
+            }
+            Statement::Expression { expr, span } => self.check_reads(expr, assigned, *span),
+            Statement::Return { value, span } => {
+                if let Some(value) = value {
+                    self.check_reads(value, assigned, *span);
+                }
+                *assigned = None;
+            }
+            Statement::Break { .. } | Statement::Continue { .. } => *assigned = None,
+            Statement::FunctionDefinition { params, body, span, .. } => self.check_function(params, body, *span),
+            Statement::Class { members, .. } => {
+                for member in members {
+                    if let ClassMember::Method(Statement::FunctionDefinition { params, body, span, .. })
+                        | ClassMember::Constructor(Statement::FunctionDefinition { params, body, span, .. }) = member {
+                        self.check_function(params, body, *span);
+                    }
+                }
+            }
+            // The `catch` block can start partway through the `try` block, so it only gets to count on
+            // what was assigned before the `try`.
+            Statement::TryCatch { try_block, catch_var, catch_block } => {
+                let mut try_assigned = assigned.clone();
+                self.check_block(try_block, &mut try_assigned, enclosing);
+                let mut catch_assigned = assigned.clone();
+                self.check_scoped_block(catch_block, &mut catch_assigned, enclosing, HashMap::from([(catch_var.clone(), None)]));
+                *assigned = merge(try_assigned, catch_assigned);
+            }
+            Statement::Import(_) | Statement::Enum { .. } | Statement::StaticAssert { .. } => {}
+        }
+    }
+
+    /// A function can't see the enclosing code's locals, so it starts from just its parameters.
+    fn check_function(&mut self, params: &[Param], body: &[Statement], span: Span) {
+        let params = params.iter().map(|param| (param.name.clone(), None)).collect();
+        let outer_scopes = std::mem::replace(&mut self.scopes, vec![params]);
+        self.check_block(body, &mut Some(HashSet::new()), span);
+        self.scopes = outer_scopes;
+    }
+
+    /// Checks the reads in an expression, and records what the assignments in it, like `(n = value)`, assign.
+    fn check_reads(&mut self, expr: &Expression, assigned: &mut Assigned, span: Span) {
+        match expr {
+            Expression::Identifier(name) => {
+                if let (Some(number), Some(assigned)) = (self.lookup(name), assigned.as_ref()) {
+                    if !assigned.contains(&number) && self.reported.insert(number) {
+                        self.warnings.push(Warning::new(
+                            UNINITIALIZED,
+                            format!("'{}' was declared without a value and might not have been assigned one yet", name),
+                            span,
+                        ));
+                    }
+                }
+            }
+            // The right side of `&&` and `||` doesn't always run, so what it assigns doesn't count afterwards.
+            Expression::BinaryOp { op: BinaryOperator::And | BinaryOperator::Or, left, right } => {
+                self.check_reads(left, assigned, span);
+                self.check_reads(right, &mut assigned.clone(), span);
+            }
+            Expression::Assign { target, value } => {
+                self.check_reads(value, assigned, span);
+                if let (Expression::Identifier(name), Some(assigned)) = (&**target, assigned.as_mut()) {
+                    assigned.extend(self.lookup(name));
+                }
+            }
+            // Only the subject and the first pattern are sure to run.
+            Expression::Match { subject, arms, default } => {
+                self.check_reads(subject, assigned, span);
+                if let Some((pattern, _)) = arms.first() {
+                    self.check_reads(pattern, assigned, span);
+                }
+                for child in arms.iter().flat_map(|(pattern, value)| [pattern, value]).skip(1).chain([&**default]) {
+                    self.check_reads(child, &mut assigned.clone(), span);
+                }
+            }
+            _ => {
+                for child in expr.children() {
+                    self.check_reads(child, assigned, span);
+                }
+            }
+        }
+    }
+
+    /// The declaration number of the `var` without a value that `name` refers to, if it does.
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).copied().flatten()
+    }
+}
+
+/// What's assigned after two paths join: only what both assigned, ignoring a path that can't get there.
+fn merge(a: Assigned, b: Assigned) -> Assigned {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.intersection(&b).copied().collect()),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Whether evaluating the expression twice is guaranteed to give the same value.
+fn is_pure(expr: &Expression) -> bool {
+    match expr {
+        Expression::Literal(_) | Expression::Identifier(_) | Expression::This => true,
+        Expression::Get { object, .. } => is_pure(object),
+        Expression::Index { list, index } => is_pure(list) && is_pure(index),
+        Expression::BinaryOp { left, right, .. } => is_pure(left) && is_pure(right),
+        Expression::Unary { operand, .. } => is_pure(operand),
+        _ => false,
+    }
+}
+
+/// The source spelling of a plain name or member access, like `count` or `this.count`.
+fn describe_name(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Identifier(name) => Some(name.clone()),
+        Expression::This => Some("this".to_string()),
+        Expression::Get { object, name } => describe_name(object).map(|object| format!("{}.{}", object, name)),
+        _ => None,
+    }
+}
+
+fn describe(expr: &Expression) -> String {
+    match describe_name(expr) {
+        Some(name) => format!("'{}'", name),
+        None => "an expression".to_string(),
+    }
+}
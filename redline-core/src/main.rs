@@ -2,18 +2,76 @@ use std::env;
 use std::fs;
 use std::process;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
-mod codegen;
-mod lexer;
-mod parser;
-mod ast;
+use redline_core::{build_flags, semantic_options, version};
+use redline_core::cfg;
+use redline_core::formatter;
+use redline_core::lexer::Lexer;
+use redline_core::parser::Parser;
+use redline_core::semantic;
+use redline_core::optimizer;
+use redline_core::source_map::ColumnMode;
+use redline_core::lint::{LintLevel, LintLevels};
+use redline_core::dialect::Dialect;
+use redline_core::codegen::{generate, GenMode, CodegenOptions, IntDivMode};
 
-use lexer::Lexer;
-use parser::Parser;
-use codegen::{generate, GenMode};
+/// The flags that are followed by a value, so the value isn't mistaken for a file to compile.
+const FLAGS_WITH_VALUES: &[&str] = &["--gen", "--int-div", "--emit", "--allow", "--deny", "--target", "--column-mode", "--dialect"];
 
-fn report_error(file_path: &str, input: &str, message: &str, line: usize, column: usize) {
-    eprintln!("\nError: {}", message);
+/// Debug output requested with `--emit`, printed instead of the generated code.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Emit {
+    /// The lexer's indentation decisions, line by line.
+    DedentDebug,
+    /// The control-flow graph of each function, in Graphviz `.dot` format.
+    Cfg,
+}
+
+/// Wall-clock time spent in each compiler phase, collected for `--profile`.
+struct Profile {
+    enabled: bool,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Profile {
+    fn new(enabled: bool) -> Self {
+        Self { enabled, phases: Vec::new() }
+    }
+
+    /// Records the time since `started` as the duration of `phase`.
+    fn record(&mut self, phase: &'static str, started: Instant) {
+        if self.enabled {
+            self.phases.push((phase, started.elapsed()));
+        }
+    }
+
+    /// Prints the recorded phases as a table on stderr, so it doesn't mix with the generated code.
+    fn report(&self) {
+        if !self.enabled {
+            return;
+        }
+        eprintln!("{:<10} {:>10}", "phase", "time (ms)");
+        for (phase, duration) in &self.phases {
+            eprintln!("{:<10} {:>10.3}", phase, duration.as_secs_f64() * 1000.0);
+        }
+        let total: Duration = self.phases.iter().map(|(_, duration)| *duration).sum();
+        eprintln!("{:<10} {:>10.3}", "total", total.as_secs_f64() * 1000.0);
+    }
+}
+
+fn report_error(file_path: &str, input: &str, message: &str, line: usize, column: usize, column_mode: ColumnMode) {
+    report_diagnostic("Error", file_path, input, message, line, column, column_mode);
+}
+
+fn report_warning(file_path: &str, input: &str, message: &str, line: usize, column: usize, column_mode: ColumnMode) {
+    report_diagnostic("Warning", file_path, input, message, line, column, column_mode);
+}
+
+/// Prints a diagnostic with the line it's on. `column` is counted the way `column_mode` says, which is
+/// how it's shown in the location, but the caret under the line is placed by characters.
+fn report_diagnostic(kind: &str, file_path: &str, input: &str, message: &str, line: usize, column: usize, column_mode: ColumnMode) {
+    eprintln!("\n{}: {}", kind, message);
     eprintln!("  --> {}:{}:{}", file_path, line, column);
 
     if line > 0 {
@@ -23,23 +81,41 @@ fn report_error(file_path: &str, input: &str, message: &str, line: usize, column
 
             eprintln!("{:>width$} |", "", width = line_num_width);
             eprintln!("{} | {}", line, line_str);
-            eprintln!("{:>width$} | {:>col$}", "", "^", width = line_num_width, col = column);
+            let caret = column_mode.chars_before(line_str, column) + 1;
+            eprintln!("{:>width$} | {:>col$}", "", "^", width = line_num_width, col = caret);
         }
     }
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: redline-core <file.rl> [--json-ast | --gen <hpp|cpp>]");
+    // `--version` doesn't need a file, so it's answered before looking for one.
+    if args.iter().skip(1).any(|arg| arg == "--version" || arg == "-V") {
+        println!("redline-core {}", version());
+        return;
+    }
+    // Every argument that isn't a flag (or a flag's value) is a file. Several files are compiled
+    // together into one program, in the order they're given.
+    let mut file_paths: Vec<&str> = Vec::new();
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        if FLAGS_WITH_VALUES.contains(&arg.as_str()) {
+            rest.next();
+        } else if !arg.starts_with('-') {
+            file_paths.push(arg);
+        }
+    }
+    if file_paths.is_empty() {
+        eprintln!("Usage: redline-core --version | <file.rl>... [--json-ast | --gen <hpp|cpp> | --format] [--int-div <trunc|floor>] [--target <name>] [--constexpr-functions] [-O] [--emit-docs] [--auto-stringify] [--nodiscard] [--noexcept] [--no-main-wrapper] [--dump-symbols] [--emit <dedent-debug|cfg>] [--profile] [--allow <lint>] [--deny <lint>] [--column-mode <utf8-scalar|utf16|byte>] [--dialect <conservative|latest>]");
         process::exit(1);
     }
 
-    let file_path_arg = &args[1];
-    let module_name = Path::new(file_path_arg).file_stem().unwrap().to_str().unwrap();
+    // The generated code is named after the first file.
+    let module_name = Path::new(file_paths[0]).file_stem().unwrap().to_str().unwrap();
 
     let mut gen_mode = GenMode::Cpp; // Default to Cpp
     let mut dump_json_ast = false;
+    let mut options = CodegenOptions::default();
 
     if let Some(gen_flag_pos) = args.iter().position(|arg| arg == "--gen") {
         if let Some(mode_str) = args.get(gen_flag_pos + 1) {
@@ -59,29 +135,220 @@ fn main() {
         dump_json_ast = true;
     }
 
-    let content = match fs::read_to_string(file_path_arg) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("Error reading file [{}]: {}", file_path_arg, e);
+    if let Some(flag_pos) = args.iter().position(|arg| arg == "--int-div") {
+        options.int_div = match args.get(flag_pos + 1).map(|s| s.as_str()) {
+            Some("trunc") => IntDivMode::Trunc,
+            Some("floor") => IntDivMode::Floor,
+            _ => {
+                eprintln!("Invalid or missing value for --int-div flag. Use 'trunc' or 'floor'.");
+                process::exit(1);
+            }
+        };
+    }
+
+    options.constexpr_functions = args.iter().any(|arg| arg == "--constexpr-functions");
+    options.optimize = args.iter().any(|arg| arg == "-O");
+    options.emit_docs = args.iter().any(|arg| arg == "--emit-docs");
+    options.auto_stringify = args.iter().any(|arg| arg == "--auto-stringify");
+    options.nodiscard = args.iter().any(|arg| arg == "--nodiscard");
+    options.noexcept = args.iter().any(|arg| arg == "--noexcept");
+    options.no_main_wrapper = args.iter().any(|arg| arg == "--no-main-wrapper");
+
+    if let Some(flag_pos) = args.iter().position(|arg| arg == "--target") {
+        match args.get(flag_pos + 1) {
+            Some(target) => options.target = Some(target.clone()),
+            None => {
+                eprintln!("Missing value for --target flag.");
+                process::exit(1);
+            }
+        }
+    }
+    let flags = build_flags(&options);
+
+    if let Some(flag_pos) = args.iter().position(|arg| arg == "--dialect") {
+        match args.get(flag_pos + 1).and_then(|name| Dialect::from_name(name)) {
+            Some(dialect) => options.features = dialect.features(),
+            None => {
+                eprintln!("Invalid or missing value for --dialect flag. Use 'conservative' or 'latest'.");
+                process::exit(1);
+            }
+        }
+    }
+
+    let mut column_mode = ColumnMode::default();
+    if let Some(flag_pos) = args.iter().position(|arg| arg == "--column-mode") {
+        column_mode = match args.get(flag_pos + 1).map(|s| s.as_str()) {
+            Some("utf8-scalar") => ColumnMode::Utf8Scalar,
+            Some("utf16") => ColumnMode::Utf16,
+            Some("byte") => ColumnMode::Byte,
+            _ => {
+                eprintln!("Invalid or missing value for --column-mode flag. Use 'utf8-scalar', 'utf16', or 'byte'.");
+                process::exit(1);
+            }
+        };
+    }
+
+    let mut emit = None;
+    if let Some(flag_pos) = args.iter().position(|arg| arg == "--emit") {
+        emit = match args.get(flag_pos + 1).map(|s| s.as_str()) {
+            Some("dedent-debug") => Some(Emit::DedentDebug),
+            Some("cfg") => Some(Emit::Cfg),
+            _ => {
+                eprintln!("Invalid or missing value for --emit flag. Use 'dedent-debug' or 'cfg'.");
+                process::exit(1);
+            }
+        };
+    }
+
+    let mut lint_levels = LintLevels::default();
+    for (i, arg) in args.iter().enumerate() {
+        let level = match arg.as_str() {
+            "--allow" => LintLevel::Allow,
+            "--deny" => LintLevel::Deny,
+            _ => continue,
+        };
+        let Some(name) = args.get(i + 1) else {
+            eprintln!("Missing lint name for {} flag.", arg);
+            process::exit(1);
+        };
+        if let Err(message) = lint_levels.set(name, level) {
+            eprintln!("{}", message);
             process::exit(1);
         }
-    };
+    }
+
+    let mut profile = Profile::new(args.iter().any(|arg| arg == "--profile"));
+
+    let mut contents = Vec::new();
+    for file_path in &file_paths {
+        match fs::read_to_string(file_path) {
+            Ok(c) => contents.push(c),
+            Err(e) => {
+                eprintln!("Error reading file [{}]: {}", file_path, e);
+                process::exit(1);
+            }
+        }
+    }
+
+    // `--format` only needs each file's tokens, and prints the files back out instead of compiling them.
+    if args.iter().any(|arg| arg == "--format") {
+        for (file_path, content) in file_paths.iter().zip(&contents) {
+            match formatter::format_source(content) {
+                Ok(formatted) => print!("{}", formatted),
+                // The formatter's lexer counts columns in characters, whatever `--column-mode` says.
+                Err(e) => {
+                    report_error(file_path, content, &e.message, e.line, e.column, ColumnMode::default());
+                    process::exit(1);
+                }
+            }
+        }
+        return;
+    }
+
+    let mut token_lists = Vec::new();
+    let started = Instant::now();
+    for (file_path, content) in file_paths.iter().zip(&contents) {
+        let mut lexer = Lexer::new(content.clone());
+        lexer.set_column_mode(column_mode);
+        lexer.set_features(options.features);
+        if emit == Some(Emit::DedentDebug) {
+            lexer.trace_indentation();
+        }
+        let lex_result = lexer.tokenize();
+        for entry in lexer.indent_trace() {
+            println!("{}", entry);
+        }
+        match lex_result {
+            Ok(t) => token_lists.push(t),
+            Err(e) => {
+                report_error(file_path, content, &e.message, e.line, e.column, column_mode);
+                process::exit(1);
+            }
+        }
+    }
+    profile.record("lex", started);
+    if emit == Some(Emit::DedentDebug) {
+        profile.report();
+        return;
+    }
 
-    let tokens = match Lexer::new(content.clone()).tokenize() {
-        Ok(t) => t,
+    let mut programs = Vec::new();
+    let started = Instant::now();
+    for (file, tokens) in token_lists.iter().enumerate() {
+        let mut parser = Parser::new(tokens);
+        parser.set_file(file);
+        parser.set_flags(flags.clone());
+        parser.set_features(options.features);
+        parser.set_recovery(true);
+        let parsed = parser.parse();
+        let recovered = parser.take_recovered();
+        for e in &recovered {
+            report_error(file_paths[file], &contents[file], &e.message, e.line, e.column, column_mode);
+        }
+        match parsed {
+            Ok(p) if recovered.is_empty() => programs.push(p),
+            Ok(_) => process::exit(1),
+            Err(e) => {
+                report_error(file_paths[file], &contents[file], &e.message, e.line, e.column, column_mode);
+                process::exit(1);
+            }
+        }
+    }
+    profile.record("parse", started);
+    let mut program = match semantic::merge(programs, &file_paths) {
+        Ok(p) => p,
         Err(e) => {
-            report_error(file_path_arg, &content, &e.message, e.line, e.column);
+            report_error(file_paths[e.file], &contents[e.file], &e.message, e.line, e.column, column_mode);
             process::exit(1);
         }
     };
 
-    let program = match Parser::new(&tokens).parse() {
-        Ok(p) => p,
+    let started = Instant::now();
+    let analysis = semantic::analyze_with(&program, &semantic_options(&options));
+    profile.record("semantic", started);
+    match analysis {
+        Ok(warnings) => {
+            let mut denied = false;
+            for w in &warnings {
+                let message = format!("{} [{}]", w.message, w.lint);
+                match lint_levels.level(w.lint) {
+                    LintLevel::Allow => {}
+                    LintLevel::Warn => report_warning(file_paths[w.file], &contents[w.file], &message, w.line, w.column, column_mode),
+                    LintLevel::Deny => {
+                        report_error(file_paths[w.file], &contents[w.file], &message, w.line, w.column, column_mode);
+                        denied = true;
+                    }
+                }
+            }
+            if denied {
+                process::exit(1);
+            }
+        }
         Err(e) => {
-            report_error(file_path_arg, &content, &e.message, e.line, e.column);
+            report_error(file_paths[e.file], &contents[e.file], &e.message, e.line, e.column, column_mode);
             process::exit(1);
         }
-    };
+    }
+
+    if args.iter().any(|arg| arg == "--dump-symbols") {
+        for symbol in semantic::symbols(&program) {
+            println!("{}", symbol);
+        }
+        profile.report();
+        return;
+    }
+
+    if emit == Some(Emit::Cfg) {
+        print!("{}", cfg::to_dot(&cfg::build(&program)));
+        profile.report();
+        return;
+    }
+
+    if options.optimize {
+        let started = Instant::now();
+        optimizer::optimize(&mut program);
+        profile.record("optimize", started);
+    }
 
     if dump_json_ast {
         match serde_json::to_string_pretty(&program) {
@@ -92,12 +359,16 @@ fn main() {
             }
         }
     } else {
-        match generate(&program, gen_mode, module_name) {
+        let started = Instant::now();
+        let generated = generate(&program, gen_mode, module_name, &options);
+        profile.record("codegen", started);
+        match generated {
             Ok(code) => println!("{}", code),
             Err(e) => {
-                eprintln!("Codegen Error: {}", e);
+                eprintln!("{}", e);
                 process::exit(1);
             }
         }
     }
+    profile.report();
 }
@@ -0,0 +1,1007 @@
+//! AST-level optimizations, enabled with `-O`.
+//! These run after semantic analysis and rewrite the program in place before codegen.
+use crate::ast::{Program, Statement, Expression, Literal, BinaryOperator, UnaryOperator, ClassMember, Type, Param, Span, DISCARD};
+use std::collections::{HashMap, HashSet};
+
+/// Functions whose body has at most this many statements and expressions are inlined at their call sites.
+const INLINE_SIZE_LIMIT: usize = 24;
+
+/// Runs every optimization pass over the program.
+pub fn optimize(program: &mut Program) {
+    let mut optimizer = Optimizer::new(program);
+    optimizer.optimize_block(&mut program.statements);
+}
+
+struct Optimizer {
+    /// Names in scope, innermost block last. `Some` holds the literal value of a `val`
+    /// that can be substituted into its uses; `None` marks a name that isn't constant.
+    scopes: Vec<HashMap<String, Option<Literal>>>,
+    /// Functions that are inlined at their call sites, by name.
+    inline: HashMap<String, InlineCandidate>,
+    /// The value of each enum variant, by enum name and then variant name.
+    enums: HashMap<String, HashMap<String, i64>>,
+    names: FreshNames,
+}
+
+impl Optimizer {
+    fn new(program: &Program) -> Self {
+        let mut used = HashSet::new();
+        collect_names(&program.statements, &mut used);
+        Self {
+            scopes: vec![HashMap::new()],
+            inline: find_inline_candidates(program),
+            enums: program.statements.iter()
+                .filter_map(|s| match s {
+                    Statement::Enum { name, variants, .. } => Some((name.clone(), variants.iter().map(|v| (v.name.clone(), v.value)).collect())),
+                    _ => None,
+                })
+                .collect(),
+            names: FreshNames { used, next: 0 },
+        }
+    }
+
+    fn is_bound(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains_key(name))
+    }
+
+    fn bind(&mut self, name: &str, value: Option<Literal>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    /// The discriminant of `Enum.Variant`, unless a local has taken the enum's name.
+    fn enum_value(&self, expr: &Expression) -> Option<i64> {
+        let Expression::Get { object, name } = expr else { return None };
+        let Expression::Identifier(enum_name) = &**object else { return None };
+        if self.is_bound(enum_name) {
+            return None;
+        }
+        self.enums.get(enum_name)?.get(name).copied()
+    }
+
+    fn constant_value(&self, name: &str) -> Option<Literal> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).cloned().flatten()
+    }
+
+    fn optimize_block(&mut self, statements: &mut Vec<Statement>) {
+        for statement in std::mem::take(statements) {
+            for mut statement in self.inline_calls(statement) {
+                self.optimize_statement(&mut statement);
+                simplify_constant_control_flow(statement, statements);
+            }
+        }
+    }
+
+    /// Inlines the calls to small functions in a statement, returning the statements that replace it:
+    /// the inlined bodies, then the statement itself using their results. Only calls that are sure to
+    /// run exactly once, before the statement does anything else, are inlined: not the right side of
+    /// `&&`/`||`, and not loop conditions, which run again on every iteration.
+    fn inline_calls(&mut self, mut statement: Statement) -> Vec<Statement> {
+        let mut prelude = Vec::new();
+        match &mut statement {
+            // A call to a `void` function on its own is replaced by the function's body.
+            Statement::Expression { expr: Expression::Call { callee, args }, .. } if self.can_inline(callee, args) => {
+                for arg in args.iter_mut() {
+                    self.inline_calls_in(arg, &mut prelude);
+                }
+                let Expression::Identifier(name) = &**callee else { unreachable!() };
+                let name = name.clone();
+                // Only the body matters, not the local holding a result nobody uses.
+                self.inline_call(&name, std::mem::take(args), &mut prelude);
+                return self.inline_calls_in_prelude(prelude);
+            }
+            Statement::Declaration { initializer: Some(expr), .. }
+            | Statement::Destructure { value: expr, .. }
+            | Statement::Assignment { value: expr, .. }
+            | Statement::Expression { expr, .. }
+            | Statement::Return { value: Some(expr), .. }
+            | Statement::If { condition: expr, .. } => self.inline_calls_in(expr, &mut prelude),
+            // Only the first of several printed values is evaluated before everything else.
+            Statement::Print { values, .. } => self.inline_calls_in(&mut values[0], &mut prelude),
+            _ => {}
+        }
+        let mut statements = self.inline_calls_in_prelude(prelude);
+        statements.push(statement);
+        statements
+    }
+
+    /// Inlined bodies can call other small functions in turn. Those are inlined too; this stops because
+    /// functions that can reach themselves are never inlined.
+    fn inline_calls_in_prelude(&mut self, prelude: Vec<Statement>) -> Vec<Statement> {
+        prelude.into_iter().flat_map(|statement| self.inline_calls(statement)).collect()
+    }
+
+    fn inline_calls_in(&mut self, expr: &mut Expression, prelude: &mut Vec<Statement>) {
+        match expr {
+            Expression::BinaryOp { op: BinaryOperator::And | BinaryOperator::Or, left, .. } => self.inline_calls_in(left, prelude),
+            Expression::Call { callee, args } if self.can_inline(callee, args) && !self.returns_void(callee) => {
+                for arg in args.iter_mut() {
+                    self.inline_calls_in(arg, prelude);
+                }
+                let Expression::Identifier(name) = &**callee else { unreachable!() };
+                let name = name.clone();
+                if let Some(value) = self.inline_call(&name, std::mem::take(args), prelude) {
+                    *expr = value;
+                }
+            }
+            _ => {
+                for child in expr.children_mut() {
+                    self.inline_calls_in(child, prelude);
+                }
+            }
+        }
+    }
+
+    /// Whether this call can be replaced by the body of the function it calls. The call site mustn't have
+    /// a local that hides the function, or hides any name the body uses.
+    fn can_inline(&self, callee: &Expression, args: &[Expression]) -> bool {
+        let Expression::Identifier(name) = callee else { return false };
+        let Some(candidate) = self.inline.get(name) else { return false };
+        args.len() == candidate.params.len()
+            && !self.is_bound(name)
+            && !candidate.free_names.iter().any(|free| self.is_bound(free))
+    }
+
+    fn returns_void(&self, callee: &Expression) -> bool {
+        matches!(callee, Expression::Identifier(name) if self.inline.get(name).is_some_and(|c| c.return_type == Type::Void))
+    }
+
+    /// Adds the body of `name` to `prelude`, with each parameter turned into a local holding its argument
+    /// and every local renamed so nothing at the call site can be captured. Returns the expression that
+    /// stands for the call's result, or `None` for a `void` function.
+    fn inline_call(&mut self, name: &str, args: Vec<Expression>, prelude: &mut Vec<Statement>) -> Option<Expression> {
+        let candidate = self.inline[name].clone();
+        let mut renamer = Renamer::new(name, &mut self.names);
+        for (param, arg) in candidate.params.iter().zip(args) {
+            let temp = renamer.declare(&param.name);
+            prelude.push(Statement::Declaration {
+                is_public: false,
+                is_mutable: param.is_mutable,
+                name: temp,
+                data_type: param.data_type.clone(),
+                initializer: Some(arg),
+                span: Span::default(),
+            });
+        }
+        let mut body = candidate.body;
+        renamer.rename_statements(&mut body);
+        if candidate.return_type == Type::Void {
+            prelude.extend(store_returns(&body, None).unwrap_or_default());
+            return None;
+        }
+
+        // The result goes through a local of the return type even for a lone `return`, so it converts
+        // the same way: `return n` from a `float` function still gives a float when `n` is an `int`.
+        let default = default_value(&candidate.return_type)?;
+        let result = self.names.fresh(name, "result");
+        let (initializer, body) = match body.as_slice() {
+            [Statement::Return { value: Some(value), .. }] => (value.clone(), Vec::new()),
+            _ => (default, body),
+        };
+        prelude.push(Statement::Declaration {
+            is_public: false,
+            is_mutable: !body.is_empty(),
+            name: result.clone(),
+            data_type: candidate.return_type.clone(),
+            initializer: Some(initializer),
+            span: Span::default(),
+        });
+        // Candidates are only chosen if this succeeds.
+        prelude.extend(store_returns(&body, Some(&result)).unwrap_or_default());
+        Some(Expression::Identifier(result))
+    }
+
+    fn optimize_scoped_block(&mut self, statements: &mut Vec<Statement>) {
+        self.scopes.push(HashMap::new());
+        self.optimize_block(statements);
+        self.scopes.pop();
+    }
+
+    fn optimize_function(&mut self, statement: &mut Statement) {
+        if let Statement::FunctionDefinition { params, body, .. } = statement {
+            // Constants from the enclosing code aren't visible inside a function.
+            let outer_scopes = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+            for param in params.iter() {
+                self.bind(&param.name, None);
+            }
+            self.optimize_block(body);
+            self.scopes = outer_scopes;
+        }
+    }
+
+    fn optimize_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::Declaration { is_mutable, name, data_type, initializer, .. } => {
+                if let Some(initializer) = initializer {
+                    self.optimize_expression(initializer);
+                }
+                // Only substitute literals that already have the declared type: `val x: float = 7`
+                // must keep dividing as a float wherever `x` is used. Strings stay put because a
+                // C++ string literal isn't a `std::string` and would pick different overloads.
+                let value = match (initializer, data_type) {
+                    (Some(Expression::Literal(literal @ Literal::Int(_))), Type::Int)
+                    | (Some(Expression::Literal(literal @ Literal::Float(..))), Type::Float)
+                    | (Some(Expression::Literal(literal @ Literal::Bool(_))), Type::Bool)
+                    | (Some(Expression::Literal(literal @ Literal::Char(_))), Type::Char) if !*is_mutable => Some(literal.clone()),
+                    _ => None,
+                };
+                self.bind(name, value);
+            }
+            Statement::Destructure { names, value, .. } => {
+                self.optimize_expression(value);
+                for name in names.iter() {
+                    self.bind(name, None);
+                }
+            }
+            Statement::Assignment { targets, value, .. } => {
+                // The targets themselves are never substituted, only the parts that compute where they are.
+                for target in targets {
+                    match target {
+                        Expression::Index { list, index } => {
+                            self.optimize_expression(list);
+                            self.optimize_expression(index);
+                        }
+                        Expression::Get { object, .. } => self.optimize_expression(object),
+                        _ => {}
+                    }
+                }
+                self.optimize_expression(value);
+            }
+            Statement::If { condition, consequence, alternative, else_span, .. } => {
+                self.optimize_expression(condition);
+                // A constant condition leaves only one branch that can run. It stays wrapped in
+                // `if (true)` so the declarations inside it keep their own scope.
+                match condition {
+                    Expression::Literal(Literal::Bool(true)) => {
+                        *alternative = None;
+                        *else_span = None;
+                    }
+                    Expression::Literal(Literal::Bool(false)) => {
+                        *condition = Expression::Literal(Literal::Bool(true));
+                        *consequence = alternative.take().unwrap_or_default();
+                        *else_span = None;
+                    }
+                    _ => {}
+                }
+                self.optimize_scoped_block(consequence);
+                if let Some(alt) = alternative {
+                    self.optimize_scoped_block(alt);
+                }
+            }
+            Statement::While { condition, body, else_block, .. } => {
+                self.optimize_expression(condition);
+                self.optimize_scoped_block(body);
+                if let Some(block) = else_block {
+                    self.optimize_scoped_block(block);
+                }
+            }
+            Statement::For { iterator, start, end, body, else_block, .. } => {
+                self.optimize_expression(start);
+                self.optimize_expression(end);
+                self.scopes.push(HashMap::new());
+                self.bind(iterator, None);
+                self.optimize_block(body);
+                self.scopes.pop();
+                if let Some(block) = else_block {
+                    self.optimize_scoped_block(block);
+                }
+            }
+            Statement::ForEach { iterator, iterable, body, else_block, .. } => {
+                self.optimize_expression(iterable);
+                self.scopes.push(HashMap::new());
+                self.bind(iterator, None);
+                self.optimize_block(body);
+                self.scopes.pop();
+                if let Some(block) = else_block {
+                    self.optimize_scoped_block(block);
+                }
+            }
+            Statement::TryCatch { try_block, catch_var, catch_block } => {
+                self.optimize_scoped_block(try_block);
+                self.scopes.push(HashMap::new());
+                self.bind(catch_var, None);
+                self.optimize_block(catch_block);
+                self.scopes.pop();
+            }
+            Statement::Print { values, .. } => {
+                for value in values.iter_mut() {
+                    self.optimize_expression(value);
+                }
+            }
+            Statement::Expression { expr, .. } | Statement::Return { value: Some(expr), .. } => self.optimize_expression(expr),
+            Statement::FunctionDefinition { .. } => self.optimize_function(statement),
+            Statement::Class { members, .. } => {
+                for member in members.iter_mut() {
+                    if let ClassMember::Method(method) | ClassMember::Constructor(method) = member {
+                        self.optimize_function(method);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn optimize_expression(&mut self, expr: &mut Expression) {
+        match expr {
+            Expression::Identifier(name) => {
+                if let Some(literal) = self.constant_value(name) {
+                    *expr = Expression::Literal(literal);
+                }
+            }
+            Expression::BinaryOp { op: op @ (BinaryOperator::And | BinaryOperator::Or), left, right } => {
+                self.optimize_expression(left);
+                self.optimize_expression(right);
+                if let Some(folded) = fold_logical(op, left, right) {
+                    *expr = folded;
+                }
+            }
+            Expression::BinaryOp { op, left, right } => {
+                self.optimize_expression(left);
+                self.optimize_expression(right);
+                // In arithmetic an enum variant stands for its discriminant, so `Status.Err + 1` folds.
+                if op.is_arithmetic() {
+                    for operand in [&mut **left, &mut **right] {
+                        if let Some(value) = self.enum_value(operand) {
+                            *operand = Expression::Literal(Literal::Int(value));
+                        }
+                    }
+                }
+                if let (Expression::Literal(l), Expression::Literal(r)) = (&**left, &**right) {
+                    if let Some(folded) = fold_binary(op, l, r) {
+                        *expr = Expression::Literal(folded);
+                    }
+                }
+            }
+            Expression::Unary { op, operand } => {
+                self.optimize_expression(operand);
+                if let Expression::Literal(literal) = &**operand {
+                    if let Some(folded) = fold_unary(op, literal) {
+                        *expr = Expression::Literal(folded);
+                    }
+                }
+            }
+            Expression::Call { callee, args } => {
+                // Calls through an identifier name a function, which is never a constant.
+                if !matches!(**callee, Expression::Identifier(_)) {
+                    self.optimize_expression(callee);
+                }
+                for arg in args.iter_mut() {
+                    self.optimize_expression(arg);
+                }
+                if let Some(folded) = fold_call(callee, args) {
+                    *expr = Expression::Literal(folded);
+                } else if let Some(list) = fold_range(callee, args) {
+                    *expr = list;
+                }
+            }
+            Expression::Index { list, index } => {
+                self.optimize_expression(list);
+                self.optimize_expression(index);
+            }
+            Expression::Slice { target, start, end } => {
+                self.optimize_expression(target);
+                for bound in [start, end].into_iter().flatten() {
+                    self.optimize_expression(bound);
+                }
+            }
+            Expression::Get { object, .. } => self.optimize_expression(object),
+            // The target is a `var`, which is never substituted.
+            Expression::Assign { value, .. } => self.optimize_expression(value),
+            Expression::Match { .. } => {
+                for child in expr.children_mut() {
+                    self.optimize_expression(child);
+                }
+            }
+            Expression::New { args, .. } => {
+                for arg in args.iter_mut() {
+                    self.optimize_expression(arg);
+                }
+            }
+            Expression::ListLiteral(elements) | Expression::TupleLiteral(elements) => {
+                for element in elements.iter_mut() {
+                    self.optimize_expression(element);
+                }
+            }
+            Expression::DictLiteral(entries) => {
+                for (key, value) in entries.iter_mut() {
+                    self.optimize_expression(key);
+                    self.optimize_expression(value);
+                }
+            }
+            Expression::Literal(_) | Expression::This => {}
+        }
+    }
+}
+
+/// A function that `-O` inlines at its call sites.
+#[derive(Clone)]
+struct InlineCandidate {
+    params: Vec<Param>,
+    return_type: Type,
+    body: Vec<Statement>,
+    /// Names the body uses without declaring them, like the functions it calls.
+    free_names: HashSet<String>,
+}
+
+/// Finds the top-level functions that are worth inlining and safe to inline: small ones that aren't
+/// overloaded, take and return plain values (`int`, `float`, `bool`, `char`, `string`, or nothing), and
+/// whose bodies are straight-line code and `if`s. Functions that can end up calling themselves are
+/// left out, so inlining always stops.
+fn find_inline_candidates(program: &Program) -> HashMap<String, InlineCandidate> {
+    let mut overloads: HashMap<&str, usize> = HashMap::new();
+    for statement in &program.statements {
+        if let Statement::FunctionDefinition { name, .. } = statement {
+            *overloads.entry(name).or_default() += 1;
+        }
+    }
+
+    let mut candidates = HashMap::new();
+    for statement in &program.statements {
+        let Statement::FunctionDefinition { name, params, return_type, body, .. } = statement else { continue };
+        let plain_values = params.iter().all(|p| !p.is_variadic && is_plain_value(&p.data_type))
+            && (*return_type == Type::Void || is_plain_value(return_type));
+        if overloads[name.as_str()] > 1
+            || !plain_values
+            || !is_straight_line(body)
+            || block_size(body) > INLINE_SIZE_LIMIT
+            || store_returns(body, default_value(return_type).map(|_| "result")).is_none()
+        {
+            continue;
+        }
+        // Renaming into a throwaway namespace finds the names the body doesn't declare.
+        let mut names = FreshNames::default();
+        let mut renamer = Renamer::new(name, &mut names);
+        for param in params {
+            renamer.declare(&param.name);
+        }
+        renamer.rename_statements(&mut body.clone());
+        let free_names = renamer.free;
+        candidates.insert(name.clone(), InlineCandidate { params: params.clone(), return_type: return_type.clone(), body: body.clone(), free_names });
+    }
+
+    let recursive: Vec<String> = candidates.keys().filter(|name| calls_itself(name, &candidates)).cloned().collect();
+    for name in recursive {
+        candidates.remove(&name);
+    }
+    candidates
+}
+
+fn is_plain_value(data_type: &Type) -> bool {
+    matches!(data_type, Type::Int | Type::Float | Type::Bool | Type::Char | Type::String)
+}
+
+/// The value a function's result starts out as before the inlined body stores into it, or `None` for `void`.
+fn default_value(data_type: &Type) -> Option<Expression> {
+    let literal = match data_type {
+        Type::Int => Literal::Int(0),
+        Type::Float => Literal::Float(0.0, None),
+        Type::Bool => Literal::Bool(false),
+        Type::Char => Literal::Char('\0'),
+        Type::String => Literal::String(String::new()),
+        _ => return None,
+    };
+    Some(Expression::Literal(literal))
+}
+
+/// Whether every statement in the block (and its nested `if`s) is one the inliner knows how to move.
+fn is_straight_line(statements: &[Statement]) -> bool {
+    statements.iter().all(|statement| match statement {
+        Statement::Declaration { .. } | Statement::Assignment { .. } | Statement::Print { .. } | Statement::Expression { .. } | Statement::Return { .. } => true,
+        Statement::If { consequence, alternative, .. } => is_straight_line(consequence) && alternative.as_deref().is_none_or(is_straight_line),
+        _ => false,
+    })
+}
+
+/// The expressions a straight-line statement evaluates, and the blocks nested in it.
+fn statement_parts(statement: &Statement) -> (Vec<&Expression>, Vec<&[Statement]>) {
+    match statement {
+        Statement::Declaration { initializer, .. } => (initializer.iter().collect(), vec![]),
+        Statement::Assignment { targets, value, .. } => (targets.iter().chain([value]).collect(), vec![]),
+        Statement::Print { values, .. } => (values.iter().collect(), vec![]),
+        Statement::Expression { expr, .. } | Statement::Return { value: Some(expr), .. } => (vec![expr], vec![]),
+        Statement::If { condition, consequence, alternative, .. } => {
+            let mut blocks = vec![consequence.as_slice()];
+            blocks.extend(alternative.as_deref());
+            (vec![condition], blocks)
+        }
+        _ => (vec![], vec![]),
+    }
+}
+
+/// Counts the statements and expressions in a straight-line block.
+fn block_size(statements: &[Statement]) -> usize {
+    fn expression_size(expr: &Expression) -> usize {
+        1 + expr.children().into_iter().map(expression_size).sum::<usize>()
+    }
+    statements.iter().map(|statement| {
+        let (exprs, blocks) = statement_parts(statement);
+        1 + exprs.into_iter().map(expression_size).sum::<usize>() + blocks.into_iter().map(block_size).sum::<usize>()
+    }).sum()
+}
+
+/// Whether inlining `name` could lead back to another call to `name`.
+fn calls_itself(name: &str, candidates: &HashMap<String, InlineCandidate>) -> bool {
+    let mut pending = vec![name];
+    let mut seen = HashSet::new();
+    while let Some(current) = pending.pop() {
+        for callee in &candidates[current].free_names {
+            if callee == name {
+                return true;
+            }
+            if candidates.contains_key(callee) && seen.insert(callee.as_str()) {
+                pending.push(callee);
+            }
+        }
+    }
+    false
+}
+
+/// Rewrites an inlined body so it stores what it returns in the local `result` (when there is one) instead
+/// of returning it. The body is straight-line code and `if`s, so returning early from an `if` is the same
+/// as moving the rest of the block into both of its branches, minus the part after the `return`. Returns
+/// `None` if the end of a function that returns a value can be reached without a `return`.
+fn store_returns(statements: &[Statement], result: Option<&str>) -> Option<Vec<Statement>> {
+    let mut stored = Vec::new();
+    for (i, statement) in statements.iter().enumerate() {
+        match statement {
+            Statement::Return { value, .. } => {
+                if let (Some(result), Some(value)) = (result, value) {
+                    stored.push(Statement::Assignment {
+                        targets: vec![Expression::Identifier(result.to_string())],
+                        op: None,
+                        value: value.clone(),
+                        span: Span::default(),
+                    });
+                }
+                return Some(stored);
+            }
+            Statement::If { condition, consequence, alternative, span, .. } if returns_within(std::slice::from_ref(statement)) => {
+                let rest = &statements[i + 1..];
+                let consequence = [consequence.as_slice(), rest].concat();
+                let alternative = [alternative.as_deref().unwrap_or_default(), rest].concat();
+                let consequence = store_returns(&consequence, result)?;
+                let alternative = store_returns(&alternative, result)?;
+                // An early `return` from a `void` function leaves nothing to do in its branch.
+                let (condition, consequence, alternative) = match (consequence.is_empty(), alternative.is_empty()) {
+                    (true, false) => (Expression::Unary { op: UnaryOperator::Not, operand: Box::new(condition.clone()) }, alternative, None),
+                    (_, true) => (condition.clone(), consequence, None),
+                    (false, false) => (condition.clone(), consequence, Some(alternative)),
+                };
+                stored.push(Statement::If { condition, consequence, alternative, span: *span, else_span: None });
+                return Some(stored);
+            }
+            statement => stored.push(statement.clone()),
+        }
+    }
+    result.is_none().then_some(stored)
+}
+
+fn returns_within(statements: &[Statement]) -> bool {
+    statements.iter().any(|statement| match statement {
+        Statement::Return { .. } => true,
+        Statement::If { consequence, alternative, .. } => returns_within(consequence) || alternative.as_deref().is_some_and(returns_within),
+        _ => false,
+    })
+}
+
+/// Hands out names for the locals that inlining introduces, checked against every name in the program.
+#[derive(Default)]
+struct FreshNames {
+    used: HashSet<String>,
+    next: usize,
+}
+
+impl FreshNames {
+    /// A new name like `inc_x_1` for the local `x` of the inlined function `inc`.
+    fn fresh(&mut self, function: &str, name: &str) -> String {
+        loop {
+            self.next += 1;
+            let candidate = format!("{}_{}_{}", function, name, self.next);
+            if self.used.insert(candidate.clone()) {
+                return candidate;
+            }
+        }
+    }
+}
+
+/// Gives the parameters and locals of an inlined body fresh names, and records the names it uses
+/// without declaring them.
+struct Renamer<'a> {
+    function: &'a str,
+    names: &'a mut FreshNames,
+    scopes: Vec<HashMap<String, String>>,
+    free: HashSet<String>,
+}
+
+impl<'a> Renamer<'a> {
+    fn new(function: &'a str, names: &'a mut FreshNames) -> Self {
+        Self { function, names, scopes: vec![HashMap::new()], free: HashSet::new() }
+    }
+
+    /// Declares `name` in the innermost scope and returns its new name.
+    fn declare(&mut self, name: &str) -> String {
+        let renamed = self.names.fresh(self.function, name);
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), renamed.clone());
+        }
+        renamed
+    }
+
+    /// Renames a block that shares the innermost scope, like a function's outermost block shares its parameters'.
+    fn rename_statements(&mut self, statements: &mut [Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::Declaration { name, initializer, .. } => {
+                    if let Some(initializer) = initializer {
+                        self.rename_expression(initializer);
+                    }
+                    if name != DISCARD {
+                        *name = self.declare(name);
+                    }
+                }
+                Statement::Assignment { targets, value, .. } => {
+                    for target in targets.iter_mut().filter(|target| !target.is_discard()) {
+                        self.rename_expression(target);
+                    }
+                    self.rename_expression(value);
+                }
+                Statement::Print { values, .. } => {
+                    for value in values.iter_mut() {
+                        self.rename_expression(value);
+                    }
+                }
+                Statement::Expression { expr, .. } | Statement::Return { value: Some(expr), .. } => self.rename_expression(expr),
+                Statement::If { condition, consequence, alternative, .. } => {
+                    self.rename_expression(condition);
+                    for block in std::iter::once(consequence).chain(alternative.as_mut()) {
+                        self.scopes.push(HashMap::new());
+                        self.rename_statements(block);
+                        self.scopes.pop();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn rename_expression(&mut self, expr: &mut Expression) {
+        if let Expression::Identifier(name) = expr {
+            match self.scopes.iter().rev().find_map(|scope| scope.get(name.as_str())) {
+                Some(renamed) => *name = renamed.clone(),
+                None => {
+                    self.free.insert(name.clone());
+                }
+            }
+        }
+        for child in expr.children_mut() {
+            self.rename_expression(child);
+        }
+    }
+}
+
+/// Collects every name declared or used anywhere in the program.
+fn collect_names(statements: &[Statement], names: &mut HashSet<String>) {
+    fn collect_expression(expr: &Expression, names: &mut HashSet<String>) {
+        if let Expression::Identifier(name) = expr {
+            names.insert(name.clone());
+        }
+        for child in expr.children() {
+            collect_expression(child, names);
+        }
+    }
+    for statement in statements {
+        match statement {
+            Statement::Declaration { name, initializer, .. } => {
+                names.insert(name.clone());
+                if let Some(initializer) = initializer {
+                    collect_expression(initializer, names);
+                }
+            }
+            Statement::Destructure { names: declared, value, .. } => {
+                names.extend(declared.iter().cloned());
+                collect_expression(value, names);
+            }
+            Statement::Assignment { targets, value, .. } => {
+                for target in targets {
+                    collect_expression(target, names);
+                }
+                collect_expression(value, names);
+            }
+            Statement::If { condition, consequence, alternative, .. } => {
+                collect_expression(condition, names);
+                collect_names(consequence, names);
+                collect_names(alternative.as_deref().unwrap_or_default(), names);
+            }
+            Statement::While { condition, body, else_block, .. } => {
+                collect_expression(condition, names);
+                collect_names(body, names);
+                collect_names(else_block.as_deref().unwrap_or_default(), names);
+            }
+            Statement::For { iterator, start, end, body, else_block, .. } => {
+                names.insert(iterator.clone());
+                collect_expression(start, names);
+                collect_expression(end, names);
+                collect_names(body, names);
+                collect_names(else_block.as_deref().unwrap_or_default(), names);
+            }
+            Statement::ForEach { iterator, iterable, body, else_block, .. } => {
+                names.insert(iterator.clone());
+                collect_expression(iterable, names);
+                collect_names(body, names);
+                collect_names(else_block.as_deref().unwrap_or_default(), names);
+            }
+            Statement::Print { values, .. } => values.iter().for_each(|value| collect_expression(value, names)),
+            Statement::Expression { expr, .. } | Statement::Return { value: Some(expr), .. } => collect_expression(expr, names),
+            Statement::FunctionDefinition { name, params, body, .. } => {
+                names.insert(name.clone());
+                names.extend(params.iter().map(|p| p.name.clone()));
+                collect_names(body, names);
+            }
+            Statement::Enum { name, .. } => {
+                names.insert(name.clone());
+            }
+            Statement::Class { name, members, .. } => {
+                names.insert(name.clone());
+                for member in members {
+                    let (ClassMember::Variable(member) | ClassMember::Method(member) | ClassMember::Constructor(member)) = member;
+                    collect_names(std::slice::from_ref(member), names);
+                }
+            }
+            Statement::TryCatch { try_block, catch_var, catch_block } => {
+                names.insert(catch_var.clone());
+                collect_names(try_block, names);
+                collect_names(catch_block, names);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Pushes `statement` onto `block`, replacing control flow whose condition is a known constant with
+/// the code that actually runs: an `if true:` becomes its body, and a `while false:` or a `for` over an
+/// empty range like `5..5` disappears (or becomes its `else` block, which always runs). A condition is only a literal once every part of it
+/// has been folded, so nothing with side effects is lost. A body that declares names keeps a scope
+/// of its own (as `if true:`) so they can't collide with the names around it.
+fn simplify_constant_control_flow(statement: Statement, block: &mut Vec<Statement>) {
+    match statement {
+        // Pruning has already turned a constant `if` into `if true:` without an `else`.
+        Statement::If { condition: Expression::Literal(Literal::Bool(true)), consequence, alternative: None, .. }
+            if !declares_names(&consequence) => block.extend(consequence),
+        Statement::While { condition: Expression::Literal(Literal::Bool(false)), else_block, span, .. } => push_else_block(else_block, span, block),
+        Statement::For { start: Expression::Literal(Literal::Int(start)), end: Expression::Literal(Literal::Int(end)), else_block, span, .. }
+            if start >= end => push_else_block(else_block, span, block),
+        statement => block.push(statement),
+    }
+}
+
+/// Pushes what's left of a loop that never runs: its `else` block, if it has one.
+fn push_else_block(else_block: Option<Vec<Statement>>, span: Span, block: &mut Vec<Statement>) {
+    match else_block {
+        Some(else_block) if declares_names(&else_block) => block.push(Statement::If {
+            condition: Expression::Literal(Literal::Bool(true)),
+            consequence: else_block,
+            alternative: None,
+            span,
+            else_span: None,
+        }),
+        Some(else_block) => block.extend(else_block),
+        None => {}
+    }
+}
+
+/// Returns true if the block itself (not a nested block) declares a variable.
+fn declares_names(statements: &[Statement]) -> bool {
+    statements.iter().any(|s| matches!(s, Statement::Declaration { .. } | Statement::Destructure { .. }))
+}
+
+/// The value of an expression made only of literals, the constants `constant` knows, and operators on
+/// them, worked out the same way the optimizer folds it. `None` if it can't be known while compiling.
+/// An enum variant in arithmetic is looked up as `constant("Enum.Variant")`.
+pub fn evaluate_constant(expr: &Expression, constant: &dyn Fn(&str) -> Option<Literal>) -> Option<Literal> {
+    match expr {
+        Expression::Literal(literal) => Some(literal.clone()),
+        Expression::Identifier(name) => constant(name),
+        Expression::BinaryOp { op, left, right } if op.is_arithmetic() => {
+            let operand = |expr: &Expression| match expr {
+                Expression::Get { object, name } => match &**object {
+                    Expression::Identifier(enum_name) => constant(&format!("{}.{}", enum_name, name)),
+                    _ => None,
+                },
+                _ => evaluate_constant(expr, constant),
+            };
+            fold_binary(op, &operand(left)?, &operand(right)?)
+        }
+        Expression::BinaryOp { op, left, right } => fold_binary(op, &evaluate_constant(left, constant)?, &evaluate_constant(right, constant)?),
+        Expression::Unary { op, operand } => fold_unary(op, &evaluate_constant(operand, constant)?),
+        Expression::Call { callee, args } => {
+            let args = args.iter().map(|arg| evaluate_constant_argument(arg, constant)).collect::<Option<Vec<_>>>()?;
+            fold_call(callee, &args)
+        }
+        _ => None,
+    }
+}
+
+/// An argument with every part that's a constant replaced by its value: a list literal's elements, or the
+/// whole argument otherwise.
+fn evaluate_constant_argument(arg: &Expression, constant: &dyn Fn(&str) -> Option<Literal>) -> Option<Expression> {
+    match arg {
+        Expression::ListLiteral(elements) => elements.iter()
+            .map(|element| evaluate_constant(element, constant).map(Expression::Literal))
+            .collect::<Option<Vec<_>>>()
+            .map(Expression::ListLiteral),
+        _ => evaluate_constant(arg, constant).map(Expression::Literal),
+    }
+}
+
+/// Folds `&&` or `||` with a literal on either side. The right side only runs when the left doesn't decide
+/// the result, so `false && x` is `false` whatever `x` would do. `x && false` is `false` too, but dropping
+/// `x` there skips something that used to run, so that's only done when `x` is pure.
+fn fold_logical(op: &BinaryOperator, left: &Expression, right: &Expression) -> Option<Expression> {
+    // The value of either side that decides the result on its own: `false` for `&&`, `true` for `||`.
+    let decisive = *op == BinaryOperator::Or;
+    match (left, right) {
+        (Expression::Literal(Literal::Bool(b)), _) if *b == decisive => Some(left.clone()),
+        (Expression::Literal(Literal::Bool(_)), _) => Some(right.clone()),
+        (_, Expression::Literal(Literal::Bool(b))) if *b == decisive => is_pure(left).then(|| right.clone()),
+        (_, Expression::Literal(Literal::Bool(_))) => Some(left.clone()),
+        _ => None,
+    }
+}
+
+/// Whether evaluating an expression can't do anything observable: it makes no calls or assignments, and
+/// nothing in it can throw (like an out-of-range index) or crash (like dividing by zero).
+fn is_pure(expr: &Expression) -> bool {
+    match expr {
+        Expression::Literal(_) | Expression::Identifier(_) => true,
+        Expression::Unary { operand, .. } => is_pure(operand),
+        Expression::BinaryOp { op: BinaryOperator::Divide | BinaryOperator::Modulo | BinaryOperator::In, .. } => false,
+        Expression::BinaryOp { left, right, .. } => is_pure(left) && is_pure(right),
+        _ => false,
+    }
+}
+
+/// Evaluates a prefix operator on a literal, keeping a float's spelling. Like `fold_binary`, a result
+/// that doesn't fit in an `int` is left to the runtime.
+fn fold_unary(op: &UnaryOperator, operand: &Literal) -> Option<Literal> {
+    match (op, operand) {
+        (UnaryOperator::Not, Literal::Bool(b)) => Some(Literal::Bool(!b)),
+        (UnaryOperator::Negate, Literal::Int(n)) => int(n.checked_neg()),
+        (UnaryOperator::Negate, Literal::Float(x, spelling)) => {
+            let spelling = spelling.as_ref().map(|text| text.strip_prefix('-').map_or_else(|| format!("-{}", text), str::to_string));
+            Some(Literal::Float(-x, spelling))
+        }
+        _ => None,
+    }
+}
+
+/// Evaluates a binary operator on two literals, or returns `None` if the result can't be known
+/// at compile time (or would differ from what the generated C++ computes).
+fn fold_binary(op: &BinaryOperator, left: &Literal, right: &Literal) -> Option<Literal> {
+    use BinaryOperator::*;
+    match (left, right) {
+        (Literal::Int(a), Literal::Int(b)) => match op {
+            Add => int(a.checked_add(*b)),
+            Subtract => int(a.checked_sub(*b)),
+            Multiply => int(a.checked_mul(*b)),
+            // Truncating and floored division only agree when neither side is negative.
+            Divide if *a >= 0 && *b > 0 => Some(Literal::Int(a / b)),
+            Divide => None,
+            // The same goes for the remainder.
+            Modulo if *a >= 0 && *b > 0 => Some(Literal::Int(a % b)),
+            Modulo => None,
+            Equal => Some(Literal::Bool(a == b)),
+            NotEqual => Some(Literal::Bool(a != b)),
+            GreaterThan => Some(Literal::Bool(a > b)),
+            LessThan => Some(Literal::Bool(a < b)),
+            GreaterThanEqual => Some(Literal::Bool(a >= b)),
+            LessThanEqual => Some(Literal::Bool(a <= b)),
+            And | Or | In => None,
+        },
+        (Literal::Float(a, _), Literal::Float(b, _)) => match op {
+            Add => finite(a + b),
+            Subtract => finite(a - b),
+            Multiply => finite(a * b),
+            Divide => finite(a / b),
+            Modulo => None,
+            Equal => Some(Literal::Bool(a == b)),
+            NotEqual => Some(Literal::Bool(a != b)),
+            GreaterThan => Some(Literal::Bool(a > b)),
+            LessThan => Some(Literal::Bool(a < b)),
+            GreaterThanEqual => Some(Literal::Bool(a >= b)),
+            LessThanEqual => Some(Literal::Bool(a <= b)),
+            And | Or | In => None,
+        },
+        (Literal::Bool(a), Literal::Bool(b)) => match op {
+            And => Some(Literal::Bool(*a && *b)),
+            Or => Some(Literal::Bool(*a || *b)),
+            Equal => Some(Literal::Bool(a == b)),
+            NotEqual => Some(Literal::Bool(a != b)),
+            _ => None,
+        },
+        (Literal::Char(a), Literal::Char(b)) => match op {
+            Equal => Some(Literal::Bool(a == b)),
+            NotEqual => Some(Literal::Bool(a != b)),
+            GreaterThan => Some(Literal::Bool(a > b)),
+            LessThan => Some(Literal::Bool(a < b)),
+            GreaterThanEqual => Some(Literal::Bool(a >= b)),
+            LessThanEqual => Some(Literal::Bool(a <= b)),
+            _ => None,
+        },
+        (Literal::String(a), Literal::String(b)) => match op {
+            Add => Some(Literal::String(format!("{}{}", a, b))),
+            Equal => Some(Literal::Bool(a == b)),
+            NotEqual => Some(Literal::Bool(a != b)),
+            In => Some(Literal::Bool(b.contains(a.as_str()))),
+            _ => None,
+        },
+        (Literal::Char(a), Literal::String(b)) if *op == In => Some(Literal::Bool(b.contains(*a))),
+        _ => None,
+    }
+}
+
+/// Evaluates a call to a builtin whose result follows from its literal arguments: `to_string` of a
+/// literal, and `len` of a list literal whose elements are all literals (so nothing is skipped by not
+/// building it).
+fn fold_call(callee: &Expression, args: &[Expression]) -> Option<Literal> {
+    let Expression::Identifier(name) = callee else { return None };
+    match (name.as_str(), args) {
+        ("to_string", [Expression::Literal(literal)]) => fold_to_string(literal),
+        ("len", [Expression::ListLiteral(elements)]) if elements.iter().all(|element| matches!(element, Expression::Literal(_))) => {
+            i64::try_from(elements.len()).ok().map(Literal::Int)
+        }
+        _ => None,
+    }
+}
+
+/// The longest `range` with literal bounds that's written out as a list literal. Longer ones are left to
+/// build at runtime, so the generated code doesn't balloon.
+const MAX_FOLDED_RANGE: usize = 16;
+
+/// Writes out `range` with literal bounds as the list literal it gives, like `[0, 1, 2]` for `range(0, 3)`.
+/// An empty range is left alone, since `[]` on its own has no element type to generate.
+fn fold_range(callee: &Expression, args: &[Expression]) -> Option<Expression> {
+    if !matches!(callee, Expression::Identifier(name) if name == "range") {
+        return None;
+    }
+    let bounds = args.iter().map(|arg| match arg {
+        Expression::Literal(Literal::Int(n)) => Some(*n),
+        _ => None,
+    }).collect::<Option<Vec<_>>>()?;
+    let (start, end, step) = match bounds[..] {
+        [start, end] => (start, end, 1),
+        [start, end, step] if step != 0 => (start, end, step),
+        _ => return None,
+    };
+    let values: Vec<i64> = std::iter::successors(Some(start), |n| n.checked_add(step))
+        .take_while(|n| if step > 0 { *n < end } else { *n > end })
+        .take(MAX_FOLDED_RANGE + 1)
+        .collect();
+    if values.is_empty() || values.len() > MAX_FOLDED_RANGE {
+        return None;
+    }
+    Some(Expression::ListLiteral(values.into_iter().map(|n| Expression::Literal(Literal::Int(n))).collect()))
+}
+
+/// Evaluates `to_string` on a literal. Floats are left to the runtime, which formats them the way `print` does.
+fn fold_to_string(literal: &Literal) -> Option<Literal> {
+    let text = match literal {
+        Literal::Int(n) => n.to_string(),
+        Literal::Bool(b) => b.to_string(),
+        Literal::Char(c) => c.to_string(),
+        Literal::String(s) => s.clone(),
+        Literal::Float(..) | Literal::None => return None,
+    };
+    Some(Literal::String(text))
+}
+
+/// A result that doesn't fit in a C++ `int` overflows at runtime instead of giving this value, so it isn't folded.
+/// Semantic analysis reports such overflows in constants, so this only catches ones exposed by inlining.
+fn int(value: Option<i64>) -> Option<Literal> {
+    value.filter(|n| i32::try_from(*n).is_ok()).map(Literal::Int)
+}
+
+/// Infinities and NaN have no literal spelling in C++, so those results are left to the runtime.
+fn finite(value: f64) -> Option<Literal> {
+    value.is_finite().then_some(Literal::Float(value, None))
+}
@@ -1,5 +1,9 @@
 use crate::lexer::{Lexer, Token, TokenType}; // Imported Lexer
-use crate::ast::{Program, Statement, Expression, Type, Literal, BinaryOperator, ClassMember};
+use crate::ast::{Program, Statement, Expression, Type, Literal, BinaryOperator, UnaryOperator, ClassMember, EnumVariant, Span, Param, Stream};
+use crate::dialect::{unavailable, Features};
+use crate::formatter::spelling;
+use crate::optimizer::evaluate_constant;
+use std::collections::HashSet;
 
 #[derive(Debug)]
 pub struct ParserError {
@@ -14,18 +18,136 @@ impl std::fmt::Display for ParserError {
     }
 }
 
+/// How deeply expressions and blocks may nest before parsing gives up, unless changed with `set_max_depth`.
+/// An unoptimized build uses about 5 KiB of stack per level, so 128 levels (about 640 KiB) fit inside a 1 MiB thread stack.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// The built-in flags that `@if` conditions are evaluated against.
+#[derive(Debug, Clone)]
+pub struct BuildFlags {
+    /// `TARGET`: the target named with `--target`, `"cpp"` by default.
+    pub target: String,
+    /// `OPTIMIZE`: the optimization level, 1 with `-O` and 0 without.
+    pub optimize: i64,
+}
+
+impl Default for BuildFlags {
+    fn default() -> Self {
+        Self { target: "cpp".to_string(), optimize: 0 }
+    }
+}
+
+impl BuildFlags {
+    /// The value of the flag called `name`, if there is one.
+    fn get(&self, name: &str) -> Option<Literal> {
+        match name {
+            "TARGET" => Some(Literal::String(self.target.clone())),
+            "OPTIMIZE" => Some(Literal::Int(self.optimize)),
+            _ => None,
+        }
+    }
+}
+
 pub struct Parser<'a> {
     tokens: &'a [Token],
     pos: usize,
+    /// How many expressions/blocks the parser is currently inside of.
+    depth: usize,
+    /// The deepest nesting allowed. The parser is recursive, so this keeps hostile input from overflowing the stack.
+    max_depth: usize,
+    /// Names of the enums defined in the file, so a type spelled with one of them is an enum, not a class.
+    enums: HashSet<String>,
+    /// See `Span::file`.
+    file: usize,
+    /// What `@if` conditions are evaluated against.
+    flags: BuildFlags,
+    /// The syntax being accepted. See `Dialect`.
+    features: Features,
+    /// Whether a block header missing its `:` is recorded in `recovered` and parsed as if the `:` were there,
+    /// rather than ending the parse. See `set_recovery`.
+    recover: bool,
+    /// The errors the parser recovered from.
+    recovered: Vec<ParserError>,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a [Token]) -> Self {
-        Self { tokens, pos: 0 }
+        // Collected up front so an enum can be used as a type before its definition.
+        let enums = tokens.windows(2).filter_map(|pair| match (&pair[0].token_type, &pair[1].token_type) {
+            (TokenType::Enum, TokenType::Ident(name)) => Some(name.clone()),
+            _ => None,
+        }).collect();
+        Self { tokens, pos: 0, depth: 0, max_depth: DEFAULT_MAX_DEPTH, enums, file: 0, flags: BuildFlags::default(), features: Features::default(),
+            recover: false, recovered: Vec::new() }
+    }
+
+    /// Sets which file the tokens came from when several are compiled together, for every span the parser records.
+    pub fn set_file(&mut self, file: usize) {
+        self.file = file;
+    }
+
+    /// Sets how deeply expressions and blocks may nest.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Sets the build flags that `@if` conditions are evaluated against.
+    pub fn set_flags(&mut self, flags: BuildFlags) {
+        self.flags = flags;
+    }
+
+    /// Sets which newer syntax is accepted.
+    pub fn set_features(&mut self, features: Features) {
+        self.features = features;
+    }
+
+    /// Sets whether the parser carries on past a block header that's missing its `:`, so that every such
+    /// typo is reported at once. The errors are collected by `take_recovered`.
+    pub fn set_recovery(&mut self, recover: bool) {
+        self.recover = recover;
+    }
+
+    /// The errors recovered from so far, in the order they were found. A parse that recovered from any
+    /// still failed, even though it returned a program.
+    pub fn take_recovered(&mut self) -> Vec<ParserError> {
+        std::mem::take(&mut self.recovered)
+    }
+
+    /// Consumes the current token if it's `token_type`, which starts syntax that's only allowed when `enabled`
+    /// (a feature of the dialect); `what` names it in the error otherwise.
+    fn at_feature(&mut self, token_type: TokenType, enabled: bool, what: &str) -> Result<bool, ParserError> {
+        if self.current_token().token_type != token_type {
+            return Ok(false);
+        }
+        if !enabled {
+            return Err(self.error(unavailable(what)));
+        }
+        self.advance();
+        Ok(true)
+    }
+
+    /// Runs `parse` one nesting level deeper, failing cleanly if that's past the limit.
+    fn nested<T>(&mut self, what: &str, parse: impl FnOnce(&mut Self) -> Result<T, ParserError>) -> Result<T, ParserError> {
+        if self.depth >= self.max_depth {
+            return Err(self.error(format!("{} nesting too deep", what)));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
     }
 
     fn current_token(&self) -> Token {
-        self.tokens.get(self.pos).cloned().unwrap_or_else(|| Token::new(TokenType::EOF, 0, 0))
+        self.tokens.get(self.pos).cloned().unwrap_or_else(|| Token::new(TokenType::Eof, 0, 0))
+    }
+
+    fn peek_token_type(&self, offset: usize) -> TokenType {
+        self.tokens.get(self.pos + offset).map(|t| t.token_type.clone()).unwrap_or(TokenType::Eof)
+    }
+
+    fn current_span(&self) -> Span {
+        let token = self.current_token();
+        Span { line: token.line, column: token.column, file: self.file }
     }
 
     fn advance(&mut self) {
@@ -43,6 +165,11 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Skips one statement boundary: a newline, or a `;` separating statements on the same line.
+    fn consume_statement_separator(&mut self) -> bool {
+        self.consume_if(TokenType::Newline) || self.consume_if(TokenType::Semicolon)
+    }
+
     fn error(&self, message: String) -> ParserError {
         let token = self.current_token();
         ParserError { message, line: token.line, column: token.column }
@@ -57,7 +184,32 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Expects the `:` ending the header of a block, such as an `if` condition. When the line ends there
+    /// and the block follows, the only thing wrong is the `:`, so that's what's reported, at the spot it
+    /// belongs; with recovery on, the block is then parsed as if the `:` were present.
+    fn expect_header_colon(&mut self, header: &str, error_msg: &str) -> Result<(), ParserError> {
+        if self.current_token().token_type == TokenType::Newline && self.peek_token_type(1) == TokenType::Indent {
+            let error = self.error(format!("Missing ':' after the {} header", header));
+            if !self.recover {
+                return Err(error);
+            }
+            self.recovered.push(error);
+            return Ok(());
+        }
+        self.expect(TokenType::Colon, error_msg)
+    }
+
     fn parse_type(&mut self) -> Result<Type, ParserError> {
+        let data_type = self.parse_base_type()?;
+        if self.consume_if(TokenType::Question) {
+            Ok(Type::Optional(Box::new(data_type)))
+        } else {
+            Ok(data_type)
+        }
+    }
+
+    /// A type without the `?` that makes it optional.
+    fn parse_base_type(&mut self) -> Result<Type, ParserError> {
         match self.current_token().token_type {
             TokenType::Type(ty_str) => {
                 match ty_str.as_str() {
@@ -65,7 +217,9 @@ impl<'a> Parser<'a> {
                     "float" => { self.advance(); Ok(Type::Float) },
                     "string" => { self.advance(); Ok(Type::String) },
                     "bool" => { self.advance(); Ok(Type::Bool) },
+                    "char" => { self.advance(); Ok(Type::Char) },
                     "void" => { self.advance(); Ok(Type::Void) },
+                    "any" => { self.advance(); Ok(Type::Any) },
                     "list" => {
                         self.advance();
                         self.expect(TokenType::LBracket, "Expected '[' after 'list'")?;
@@ -88,109 +242,53 @@ impl<'a> Parser<'a> {
             TokenType::Ident(name) => {
                 let name = name.clone();
                 self.advance();
-                Ok(Type::Class(name))
+                if self.enums.contains(&name) {
+                    Ok(Type::Enum(name))
+                } else {
+                    Ok(Type::Class(name))
+                }
+            }
+            TokenType::LParen => {
+                if !self.features.tuples {
+                    return Err(self.error(unavailable("A tuple type")));
+                }
+                self.advance();
+                let mut elements = vec![self.parse_type()?];
+                while self.consume_if(TokenType::Comma) {
+                    elements.push(self.parse_type()?);
+                }
+                if elements.len() < 2 {
+                    return Err(self.error("A tuple type needs at least two element types, as in '(int, string)'".to_string()));
+                }
+                self.expect(TokenType::RParen, "Expected ')' after tuple element types")?;
+                Ok(Type::Tuple(elements))
             }
             _ => Err(self.error(format!("Expected type identifier, got {:?}", self.current_token().token_type)))
         }
     }
 
+    /// Parses a literal, name, or bracketed expression and what follows it. Its stack frame is part of every
+    /// level of nesting, so anything bulky is parsed in a function of its own, keeping deeply nested input
+    /// from overflowing the stack before it reaches `max_depth`.
     fn parse_expression_primary(&mut self) -> Result<Expression, ParserError> {
         let token = self.current_token();
-        let mut expr = match &token.token_type {
+        let expr = match &token.token_type {
             TokenType::FString(s) => {
                 self.advance();
-                let mut parts = Vec::new();
-                let mut last_pos = 0;
-                let chars: Vec<char> = s.chars().collect();
-                let mut i = 0;
-
-                while i < chars.len() {
-                    if chars[i] == '{' {
-                        // Add string literal before '{'
-                        if i > last_pos {
-                            let literal = s[last_pos..i].to_string();
-                            parts.push(Expression::Literal(Literal::String(literal)));
-                        }
-
-                        // Find matching '}'
-                        let start_expr = i + 1;
-                        let mut brace_count = 1;
-                        i += 1;
-                        while i < chars.len() && brace_count > 0 {
-                            if chars[i] == '{' { brace_count += 1; }
-                            else if chars[i] == '}' { brace_count -= 1; }
-                            i += 1;
-                        }
-
-                        if brace_count == 0 {
-                            let expr_str = s[start_expr..i-1].to_string();
-                            // Parse expression inside {}
-                            let mut lexer = Lexer::new(expr_str);
-                            let tokens = lexer.tokenize().map_err(|e| ParserError { message: e.message, line: token.line, column: token.column })?;
-                            let mut parser = Parser::new(&tokens);
-                            let expr = parser.parse_expression()?;
-
-                            // Wrap in to_string()
-                            let to_string_call = Expression::Call {
-                                callee: Box::new(Expression::Identifier("to_string".to_string())),
-                                args: vec![expr]
-                            };
-                            parts.push(to_string_call);
-                            last_pos = i;
-                        } else {
-                            return Err(self.error("Unclosed '{' in f-string".to_string()));
-                        }
-                    } else {
-                        i += 1;
-                    }
-                }
-
-                // Add remaining string literal
-                if last_pos < chars.len() {
-                    let literal = s[last_pos..].to_string();
-                    parts.push(Expression::Literal(Literal::String(literal)));
-                }
-
-                // Combine parts with '+'
-                if parts.is_empty() {
-                    Ok(Expression::Literal(Literal::String("".to_string())))
-                } else {
-                    let mut final_expr = parts[0].clone();
-                    for j in 1..parts.len() {
-                        final_expr = Expression::BinaryOp {
-                            op: BinaryOperator::Add,
-                            left: Box::new(final_expr),
-                            right: Box::new(parts[j].clone())
-                        };
-                    }
-                    Ok(final_expr)
-                }
+                self.parse_fstring(s, &token)
             },
             TokenType::New => {
                 self.advance();
-                if let TokenType::Ident(class_name) = self.current_token().token_type {
-                    let class_name = class_name.clone();
-                    self.advance();
-                    self.expect(TokenType::LParen, "Expected '(' after class name in new expression")?;
-                    let mut args = Vec::new();
-                    if !self.consume_if(TokenType::RParen) {
-                        loop {
-                            args.push(self.parse_expression()?);
-                            if !self.consume_if(TokenType::Comma) { break; }
-                        }
-                        self.expect(TokenType::RParen, "Expected ')' after new expression arguments")?;
-                    }
-                    Ok(Expression::New { class_name, args })
-                } else {
-                    Err(self.error("Expected class name after 'new'".to_string()))
-                }
+                self.parse_new()
             },
             TokenType::This => { self.advance(); Ok(Expression::This) },
             TokenType::Int(n) => { self.advance(); Ok(Expression::Literal(Literal::Int(*n))) },
-            TokenType::Float(n) => { self.advance(); Ok(Expression::Literal(Literal::Float(*n))) },
+            TokenType::Float(n, text) => { self.advance(); Ok(Expression::Literal(Literal::Float(*n, Some(text.clone())))) },
             TokenType::Str(s) => { self.advance(); Ok(Expression::Literal(Literal::String(s.clone()))) },
+            TokenType::Char(c) => { self.advance(); Ok(Expression::Literal(Literal::Char(*c))) },
             TokenType::True => { self.advance(); Ok(Expression::Literal(Literal::Bool(true))) },
             TokenType::False => { self.advance(); Ok(Expression::Literal(Literal::Bool(false))) },
+            TokenType::None => { self.advance(); Ok(Expression::Literal(Literal::None)) },
             TokenType::Ident(name) => {
                 let name = name.clone();
                 self.advance();
@@ -198,62 +296,24 @@ impl<'a> Parser<'a> {
             },
             TokenType::LParen => {
                 self.advance();
-                let expr = self.parse_expression()?;
-                self.expect(TokenType::RParen, "Expected ')' after parenthesized expression")?;
-                Ok(expr)
+                self.parse_parenthesized()
             },
             TokenType::LBracket => self.parse_list_literal(),
-            TokenType::LBrace => {
-                self.advance();
-                let mut entries = Vec::new();
-
-                // Consume optional newline and indent after '{'
-                self.consume_if(TokenType::Newline);
-                self.consume_if(TokenType::Indent);
-
-                if !self.consume_if(TokenType::RBrace) {
-                    loop {
-                        // Consume optional newlines/indent before key
-                        self.consume_if(TokenType::Newline);
-                        self.consume_if(TokenType::Indent);
-
-                        let key = self.parse_expression()?;
-                        self.expect(TokenType::Colon, "Expected ':' after dictionary key")?;
-                        let value = self.parse_expression()?;
-                        entries.push((key, value));
-
-                        if !self.consume_if(TokenType::Comma) {
-                            // Consume optional newline before '}'
-                            self.consume_if(TokenType::Newline);
-                            break;
-                        }
-                        // Consume optional newline after ','
-                        self.consume_if(TokenType::Newline);
-                    }
-                    // Consume optional dedent before '}'
-                    self.consume_if(TokenType::Dedent);
-                    self.expect(TokenType::RBrace, "Expected '}' after dictionary entries")?;
-                }
-                Ok(Expression::DictLiteral(entries))
-            },
+            TokenType::LBrace => self.parse_dict_literal(),
+            TokenType::Match => self.parse_match(),
             _ => Err(self.error(format!("Expected a primary expression, got {:?}", token.token_type))),
         }?;
+        self.parse_postfix(expr)
+    }
 
+    /// Parses the calls, indexing, and member accesses after a primary expression, like `.items[0](x)`.
+    fn parse_postfix(&mut self, mut expr: Expression) -> Result<Expression, ParserError> {
         loop {
             if self.consume_if(TokenType::LParen) {
-                let mut args = Vec::new();
-                if !self.consume_if(TokenType::RParen) {
-                    loop {
-                        args.push(self.parse_expression()?);
-                        if !self.consume_if(TokenType::Comma) { break; }
-                    }
-                    self.expect(TokenType::RParen, "Expected ')' after function arguments")?;
-                }
+                let args = self.parse_arguments("Expected ')' after function arguments")?;
                 expr = Expression::Call { callee: Box::new(expr), args };
             } else if self.consume_if(TokenType::LBracket) {
-                let index = self.parse_expression()?;
-                self.expect(TokenType::RBracket, "Expected ']' after index expression")?;
-                expr = Expression::Index { list: Box::new(expr), index: Box::new(index) };
+                expr = self.parse_index(expr)?;
             } else if self.consume_if(TokenType::Dot) {
                 if let TokenType::Ident(name) = self.current_token().token_type {
                     let name = name.clone();
@@ -270,6 +330,194 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
 
+    /// Parses the rest of `new Class(args)` after `new`.
+    fn parse_new(&mut self) -> Result<Expression, ParserError> {
+        let TokenType::Ident(class_name) = self.current_token().token_type else {
+            return Err(self.error("Expected class name after 'new'".to_string()));
+        };
+        self.advance();
+        self.expect(TokenType::LParen, "Expected '(' after class name in new expression")?;
+        let args = self.parse_arguments("Expected ')' after new expression arguments")?;
+        Ok(Expression::New { class_name, args })
+    }
+
+    /// Parses comma-separated arguments after a `(`, and the `)` that ends them.
+    fn parse_arguments(&mut self, missing_paren: &str) -> Result<Vec<Expression>, ParserError> {
+        let mut args = Vec::new();
+        if !self.consume_if(TokenType::RParen) {
+            loop {
+                args.push(self.parse_expression()?);
+                if !self.consume_if(TokenType::Comma) { break; }
+            }
+            self.expect(TokenType::RParen, missing_paren)?;
+        }
+        Ok(args)
+    }
+
+    /// Parses the values of a `print` or `eprint` after the `(`, through the `)`, along with the string
+    /// of a trailing `sep: "..."`. The separator has to be a plain string literal.
+    fn parse_print_arguments(&mut self, keyword: &str) -> Result<(Vec<Expression>, Option<String>), ParserError> {
+        let mut values = vec![self.parse_expression()?];
+        let mut sep = None;
+        while self.consume_if(TokenType::Comma) {
+            if self.current_token().token_type == TokenType::Ident("sep".to_string()) && self.peek_token_type(1) == TokenType::Colon {
+                self.advance();
+                self.advance();
+                let TokenType::Str(text) = self.current_token().token_type else {
+                    return Err(self.error(format!("{}'s 'sep' has to be a string literal, like sep: \", \"", keyword)));
+                };
+                self.advance();
+                sep = Some(text);
+                break;
+            }
+            values.push(self.parse_expression()?);
+        }
+        self.expect(TokenType::RParen, &format!("Expected ')' after {} argument", keyword))?;
+        Ok((values, sep))
+    }
+
+    /// Parses `match subject: pattern => value, ..., _ => value`, starting at the `match`. The `_` arm
+    /// is required and has to come last, so every subject gets a value.
+    fn parse_match(&mut self) -> Result<Expression, ParserError> {
+        if !self.features.match_expressions {
+            return Err(self.error(unavailable("'match'")));
+        }
+        self.advance();
+        let subject = self.parse_expression()?;
+        self.expect(TokenType::Colon, "Expected ':' after the value being matched")?;
+        let mut arms = Vec::new();
+        loop {
+            let pattern = self.parse_expression()?;
+            self.expect(TokenType::FatArrow, "Expected '=>' after a match pattern")?;
+            let value = self.parse_expression()?;
+            if pattern.is_discard() {
+                if self.current_token().token_type == TokenType::Comma {
+                    return Err(self.error("The '_' arm matches everything, so it has to be the last arm of a match".to_string()));
+                }
+                return Ok(Expression::Match { subject: Box::new(subject), arms, default: Box::new(value) });
+            }
+            arms.push((pattern, value));
+            if !self.consume_if(TokenType::Comma) {
+                return Err(self.error("A match needs a final '_ => value' arm for the values no other arm matches".to_string()));
+            }
+        }
+    }
+
+    /// Parses the rest of `target[index]` or `target[start..end]` after the `[`.
+    /// Either bound of a slice may be left out.
+    fn parse_index(&mut self, target: Expression) -> Result<Expression, ParserError> {
+        let start = if self.current_token().token_type == TokenType::Range { None } else { Some(self.parse_expression()?) };
+        let is_slice = self.consume_if(TokenType::Range);
+        match start {
+            Some(index) if !is_slice => {
+                self.expect(TokenType::RBracket, "Expected ']' after index expression")?;
+                Ok(Expression::Index { list: Box::new(target), index: Box::new(index) })
+            }
+            start => {
+                let end = if self.current_token().token_type == TokenType::RBracket { None } else { Some(Box::new(self.parse_expression()?)) };
+                self.expect(TokenType::RBracket, "Expected ']' after slice")?;
+                Ok(Expression::Slice { target: Box::new(target), start: start.map(Box::new), end })
+            }
+        }
+    }
+
+    /// Desugars an f-string into a chain of `+` over its literal pieces and `to_string(...)` calls.
+    /// A placeholder with a width, like `{count:>5}`, pads its text with `align_right` or `align_left`.
+    fn parse_fstring(&mut self, s: &str, token: &Token) -> Result<Expression, ParserError> {
+        let mut parts = Vec::new();
+        let mut last_pos = 0;
+        let chars: Vec<char> = s.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            if chars[i] == '{' {
+                // Add string literal before '{'
+                if i > last_pos {
+                    let literal = s[last_pos..i].to_string();
+                    parts.push(Expression::Literal(Literal::String(literal)));
+                }
+
+                // Find matching '}'
+                let start_expr = i + 1;
+                let mut brace_count = 1;
+                i += 1;
+                while i < chars.len() && brace_count > 0 {
+                    if chars[i] == '{' { brace_count += 1; }
+                    else if chars[i] == '}' { brace_count -= 1; }
+                    i += 1;
+                }
+
+                if brace_count == 0 {
+                    let (expr_str, spec) = split_format_spec(&s[start_expr..i-1]);
+                    // Parse expression inside {}
+                    let mut lexer = Lexer::new(expr_str.to_string());
+                    lexer.set_features(self.features);
+                    let tokens = lexer.tokenize().map_err(|e| ParserError { message: e.message, line: token.line, column: token.column })?;
+                    let mut parser = Parser::new(&tokens);
+                    parser.set_features(self.features);
+                    let expr = parser.parse_expression()?;
+
+                    // Wrap in to_string()
+                    let to_string_call = Expression::Call {
+                        callee: Box::new(Expression::Identifier("to_string".to_string())),
+                        args: vec![expr]
+                    };
+                    match spec {
+                        Some(spec) => parts.push(apply_format_spec(to_string_call, spec, token)?),
+                        None => parts.push(to_string_call),
+                    }
+                    last_pos = i;
+                } else {
+                    return Err(self.error("Unclosed '{' in f-string".to_string()));
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        // Add remaining string literal
+        if last_pos < chars.len() {
+            let literal = s[last_pos..].to_string();
+            parts.push(Expression::Literal(Literal::String(literal)));
+        }
+
+        // Combine parts with '+'
+        if parts.is_empty() {
+            Ok(Expression::Literal(Literal::String("".to_string())))
+        } else {
+            let mut final_expr = parts[0].clone();
+            for part in parts.iter().skip(1) {
+                final_expr = Expression::BinaryOp {
+                    op: BinaryOperator::Add,
+                    left: Box::new(final_expr),
+                    right: Box::new(part.clone())
+                };
+            }
+            Ok(final_expr)
+        }
+    }
+
+    /// Parses what follows a `(`: a bracketed expression, an assignment used as a value, or a tuple.
+    fn parse_parenthesized(&mut self) -> Result<Expression, ParserError> {
+        let mut expr = self.parse_expression()?;
+        // `(n = value)` assigns and gives back the value, so a condition can bind and test at once.
+        if self.at_feature(TokenType::Assign, self.features.assignment_expressions, "Assigning inside an expression")? {
+            let value = self.parse_expression()?;
+            expr = Expression::Assign { target: Box::new(expr), value: Box::new(value) };
+        } else if self.current_token().token_type == TokenType::Comma {
+            if !self.features.tuples {
+                return Err(self.error(unavailable("A tuple")));
+            }
+            let mut elements = vec![expr];
+            while self.consume_if(TokenType::Comma) {
+                elements.push(self.parse_expression()?);
+            }
+            expr = Expression::TupleLiteral(elements);
+        }
+        self.expect(TokenType::RParen, "Expected ')' after parenthesized expression")?;
+        Ok(expr)
+    }
+
     fn parse_list_literal(&mut self) -> Result<Expression, ParserError> {
         self.expect(TokenType::LBracket, "Expected '[' to start a list literal")?;
         let mut elements = Vec::new();
@@ -283,37 +531,109 @@ impl<'a> Parser<'a> {
         Ok(Expression::ListLiteral(elements))
     }
 
-    fn get_precedence(token_type: &TokenType) -> u8 {
+    fn parse_dict_literal(&mut self) -> Result<Expression, ParserError> {
+        self.advance();
+        let mut entries = Vec::new();
+
+        // Consume optional newline and indent after '{'
+        self.consume_if(TokenType::Newline);
+        self.consume_if(TokenType::Indent);
+
+        if !self.consume_if(TokenType::RBrace) {
+            loop {
+                // Consume optional newlines/indent before key
+                self.consume_if(TokenType::Newline);
+                self.consume_if(TokenType::Indent);
+
+                let key = self.parse_expression()?;
+                self.expect(TokenType::Colon, "Expected ':' after dictionary key")?;
+                let value = self.parse_expression()?;
+                entries.push((key, value));
+
+                if !self.consume_if(TokenType::Comma) {
+                    // Consume optional newline before '}'
+                    self.consume_if(TokenType::Newline);
+                    break;
+                }
+                // Consume optional newline after ','
+                self.consume_if(TokenType::Newline);
+            }
+            // Consume optional dedent before '}'
+            self.consume_if(TokenType::Dedent);
+            self.expect(TokenType::RBrace, "Expected '}' after dictionary entries")?;
+        }
+        Ok(Expression::DictLiteral(entries))
+    }
+
+    /// How tightly a binary operator binds, or 0 if the token isn't one. Every token with a precedence
+    /// has to convert with `token_to_binary_op`, or the expression loop would stop on it mid-parse.
+    pub fn get_precedence(token_type: &TokenType) -> u8 {
         match token_type {
-            TokenType::Dot => 7,
             TokenType::Op(op) => match op.as_str() {
-                "*" | "/" => 5,
+                "*" | "/" | "%" => 5,
                 "+" | "-" => 4,
                 "==" | "!=" | ">" | "<" | ">=" | "<=" => 3,
+                "&&" => 2,
+                "||" => 1,
                 _ => 0,
             },
+            TokenType::In => 3,
             _ => 0,
         }
     }
 
-    fn token_to_binary_op(&self, token_type: &TokenType) -> Result<BinaryOperator, ParserError> {
-        if let TokenType::Op(op_str) = token_type {
-            match op_str.as_str() {
-                "+" => Ok(BinaryOperator::Add), "-" => Ok(BinaryOperator::Subtract),
-                "*" => Ok(BinaryOperator::Multiply), "/" => Ok(BinaryOperator::Divide),
-                "==" => Ok(BinaryOperator::Equal), "!=" => Ok(BinaryOperator::NotEqual),
-                ">" => Ok(BinaryOperator::GreaterThan), "<" => Ok(BinaryOperator::LessThan),
-                ">=" => Ok(BinaryOperator::GreaterThanEqual), "<=" => Ok(BinaryOperator::LessThanEqual),
-                _ => Err(self.error(format!("Unknown binary operator: {}", op_str))),
+    /// The binary operator a token stands for, if it's one.
+    pub fn token_to_binary_op(token_type: &TokenType) -> Option<BinaryOperator> {
+        match token_type {
+            TokenType::In => Some(BinaryOperator::In),
+            TokenType::Op(op_str) => match op_str.as_str() {
+                "+" => Some(BinaryOperator::Add), "-" => Some(BinaryOperator::Subtract),
+                "*" => Some(BinaryOperator::Multiply), "/" => Some(BinaryOperator::Divide), "%" => Some(BinaryOperator::Modulo),
+                "==" => Some(BinaryOperator::Equal), "!=" => Some(BinaryOperator::NotEqual),
+                ">" => Some(BinaryOperator::GreaterThan), "<" => Some(BinaryOperator::LessThan),
+                ">=" => Some(BinaryOperator::GreaterThanEqual), "<=" => Some(BinaryOperator::LessThanEqual),
+                "&&" => Some(BinaryOperator::And), "||" => Some(BinaryOperator::Or),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Parses prefix operators. They bind tighter than any binary operator, so `!a == b` is `(!a) == b`
+    /// and `-a * b` is `(-a) * b`.
+    fn parse_expression_unary(&mut self) -> Result<Expression, ParserError> {
+        if self.current_token().token_type == TokenType::Op("!".to_string()) {
+            return self.parse_prefix(UnaryOperator::Not);
+        }
+        // A `-` directly in front of a number is part of the literal, e.g. `list[-1]`.
+        if self.current_token().token_type == TokenType::Op("-".to_string()) {
+            match self.peek_token_type(1) {
+                TokenType::Int(n) => {
+                    self.advance();
+                    self.advance();
+                    return Ok(Expression::Literal(Literal::Int(-n)));
+                }
+                TokenType::Float(n, text) => {
+                    self.advance();
+                    self.advance();
+                    return Ok(Expression::Literal(Literal::Float(-n, Some(format!("-{}", text)))));
+                }
+                _ => return self.parse_prefix(UnaryOperator::Negate),
             }
-        } else {
-            Err(self.error(format!("Expected operator token, got {:?}", token_type)))
         }
+        self.parse_expression_primary()
+    }
+
+    /// Parses the operand after a prefix operator, which is at the current token.
+    fn parse_prefix(&mut self, op: UnaryOperator) -> Result<Expression, ParserError> {
+        self.advance();
+        let operand = self.nested("expression", |p| p.parse_expression_unary())?;
+        Ok(Expression::Unary { op, operand: Box::new(operand) })
     }
 
     fn parse_expression_binop(&mut self, min_precedence: u8) -> Result<Expression, ParserError> {
-        let mut left = self.parse_expression_primary()?;
-        while self.current_token().token_type != TokenType::EOF {
+        let mut left = self.parse_expression_unary()?;
+        while self.current_token().token_type != TokenType::Eof {
             let precedence = Self::get_precedence(&self.current_token().token_type);
             if precedence == 0 || precedence < min_precedence { break; }
 
@@ -321,32 +641,48 @@ impl<'a> Parser<'a> {
             self.advance();
 
             let right = self.parse_expression_binop(precedence + 1)?;
-            left = Expression::BinaryOp { op: self.token_to_binary_op(&op_token.token_type)?, left: Box::new(left), right: Box::new(right) };
+            let op = Self::token_to_binary_op(&op_token.token_type)
+                .ok_or_else(|| self.error(format!("Unknown binary operator: {:?}", op_token.token_type)))?;
+            left = Expression::BinaryOp { op, left: Box::new(left), right: Box::new(right) };
         }
         Ok(left)
     }
 
     fn parse_expression(&mut self) -> Result<Expression, ParserError> {
-        self.parse_expression_binop(0)
+        self.nested("expression", |p| p.parse_expression_binop(0))
+    }
+
+    /// Skips the blank and comment-only lines between a `:` and the `Indent` of the block after it.
+    fn skip_blank_lines(&mut self) {
+        while self.consume_if(TokenType::Newline) {}
     }
 
     fn parse_block(&mut self) -> Result<Vec<Statement>, ParserError> {
-        self.expect(TokenType::Indent, "Expected indentation for block")?;
+        self.nested("block", |p| p.parse_block_statements())
+    }
+
+    fn parse_block_statements(&mut self) -> Result<Vec<Statement>, ParserError> {
+        // Nothing indented after the `:` means the block was left empty, which is easy to do by accident.
+        self.skip_blank_lines();
+        if !self.consume_if(TokenType::Indent) {
+            return Err(self.error("Expected an indented block after the ':'; a block can't be empty".to_string()));
+        }
         let mut statements = Vec::new();
-        while self.current_token().token_type != TokenType::Dedent && self.current_token().token_type != TokenType::EOF {
-            while self.consume_if(TokenType::Newline) {}
+        while self.current_token().token_type != TokenType::Dedent && self.current_token().token_type != TokenType::Eof {
+            while self.consume_statement_separator() {}
             if self.current_token().token_type == TokenType::Dedent { break; }
-            statements.push(self.parse_statement()?);
+            self.parse_statement_into(&mut statements)?;
         }
         self.expect(TokenType::Dedent, "Expected dedent to end block")?;
         Ok(statements)
     }
 
     fn parse_class_block(&mut self) -> Result<Vec<ClassMember>, ParserError> {
+        self.skip_blank_lines();
         self.expect(TokenType::Indent, "Expected indentation for class body")?;
         let mut members = Vec::new();
-        while self.current_token().token_type != TokenType::Dedent && self.current_token().token_type != TokenType::EOF {
-            while self.consume_if(TokenType::Newline) {}
+        while self.current_token().token_type != TokenType::Dedent && self.current_token().token_type != TokenType::Eof {
+            while self.consume_statement_separator() {}
             if self.current_token().token_type == TokenType::Dedent { break; }
 
             let is_public = self.consume_if(TokenType::Pub);
@@ -373,6 +709,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_declaration(&mut self, is_public: bool) -> Result<Statement, ParserError> {
+        let span = self.current_span();
         let is_mutable = match self.current_token().token_type {
             TokenType::Val => false,
             TokenType::Var => true,
@@ -386,12 +723,49 @@ impl<'a> Parser<'a> {
 
         self.expect(TokenType::Colon, "Expected ':' after identifier in declaration")?;
         let data_type = self.parse_type()?;
+        // A `var` can get its first value later; a `val` never can.
+        if is_mutable && matches!(self.current_token().token_type, TokenType::Newline | TokenType::Semicolon | TokenType::Eof) {
+            return Ok(Statement::Declaration { is_public, is_mutable, name, data_type, initializer: None, span });
+        }
         self.expect(TokenType::Assign, "Expected '=' in declaration")?;
-        let initializer = self.parse_expression()?;
-        Ok(Statement::Declaration { is_public, is_mutable, name, data_type, initializer })
+        let initializer = Some(self.parse_expression()?);
+        Ok(Statement::Declaration { is_public, is_mutable, name, data_type, initializer, span })
+    }
+
+    /// Parses `val (a, b) = value`, which names each element of a tuple. Starts at the `val`/`var`.
+    fn parse_destructure(&mut self) -> Result<Statement, ParserError> {
+        let span = self.current_span();
+        if !self.features.tuples {
+            return Err(self.error(unavailable("Destructuring a tuple")));
+        }
+        let is_mutable = self.current_token().token_type == TokenType::Var;
+        self.advance();
+        self.expect(TokenType::LParen, "Expected '(' to start the names to destructure into")?;
+        let mut names = Vec::new();
+        loop {
+            let TokenType::Ident(name) = self.current_token().token_type else {
+                return Err(self.error("Expected a name or '_' for each element being destructured".to_string()));
+            };
+            names.push(name);
+            self.advance();
+            if !self.consume_if(TokenType::Comma) { break; }
+        }
+        if names.len() < 2 {
+            return Err(self.error("Destructuring needs a name for each of at least two elements, as in 'val (a, b) = pair'".to_string()));
+        }
+        self.expect(TokenType::RParen, "Expected ')' after the names to destructure into")?;
+        self.expect(TokenType::Assign, "Expected '=' after the names to destructure into")?;
+        let value = self.parse_expression()?;
+        Ok(Statement::Destructure { is_mutable, names, value, span })
     }
 
     fn parse_function_definition(&mut self, is_public: bool) -> Result<Statement, ParserError> {
+        // Doc comments belong to the first token on the line, which is `pub` for public functions.
+        let doc = self.current_token().doc.or_else(|| {
+            let previous = self.tokens.get(self.pos.checked_sub(1)?)?;
+            if previous.token_type == TokenType::Pub { previous.doc.clone() } else { None }
+        });
+        let span = self.current_span();
         self.expect(TokenType::Def, "Expected 'def'")?;
         let name = if let TokenType::Ident(n) = &self.current_token().token_type { n.clone() }
             else { return Err(self.error("Expected function name after 'def'".to_string())); };
@@ -401,13 +775,18 @@ impl<'a> Parser<'a> {
         let mut params = Vec::new();
         if !self.consume_if(TokenType::RParen) {
             loop {
+                let is_mutable = self.consume_if(TokenType::Var);
                 let param_name = if let TokenType::Ident(n) = &self.current_token().token_type { n.clone() }
                     else { return Err(self.error("Expected parameter name".to_string())); };
                 self.advance();
                 self.expect(TokenType::Colon, "Expected ':' after parameter name")?;
-                let param_type = self.parse_type()?;
-                params.push((param_name, param_type));
+                let data_type = self.parse_type()?;
+                let is_variadic = self.consume_if(TokenType::Ellipsis);
+                params.push(Param { name: param_name, data_type, is_mutable, is_variadic });
                 if !self.consume_if(TokenType::Comma) { break; }
+                if is_variadic {
+                    return Err(self.error("Only the last parameter can be variadic".to_string()));
+                }
             }
             self.expect(TokenType::RParen, "Expected ')' after parameters")?;
         }
@@ -418,49 +797,151 @@ impl<'a> Parser<'a> {
             Type::Void
         };
 
-        self.expect(TokenType::Colon, "Expected ':' after function signature")?;
+        self.expect_header_colon("function", "Expected ':' after function signature")?;
         self.expect(TokenType::Newline, "Expected newline after function definition")?;
-        let body = self.parse_block()?;
-        Ok(Statement::FunctionDefinition { is_public, name, params, return_type, body })
+        let mut body = self.parse_block()?;
+        // A function that returns a value can end with a bare expression instead of a `return`, as in Rust.
+        if return_type != Type::Void && matches!(body.last(), Some(Statement::Expression { .. })) {
+            if let Some(Statement::Expression { expr, span }) = body.pop() {
+                if !self.features.implicit_return {
+                    return Err(ParserError { message: unavailable("Ending a function with an expression instead of a 'return'"), line: span.line, column: span.column });
+                }
+                body.push(Statement::Return { value: Some(expr), span });
+            }
+        }
+        Ok(Statement::FunctionDefinition { is_public, name, params, return_type, body, doc, span })
     }
 
+    /// Parses an `if`, or an `unless`, which is an `if` whose condition is negated. An `elif` (or `else if`)
+    /// is parsed the same way and becomes the only statement of the `else` block it stands for.
     fn parse_if_statement(&mut self) -> Result<Statement, ParserError> {
-        self.expect(TokenType::If, "Expected 'if'")?;
-        let condition = self.parse_expression()?;
-        self.expect(TokenType::Colon, "Expected ':' after if condition")?;
-        self.expect(TokenType::Newline, "Expected newline after if colon")?;
+        let span = self.current_span();
+        let (keyword, negated) = match self.current_token().token_type {
+            TokenType::If => ("if", false),
+            TokenType::Elif => ("elif", false),
+            TokenType::Unless => ("unless", true),
+            _ => return Err(self.error("Expected 'if'".to_string())),
+        };
+        if keyword != "if" && !self.features.elif_and_unless {
+            return Err(self.error(unavailable(&format!("'{}'", keyword))));
+        }
+        self.advance();
+        let mut condition = self.parse_expression()?;
+        if negated {
+            condition = Expression::Unary { op: UnaryOperator::Not, operand: Box::new(condition) };
+        }
+        self.expect_header_colon(keyword, &format!("Expected ':' after {} condition", keyword))?;
+        self.expect(TokenType::Newline, &format!("Expected newline after {} colon", keyword))?;
         let consequence = self.parse_block()?;
         let mut alternative = None;
-        if self.consume_if(TokenType::Else) {
-            self.expect(TokenType::Colon, "Expected ':' after 'else'")?;
-            self.expect(TokenType::Newline, "Expected newline after else colon")?;
-            alternative = Some(self.parse_block()?);
+        let mut else_span = None;
+        if self.current_token().token_type == TokenType::Elif {
+            else_span = Some(self.current_span());
+            alternative = Some(vec![self.parse_if_statement()?]);
+        } else if self.current_token().token_type == TokenType::Else {
+            else_span = Some(self.current_span());
+            self.advance();
+            if self.current_token().token_type == TokenType::If {
+                if !self.features.elif_and_unless {
+                    return Err(self.error(unavailable("'else if'")));
+                }
+                alternative = Some(vec![self.parse_if_statement()?]);
+            } else {
+                self.expect_header_colon("else", "Expected ':' after 'else'")?;
+                self.expect(TokenType::Newline, "Expected newline after else colon")?;
+                alternative = Some(self.parse_block()?);
+            }
         }
-        Ok(Statement::If { condition, consequence, alternative })
+        Ok(Statement::If { condition, consequence, alternative, span, else_span })
     }
 
-    fn parse_while_statement(&mut self) -> Result<Statement, ParserError> {
+    fn parse_while_statement(&mut self, label: Option<String>, span: Span) -> Result<Statement, ParserError> {
         self.expect(TokenType::While, "Expected 'while'")?;
         let condition = self.parse_expression()?;
-        self.expect(TokenType::Colon, "Expected ':' after while condition")?;
+        self.expect_header_colon("while", "Expected ':' after while condition")?;
         self.expect(TokenType::Newline, "Expected newline after while colon")?;
         let body = self.parse_block()?;
-        Ok(Statement::While { condition, body })
+        let else_block = self.parse_loop_else()?;
+        Ok(Statement::While { label, condition, body, else_block, span })
+    }
+
+    /// Parses `loop:`, which runs until a `break` and is a `while true:` from here on. With no condition
+    /// to turn false, it has no `else` block.
+    fn parse_loop_statement(&mut self, label: Option<String>, span: Span) -> Result<Statement, ParserError> {
+        if !self.features.infinite_loop {
+            return Err(self.error(unavailable("'loop'")));
+        }
+        self.expect(TokenType::Loop, "Expected 'loop'")?;
+        self.expect_header_colon("loop", "Expected ':' after 'loop'")?;
+        self.expect(TokenType::Newline, "Expected newline after loop colon")?;
+        let body = self.parse_block()?;
+        if self.current_token().token_type == TokenType::Else {
+            return Err(self.error("A 'loop' can't have an 'else' block, since it only ends with a 'break'".to_string()));
+        }
+        Ok(Statement::While { label, condition: Expression::Literal(Literal::Bool(true)), body, else_block: None, span })
     }
 
-    fn parse_for_statement(&mut self) -> Result<Statement, ParserError> {
+    fn parse_for_statement(&mut self, label: Option<String>, span: Span) -> Result<Statement, ParserError> {
         self.expect(TokenType::For, "Expected 'for'")?;
         let iterator = if let TokenType::Ident(n) = &self.current_token().token_type { n.clone() }
-            else { return Err(self.error("Expected iterator name after 'for'".to_string())); };
+            else { return Err(self.error(format!("Expected iterator name after 'for', got {}", describe_token(&self.current_token().token_type)))); };
         self.advance();
         self.expect(TokenType::In, "Expected 'in' after iterator")?;
         let start = self.parse_expression()?;
-        self.expect(TokenType::Range, "Expected '..' range operator")?;
-        let end = self.parse_expression()?;
-        self.expect(TokenType::Colon, "Expected ':' after range")?;
+        // Without a `..`, the loop runs over the elements of what follows `in`.
+        let end = if self.consume_if(TokenType::Range) { Some(self.parse_expression()?) } else { None };
+        if end.is_none() && !self.features.for_each {
+            return Err(self.error(unavailable("Looping over a list or a string")));
+        }
+        self.expect_header_colon("for", "Expected ':' after range")?;
         self.expect(TokenType::Newline, "Expected newline after for colon")?;
         let body = self.parse_block()?;
-        Ok(Statement::For { iterator, start, end, body })
+        let else_block = self.parse_loop_else()?;
+        match end {
+            Some(end) => Ok(Statement::For { label, iterator, start, end, body, else_block, span }),
+            None => Ok(Statement::ForEach { label, iterator, iterable: start, body, else_block, span }),
+        }
+    }
+
+    /// Parses a labeled loop, e.g. `outer: while cond:`.
+    fn parse_labeled_loop(&mut self) -> Result<Statement, ParserError> {
+        let span = self.current_span();
+        if !self.features.loop_labels {
+            return Err(self.error(unavailable("Labeling a loop")));
+        }
+        let label = if let TokenType::Ident(name) = self.current_token().token_type { name }
+            else { return Err(self.error("Expected loop label".to_string())); };
+        self.advance();
+        self.expect(TokenType::Colon, "Expected ':' after loop label")?;
+        match self.current_token().token_type {
+            TokenType::While => self.parse_while_statement(Some(label), span),
+            TokenType::Loop => self.parse_loop_statement(Some(label), span),
+            TokenType::For => self.parse_for_statement(Some(label), span),
+            _ => Err(self.error(format!("Expected 'while', 'loop', or 'for' after label '{}'", label))),
+        }
+    }
+
+    /// Parses the optional `else:` block that follows a loop body.
+    fn parse_loop_else(&mut self) -> Result<Option<Vec<Statement>>, ParserError> {
+        if !self.at_feature(TokenType::Else, self.features.loop_else, "An 'else' block after a loop")? {
+            return Ok(None);
+        }
+        self.expect_header_colon("else", "Expected ':' after 'else'")?;
+        self.expect(TokenType::Newline, "Expected newline after else colon")?;
+        Ok(Some(self.parse_block()?))
+    }
+
+    /// Parses the optional label after `break` or `continue`.
+    fn parse_loop_control_label(&mut self) -> Result<Option<String>, ParserError> {
+        if let TokenType::Ident(name) = self.current_token().token_type {
+            if !self.features.loop_labels {
+                return Err(self.error(unavailable("Naming a loop to break or continue")));
+            }
+            self.advance();
+            Ok(Some(name))
+        } else {
+            Ok(None)
+        }
     }
 
     fn parse_import_statement(&mut self) -> Result<Statement, ParserError> {
@@ -475,6 +956,7 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_class_statement(&mut self, is_public: bool) -> Result<Statement, ParserError> {
+        let span = self.current_span();
         self.expect(TokenType::Class, "Expected 'class'")?;
         let name = if let TokenType::Ident(n) = &self.current_token().token_type { n.clone() }
             else { return Err(self.error("Expected class name".to_string())); };
@@ -482,7 +964,59 @@ impl<'a> Parser<'a> {
         self.expect(TokenType::Colon, "Expected ':' after class name")?;
         self.expect(TokenType::Newline, "Expected newline after class definition")?;
         let members = self.parse_class_block()?;
-        Ok(Statement::Class { is_public, name, members })
+        Ok(Statement::Class { is_public, name, members, span })
+    }
+
+    /// Parses `enum Name: A, B = 5, C`, or the same with the variants on indented lines below it.
+    fn parse_enum_statement(&mut self, is_public: bool) -> Result<Statement, ParserError> {
+        let span = self.current_span();
+        if !self.features.enums {
+            return Err(self.error(unavailable("'enum'")));
+        }
+        self.expect(TokenType::Enum, "Expected 'enum'")?;
+        let name = if let TokenType::Ident(n) = &self.current_token().token_type { n.clone() }
+            else { return Err(self.error("Expected enum name".to_string())); };
+        self.advance();
+        self.expect(TokenType::Colon, "Expected ':' after enum name")?;
+        let mut variants = Vec::new();
+        if self.consume_if(TokenType::Newline) {
+            self.skip_blank_lines();
+            self.expect(TokenType::Indent, "Expected indentation for enum variants")?;
+            while self.current_token().token_type != TokenType::Dedent && self.current_token().token_type != TokenType::Eof {
+                while self.consume_statement_separator() {}
+                if self.current_token().token_type == TokenType::Dedent { break; }
+                self.parse_enum_variants(&mut variants)?;
+            }
+            self.expect(TokenType::Dedent, "Expected dedent to end enum variants")?;
+        } else {
+            self.parse_enum_variants(&mut variants)?;
+        }
+        Ok(Statement::Enum { is_public, name, variants, span })
+    }
+
+    /// Parses a line of comma-separated enum variants, each optionally followed by `= <int>`.
+    fn parse_enum_variants(&mut self, variants: &mut Vec<EnumVariant>) -> Result<(), ParserError> {
+        loop {
+            let span = self.current_span();
+            let name = if let TokenType::Ident(n) = &self.current_token().token_type { n.clone() }
+                else { return Err(self.error(format!("Expected enum variant name, got {:?}", self.current_token().token_type))); };
+            self.advance();
+            let value = if self.consume_if(TokenType::Assign) {
+                let negative = self.consume_if(TokenType::Op("-".to_string()));
+                let TokenType::Int(n) = self.current_token().token_type else {
+                    return Err(self.error(format!("Expected an integer value for enum variant '{}'", name)));
+                };
+                self.advance();
+                if negative { -n } else { n }
+            } else {
+                variants.last().map_or(0, |previous: &EnumVariant| previous.value + 1)
+            };
+            variants.push(EnumVariant { name, value, span });
+            // A trailing comma is allowed.
+            if !self.consume_if(TokenType::Comma) || !matches!(self.current_token().token_type, TokenType::Ident(_)) {
+                return Ok(());
+            }
+        }
     }
 
     fn parse_try_catch_statement(&mut self) -> Result<Statement, ParserError> {
@@ -506,27 +1040,112 @@ impl<'a> Parser<'a> {
         Ok(Statement::TryCatch { try_block, catch_var, catch_block })
     }
 
+    /// Parses a statement into `statements`, or for an `@if`, the statements of whichever of its blocks
+    /// the build flags pick.
+    fn parse_statement_into(&mut self, statements: &mut Vec<Statement>) -> Result<(), ParserError> {
+        if self.current_token().token_type == TokenType::At {
+            statements.extend(self.parse_conditional_compilation()?);
+        } else {
+            statements.push(self.parse_statement()?);
+        }
+        Ok(())
+    }
+
+    /// Parses `@if <condition>:` with its block and an optional `@else:` block. The condition is worked out
+    /// here from the build flags, and only the block it picks is returned, so the other one is parsed but
+    /// never checked or compiled.
+    fn parse_conditional_compilation(&mut self) -> Result<Vec<Statement>, ParserError> {
+        if !self.features.conditional_compilation {
+            return Err(self.error(unavailable("'@if'")));
+        }
+        self.expect(TokenType::At, "Expected '@'")?;
+        if self.current_token().token_type != TokenType::If {
+            return Err(self.error("Expected 'if' after '@'; compile-time conditions are written '@if <condition>:'".to_string()));
+        }
+        self.advance();
+        let start = self.current_token();
+        let condition = self.parse_expression()?;
+        let included = self.evaluate_flag_condition(&condition).map_err(|message| ParserError { message, line: start.line, column: start.column })?;
+        self.expect(TokenType::Colon, "Expected ':' after '@if' condition")?;
+        self.expect(TokenType::Newline, "Expected newline after '@if' colon")?;
+        let consequence = self.parse_block()?;
+        let alternative = if self.current_token().token_type == TokenType::At && self.peek_token_type(1) == TokenType::Else {
+            self.advance();
+            self.advance();
+            self.expect(TokenType::Colon, "Expected ':' after '@else'")?;
+            self.expect(TokenType::Newline, "Expected newline after '@else' colon")?;
+            self.parse_block()?
+        } else {
+            Vec::new()
+        };
+        Ok(if included { consequence } else { alternative })
+    }
+
+    /// The value of an `@if` condition, which can only use the build flags, literals, comparisons, and
+    /// `&&`, `||`, and `!`.
+    fn evaluate_flag_condition(&self, condition: &Expression) -> Result<bool, String> {
+        let mut pending = vec![condition];
+        while let Some(expr) = pending.pop() {
+            if let Expression::Identifier(name) = expr {
+                if self.flags.get(name).is_none() {
+                    return Err(format!("Unknown build flag '{}' in '@if'; the flags are TARGET and OPTIMIZE", name));
+                }
+            }
+            pending.extend(expr.children());
+        }
+        match evaluate_constant(condition, &|name| self.flags.get(name)) {
+            Some(Literal::Bool(value)) => Ok(value),
+            _ => Err("An '@if' condition has to compare the build flags with literals, like 'TARGET == \"c\"' or 'OPTIMIZE > 0'".to_string()),
+        }
+    }
+
     fn parse_statement(&mut self) -> Result<Statement, ParserError> {
-        while self.consume_if(TokenType::Newline) {}
+        while self.consume_statement_separator() {}
 
         match self.current_token().token_type {
             TokenType::Import => self.parse_import_statement(),
             TokenType::Class => self.parse_class_statement(false),
+            TokenType::Enum => self.parse_enum_statement(false),
             TokenType::Try => self.parse_try_catch_statement(),
+            TokenType::StaticAssert => {
+                let span = self.current_span();
+                if !self.features.static_assert {
+                    return Err(self.error(unavailable("'static_assert'")));
+                }
+                self.advance();
+                let condition = self.parse_expression()?;
+                let message = if self.consume_if(TokenType::Comma) {
+                    match self.current_token().token_type {
+                        TokenType::Str(message) => {
+                            self.advance();
+                            Some(message)
+                        }
+                        _ => return Err(self.error("Expected a string message after ',' in static_assert".to_string())),
+                    }
+                } else {
+                    None
+                };
+                Ok(Statement::StaticAssert { condition, message, span })
+            },
             TokenType::Break => {
+                let span = self.current_span();
                 self.advance();
-                Ok(Statement::Break)
+                let label = self.parse_loop_control_label()?;
+                Ok(Statement::Break { label, span })
             },
             TokenType::Continue => {
+                let span = self.current_span();
                 self.advance();
-                Ok(Statement::Continue)
+                let label = self.parse_loop_control_label()?;
+                Ok(Statement::Continue { label, span })
             },
-            TokenType::Print => {
+            TokenType::Print | TokenType::Eprint => {
+                let span = self.current_span();
+                let (keyword, stream) = if self.current_token().token_type == TokenType::Print { ("print", Stream::Stdout) } else { ("eprint", Stream::Stderr) };
                 self.advance();
-                self.expect(TokenType::LParen, "Expected '(' after 'print'")?;
-                let arg = self.parse_expression()?;
-                self.expect(TokenType::RParen, "Expected ')' after print argument")?;
-                Ok(Statement::Print(arg))
+                self.expect(TokenType::LParen, &format!("Expected '(' after '{}'", keyword))?;
+                let (values, sep) = self.parse_print_arguments(keyword)?;
+                Ok(Statement::Print { values, sep, stream, span })
             },
             TokenType::Pub => {
                 self.advance();
@@ -534,38 +1153,125 @@ impl<'a> Parser<'a> {
                     TokenType::Val | TokenType::Var => self.parse_declaration(true),
                     TokenType::Def => self.parse_function_definition(true),
                     TokenType::Class => self.parse_class_statement(true),
-                    _ => Err(self.error("Expected 'val', 'var', 'def', or 'class' after 'pub'".to_string())),
+                    TokenType::Enum => self.parse_enum_statement(true),
+                    _ => Err(self.error("Expected 'val', 'var', 'def', 'class', or 'enum' after 'pub'".to_string())),
                 }
             },
+            TokenType::Val | TokenType::Var if self.peek_token_type(1) == TokenType::LParen => self.parse_destructure(),
             TokenType::Val | TokenType::Var => self.parse_declaration(false),
             TokenType::Def => self.parse_function_definition(false),
-            TokenType::If => self.parse_if_statement(),
-            TokenType::While => self.parse_while_statement(),
-            TokenType::For => self.parse_for_statement(),
+            TokenType::If | TokenType::Unless => self.parse_if_statement(),
+            TokenType::Elif => Err(self.error("'elif' has to follow an 'if' block".to_string())),
+            TokenType::While => self.parse_while_statement(None, self.current_span()),
+            TokenType::Loop => self.parse_loop_statement(None, self.current_span()),
+            TokenType::For => self.parse_for_statement(None, self.current_span()),
+            TokenType::Ident(_) if self.peek_token_type(1) == TokenType::Colon
+                && matches!(self.peek_token_type(2), TokenType::While | TokenType::Loop | TokenType::For) => self.parse_labeled_loop(),
             TokenType::Return => {
+                let span = self.current_span();
                 self.advance();
-                let expr = if self.current_token().token_type == TokenType::Newline || self.current_token().token_type == TokenType::EOF { None }
+                let expr = if matches!(self.current_token().token_type, TokenType::Newline | TokenType::Semicolon | TokenType::Eof) { None }
                     else { Some(self.parse_expression()?) };
-                Ok(Statement::Return(expr))
+                Ok(Statement::Return { value: expr, span })
             },
             _ => {
-                let target = self.parse_expression()?;
-                if self.consume_if(TokenType::Assign) {
-                    let value = self.parse_expression()?;
-                    Ok(Statement::Assignment { target, value })
-                } else {
-                    Ok(Statement::Expression(target))
+                let span = self.current_span();
+                let expr = self.parse_expression()?;
+                if let TokenType::OpAssign(op) = self.current_token().token_type {
+                    return self.parse_compound_assignment(expr, &op, span);
+                }
+                if !self.consume_if(TokenType::Assign) {
+                    return Ok(Statement::Expression { expr, span });
                 }
+                // In `a = b = value`, every expression but the last is a target.
+                let mut targets = vec![expr];
+                let mut value = self.parse_expression()?;
+                while self.consume_if(TokenType::Assign) {
+                    targets.push(std::mem::replace(&mut value, self.parse_expression()?));
+                }
+                Ok(Statement::Assignment { targets, op: None, value, span })
             }
         }
     }
 
+    /// Parses the rest of a compound assignment like `x += value`, once `target` has been parsed and the
+    /// current token is the `op=`.
+    fn parse_compound_assignment(&mut self, target: Expression, op: &str, span: Span) -> Result<Statement, ParserError> {
+        if !self.features.compound_assignment {
+            return Err(self.error(unavailable(&format!("'{}='", op))));
+        }
+        let op = Self::token_to_binary_op(&TokenType::Op(op.to_string())).expect("every compound assignment has a binary operator");
+        if !matches!(op, BinaryOperator::And | BinaryOperator::Or) {
+            self.advance();
+            let value = self.parse_expression()?;
+            return Ok(Statement::Assignment { targets: vec![target], op: Some(op), value, span });
+        }
+        if target.is_discard() {
+            return Err(self.error(format!("'_' has no value to update, so it can't be used with '{}='", op)));
+        }
+        self.advance();
+        // `ok &&= check()` is `ok = ok && check()`, so `check()` only runs when it can change `ok`.
+        let value = Expression::BinaryOp { op, left: Box::new(target.clone()), right: Box::new(self.parse_expression()?) };
+        Ok(Statement::Assignment { targets: vec![target], op: None, value, span })
+    }
+
     pub fn parse(&mut self) -> Result<Program, ParserError> {
         let mut statements = Vec::new();
-        while self.current_token().token_type != TokenType::EOF {
-            if self.consume_if(TokenType::Newline) { continue; }
-            statements.push(self.parse_statement()?);
+        while self.current_token().token_type != TokenType::Eof {
+            if self.consume_statement_separator() { continue; }
+            self.parse_statement_into(&mut statements)?;
         }
         Ok(Program { statements })
     }
 }
+
+/// Splits an f-string placeholder like `count:>5` into its expression and its format specifier, if it has
+/// one. A `:` inside brackets or quotes belongs to the expression, as in a dict literal or a `':'` char.
+fn split_format_spec(placeholder: &str) -> (&str, Option<&str>) {
+    let (mut depth, mut quote, mut escaped) = (0, None, false);
+    for (i, c) in placeholder.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, ':') if depth == 0 => return (&placeholder[..i], Some(&placeholder[i + 1..])),
+            _ => {}
+        }
+    }
+    (placeholder, None)
+}
+
+/// Pads a placeholder's text to the width its format specifier asks for: `>5` (or just `5`) right-aligns
+/// it in 5 columns and `<5` left-aligns it. Anything else is an error, so a typo is caught while compiling.
+fn apply_format_spec(text: Expression, spec: &str, token: &Token) -> Result<Expression, ParserError> {
+    let (function, digits) = match spec.strip_prefix('<') {
+        Some(digits) => ("align_left", digits),
+        None => ("align_right", spec.strip_prefix('>').unwrap_or(spec)),
+    };
+    let width = match digits.parse::<i64>() {
+        Ok(width) if digits.bytes().all(|b| b.is_ascii_digit()) => width,
+        _ => return Err(ParserError {
+            message: format!("Invalid format specifier '{}' in f-string; expected a width like '5' or '>5' to right-align, or '<5' to left-align", spec),
+            line: token.line,
+            column: token.column,
+        }),
+    };
+    Ok(Expression::Call {
+        callee: Box::new(Expression::Identifier(function.to_string())),
+        args: vec![text, Expression::Literal(Literal::Int(width))],
+    })
+}
+
+/// A token as it's written, for an error message about finding it where something else was expected.
+fn describe_token(token_type: &TokenType) -> String {
+    match token_type {
+        TokenType::Newline => "the end of the line".to_string(),
+        TokenType::Indent | TokenType::Dedent => "a change of indentation".to_string(),
+        TokenType::Eof => "the end of the file".to_string(),
+        _ => format!("'{}'", spelling(token_type)),
+    }
+}
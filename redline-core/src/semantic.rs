@@ -0,0 +1,1092 @@
+//! Semantic analysis for REDLINE.
+//! Runs between parsing and codegen and rejects programs that parse fine but make no sense,
+//! like a `break` that isn't inside any loop or an assignment to a `val`.
+//! It also collects warnings for code that is legal but almost certainly a mistake.
+use crate::ast::{Program, Statement, Expression, AssignTarget, BinaryOperator, ClassMember, EnumVariant, Span, Param, Literal, Type, UnaryOperator, DISCARD};
+use crate::dialect::{unavailable, Features};
+use crate::lint::{self, CONSTANT_CONDITION, FLOAT_EQUALITY};
+use crate::optimizer::evaluate_constant;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug)]
+pub struct SemanticError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    /// See `Span::file`.
+    pub file: usize,
+}
+
+impl SemanticError {
+    fn new(message: String, span: Span) -> Self {
+        Self { message, line: span.line, column: span.column, file: span.file }
+    }
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Semantic Error: {}", self.message)
+    }
+}
+
+/// A problem that doesn't stop compilation but is reported to the user.
+#[derive(Debug)]
+pub struct Warning {
+    /// The lint that produced the warning, e.g. `self-comparison`. See `lint::LINTS`.
+    pub lint: &'static str,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    /// See `Span::file`.
+    pub file: usize,
+}
+
+impl Warning {
+    pub(crate) fn new(lint: &'static str, message: String, span: Span) -> Self {
+        Self { lint, message, line: span.line, column: span.column, file: span.file }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Warning: {} [{}]", self.message, self.lint)
+    }
+}
+
+/// Settings that loosen what the semantic pass accepts.
+#[derive(Debug, Clone, Default)]
+pub struct SemanticOptions {
+    /// Allow `+` between a string and a plain value like an `int`, which codegen then converts with
+    /// `to_string` (`--auto-stringify`). Without it, that's an error.
+    pub auto_stringify: bool,
+    /// The syntax being accepted. See `Dialect`.
+    pub features: Features,
+}
+
+/// Checks a parsed program for semantic errors, returning any warnings (including lints) if there are none.
+pub fn analyze(program: &Program) -> Result<Vec<Warning>, SemanticError> {
+    analyze_with(program, &SemanticOptions::default())
+}
+
+/// `analyze` with non-default settings.
+pub fn analyze_with(program: &Program, options: &SemanticOptions) -> Result<Vec<Warning>, SemanticError> {
+    let mut analyzer = Analyzer::new();
+    analyzer.auto_stringify = options.auto_stringify;
+    analyzer.features = options.features;
+    analyzer.functions = program.statements.iter()
+        .filter_map(|s| match s { Statement::FunctionDefinition { name, .. } => Some(name.clone()), _ => None })
+        .collect();
+    analyzer.enums = program.statements.iter()
+        .filter_map(|s| match s {
+            Statement::Enum { name, variants, .. } => Some((name.clone(), variants.iter().map(|v| (v.name.clone(), v.value)).collect())),
+            _ => None,
+        })
+        .collect();
+    check_type_names(program)?;
+    analyzer.check_block(&program.statements)?;
+    let mut warnings = analyzer.warnings;
+    warnings.extend(lint::lint(program));
+    Ok(warnings)
+}
+
+/// Combines programs parsed from separate files (see `Parser::set_file`) into one, in order. Each
+/// top-level name can only be defined in one of the files, except that a function can be overloaded
+/// across files as long as the overloads take different parameter types. `file_names` are for the message.
+pub fn merge(programs: Vec<Program>, file_names: &[&str]) -> Result<Program, SemanticError> {
+    let mut statements = Vec::new();
+    // Each top-level definition so far: its name, its parameter types if it's a function, and where it is.
+    let mut defined: Vec<(&str, Option<Vec<&Type>>, Span)> = Vec::new();
+    for program in &programs {
+        for statement in &program.statements {
+            let (name, params, span) = match statement {
+                Statement::FunctionDefinition { name, params, span, .. } => (name, Some(params.iter().map(|p| &p.data_type).collect()), *span),
+                Statement::Class { name, span, .. } | Statement::Enum { name, span, .. } => (name, None, *span),
+                Statement::Declaration { name, span, .. } if name != DISCARD => (name, None, *span),
+                _ => continue,
+            };
+            let earlier = defined.iter().find(|(other, other_params, other_span)| {
+                other == name && other_span.file != span.file && (params.is_none() || other_params.is_none() || *other_params == params)
+            });
+            if let Some((_, _, earlier)) = earlier {
+                return Err(SemanticError::new(
+                    format!("'{}' is already defined in {} at line {}", name, file_names[earlier.file], earlier.line),
+                    span,
+                ));
+            }
+            defined.push((name, params, span));
+        }
+    }
+    for program in programs {
+        statements.extend(program.statements);
+    }
+    Ok(Program { statements })
+}
+
+/// What kind of thing a top-level symbol names.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SymbolKind {
+    Function,
+    Class,
+    Enum,
+    Variable,
+}
+
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `pad` so the kinds line up when printed with a width.
+        f.pad(match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Class => "class",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Variable => "variable",
+        })
+    }
+}
+
+/// A top-level definition, as listed by `--dump-symbols`.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The function's signature or the variable's type, in REDLINE syntax. Empty for classes.
+    pub signature: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let definition = match self.kind {
+            SymbolKind::Function => format!("{}{}", self.name, self.signature),
+            SymbolKind::Class | SymbolKind::Enum => self.name.clone(),
+            SymbolKind::Variable => format!("{}: {}", self.name, self.signature),
+        };
+        write!(f, "{:<8}  {}  at {}:{}", self.kind, definition, self.line, self.column)
+    }
+}
+
+/// Lists the functions, classes, and variables defined at the top level of a program, in source order.
+pub fn symbols(program: &Program) -> Vec<Symbol> {
+    program.statements.iter().filter_map(|statement| {
+        let (name, kind, signature, span) = match statement {
+            Statement::FunctionDefinition { name, params, return_type, span, .. } => {
+                let params: Vec<String> = params.iter()
+                    .map(|p| format!("{}{}: {}{}", if p.is_mutable { "var " } else { "" }, p.name, p.data_type.source_name(), if p.is_variadic { "..." } else { "" }))
+                    .collect();
+                (name, SymbolKind::Function, format!("({}) -> {}", params.join(", "), return_type.source_name()), span)
+            }
+            Statement::Class { name, span, .. } => (name, SymbolKind::Class, String::new(), span),
+            Statement::Enum { name, span, .. } => (name, SymbolKind::Enum, String::new(), span),
+            Statement::Declaration { name, data_type, span, .. } if name != DISCARD => (name, SymbolKind::Variable, data_type.source_name(), span),
+            _ => return None,
+        };
+        Some(Symbol { name: name.clone(), kind, signature, line: span.line, column: span.column })
+    }).collect()
+}
+
+/// How a name in scope was introduced.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum BindingKind {
+    Variable,
+    Parameter,
+}
+
+/// What the analyzer knows about a name in scope.
+#[derive(Debug, Clone)]
+struct Binding {
+    kind: BindingKind,
+    is_mutable: bool,
+    /// The declared type, when the name has one (a `catch` variable doesn't).
+    data_type: Option<Type>,
+    /// The value of a `val` whose initializer is a constant of its declared type, for `static_assert`.
+    constant: Option<Literal>,
+}
+
+struct Analyzer {
+    /// Names declared in each enclosing block, innermost last.
+    scopes: Vec<HashMap<String, Binding>>,
+    /// The loops enclosing the statement being checked, innermost last. `None` for unlabeled loops.
+    loops: Vec<Option<String>>,
+    /// Every label used so far in the current function. C++ labels are function-scoped, so they must be unique.
+    used_labels: Vec<String>,
+    /// The fields of the class whose methods are being checked, and whether each is a `var`.
+    fields: HashMap<String, bool>,
+    /// Top-level functions defined in the program. A user function replaces the builtin of the same name.
+    functions: HashSet<String>,
+    /// The value of each enum variant, by enum name and then variant name.
+    enums: HashMap<String, HashMap<String, i64>>,
+    /// See `SemanticOptions::auto_stringify`.
+    auto_stringify: bool,
+    /// See `SemanticOptions::features`.
+    features: Features,
+    /// The declared return type of the function or method whose body is being checked, or `None` in
+    /// the top-level code, where `return` ends the program.
+    return_type: Option<Type>,
+    warnings: Vec<Warning>,
+}
+
+impl Analyzer {
+    fn new() -> Self {
+        Self { scopes: vec![HashMap::new()], loops: Vec::new(), used_labels: Vec::new(), fields: HashMap::new(), functions: HashSet::new(), enums: HashMap::new(), auto_stringify: false, features: Features::default(), return_type: None, warnings: Vec::new() }
+    }
+
+    fn declare(&mut self, name: &str, kind: BindingKind, is_mutable: bool, data_type: Option<Type>, constant: Option<Literal>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), Binding { kind, is_mutable, data_type, constant });
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn check_block(&mut self, statements: &[Statement]) -> Result<(), SemanticError> {
+        for statement in statements {
+            self.check_statement(statement)?;
+        }
+        Ok(())
+    }
+
+    /// Checks a nested block in its own scope.
+    fn check_scoped_block(&mut self, statements: &[Statement]) -> Result<(), SemanticError> {
+        self.scopes.push(HashMap::new());
+        let result = self.check_block(statements);
+        self.scopes.pop();
+        result
+    }
+
+    fn check_function(&mut self, params: &[Param], return_type: &Type, body: &[Statement]) -> Result<(), SemanticError> {
+        // Neither variables nor loops reach across function boundaries.
+        let outer_scopes = std::mem::replace(&mut self.scopes, vec![HashMap::new()]);
+        let outer_loops = std::mem::take(&mut self.loops);
+        let outer_labels = std::mem::take(&mut self.used_labels);
+        let outer_return_type = self.return_type.replace(return_type.clone());
+        for param in params {
+            self.declare(&param.name, BindingKind::Parameter, param.is_mutable, Some(param.body_type()), None);
+        }
+        // A function mixing bare and valued `return`s gets the error pointing at one of each, rather than
+        // one about whichever comes first.
+        let result = check_returns(body).and_then(|_| self.check_block(body));
+        self.scopes = outer_scopes;
+        self.loops = outer_loops;
+        self.used_labels = outer_labels;
+        self.return_type = outer_return_type;
+        result
+    }
+
+    /// Checks the methods and constructors of a class, with its fields in `self.fields`.
+    fn check_methods(&mut self, members: &[ClassMember]) -> Result<(), SemanticError> {
+        for member in members {
+            if let ClassMember::Method(Statement::FunctionDefinition { params, return_type, body, span, .. })
+                | ClassMember::Constructor(Statement::FunctionDefinition { params, return_type, body, span, .. }) = member {
+                if params.iter().any(|p| p.is_variadic) {
+                    return Err(SemanticError::new("Variadic parameters are only supported on functions, not methods".to_string(), *span));
+                }
+                self.check_function(params, return_type, body)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_assignment(&self, target: &Expression, value: &Expression, span: Span) -> Result<(), SemanticError> {
+        if let Expression::Slice { .. } = target {
+            return Err(SemanticError::new("Cannot assign to a slice; a slice is a copy, so assign to the elements instead".to_string(), span));
+        }
+        let (container, part) = match target.assign_target() {
+            Some(AssignTarget::Variable(name)) => return self.check_variable_assignment(name, value, span),
+            Some(AssignTarget::Element { container, .. }) => (container, "an element"),
+            Some(AssignTarget::Field { object, .. }) => (object, "a field"),
+            None => return Err(SemanticError::new(
+                format!("Cannot assign to '{}'; only a variable, a list or dict element, or a field can be assigned to", target.to_source()),
+                span,
+            )),
+        };
+        // Changing part of a value changes the variable holding it, so that variable has to be a `var`.
+        let Some(name) = root_variable(container) else { return Ok(()) };
+        let is_parameter = match self.lookup(name) {
+            Some(Binding { is_mutable: true, .. }) => return Ok(()),
+            Some(binding) => binding.kind == BindingKind::Parameter,
+            None if self.fields.get(name) == Some(&false) => false,
+            None => return Ok(()),
+        };
+        let message = if is_parameter {
+            format!("Cannot assign to {} of parameter '{}' because parameters are immutable; declare it as 'var {}' to allow this", part, name, name)
+        } else {
+            format!("Cannot assign to {} of '{}' because it was declared with 'val'; declare it with 'var' to allow this", part, name)
+        };
+        Err(SemanticError::new(message, span))
+    }
+
+    fn check_variable_assignment(&self, name: &str, value: &Expression, span: Span) -> Result<(), SemanticError> {
+        if name == DISCARD {
+            return Ok(());
+        }
+        match self.lookup(name) {
+            Some(Binding { kind: BindingKind::Parameter, is_mutable: false, .. }) => Err(SemanticError::new(
+                format!("Cannot assign to parameter '{}' because parameters are immutable; declare it as 'var {}' to allow this", name, name),
+                span,
+            )),
+            Some(Binding { kind: BindingKind::Variable, is_mutable: false, .. }) => Err(SemanticError::new(
+                format!("Cannot assign to '{}' because it was declared with 'val'", name),
+                span,
+            )),
+            Some(Binding { data_type: Some(data_type), .. }) => check_literal_range(data_type, value, span),
+            _ => Ok(()),
+        }
+    }
+
+    /// `(name = value)` can only assign to a variable that's already in scope: a local, a parameter, or
+    /// (in a method) a field of the class.
+    fn check_assign_expression(&self, target: &Expression, value: &Expression, span: Span) -> Result<(), SemanticError> {
+        let name = match target {
+            Expression::Identifier(name) if name != DISCARD => name,
+            _ => return Err(SemanticError::new("Only a variable can be assigned to inside an expression, as in '(n = value)'".to_string(), span)),
+        };
+        if self.lookup(name).is_some() {
+            return self.check_assignment(target, value, span);
+        }
+        match self.fields.get(name) {
+            Some(true) => Ok(()),
+            Some(false) => Err(SemanticError::new(format!("Cannot assign to '{}' because it was declared with 'val'", name), span)),
+            None => Err(SemanticError::new(
+                format!("Cannot assign to '{}' because it isn't declared; declare it first with 'var {}: <type>'", name, name),
+                span,
+            )),
+        }
+    }
+
+    /// Warns about the branch of an `if` that a literal `true`/`false` condition makes unreachable.
+    fn check_constant_condition(&mut self, condition: &Expression, span: Span, else_span: Option<Span>) {
+        match (condition, else_span) {
+            (Expression::Literal(Literal::Bool(true)), Some(else_span)) => self.warnings.push(Warning::new(
+                CONSTANT_CONDITION,
+                "This 'else' branch is unreachable because the 'if' condition is always true".to_string(),
+                else_span,
+            )),
+            (Expression::Literal(Literal::Bool(false)), _) => self.warnings.push(Warning::new(
+                CONSTANT_CONDITION,
+                "The body of this 'if' is unreachable because its condition is always false".to_string(),
+                span,
+            )),
+            _ => {}
+        }
+    }
+
+    fn check_loop(&mut self, label: &Option<String>, body: &[Statement], span: Span) -> Result<(), SemanticError> {
+        if let Some(name) = label {
+            if self.used_labels.contains(name) {
+                return Err(SemanticError::new(format!("Loop label '{}' is already used in this function", name), span));
+            }
+            self.used_labels.push(name.clone());
+        }
+        self.loops.push(label.clone());
+        let result = self.check_scoped_block(body);
+        self.loops.pop();
+        result
+    }
+
+    /// A loop's `else` block runs after the loop is over, so `break`/`continue` in it refer to an outer loop.
+    fn check_loop_else(&mut self, else_block: &Option<Vec<Statement>>) -> Result<(), SemanticError> {
+        match else_block {
+            Some(block) => self.check_scoped_block(block),
+            None => Ok(()),
+        }
+    }
+
+    fn check_loop_control(&self, keyword: &str, label: &Option<String>, span: Span) -> Result<(), SemanticError> {
+        if self.loops.is_empty() {
+            return Err(SemanticError::new(format!("'{}' outside of a loop", keyword), span));
+        }
+        if let Some(name) = label {
+            if !self.loops.iter().any(|l| l.as_deref() == Some(name.as_str())) {
+                return Err(SemanticError::new(format!("'{} {}' does not refer to an enclosing loop", keyword, name), span));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the calls to builtins, the string concatenations, and the assignments inside an expression. `span` is the statement the expression is in.
+    fn check_expression(&self, expr: &Expression, span: Span) -> Result<(), SemanticError> {
+        if let Expression::Call { callee, args } = expr {
+            match &**callee {
+                Expression::Identifier(name) if self.functions.contains(name) => {}
+                Expression::Identifier(name) if name == "to_string" => self.check_to_string(args, span)?,
+                Expression::Identifier(name) if name == "parse_int" || name == "parse_float" => self.check_parse(name, args, span)?,
+                Expression::Identifier(name) if name == "range" => self.check_range(args, span)?,
+                _ => {}
+            }
+        }
+        if let Expression::Assign { target, value } = expr {
+            self.check_assign_expression(target, value, span)?;
+        }
+        if let Expression::Match { subject, arms, default } = expr {
+            self.check_match(subject, arms, default, span)?;
+        }
+        if let Expression::BinaryOp { op: BinaryOperator::In, left, right } = expr {
+            self.check_membership(left, right, span)?;
+        }
+        if let Expression::BinaryOp { op, left, right } = expr {
+            if op.is_arithmetic() {
+                self.check_constant_overflow(op, left, right, span)?;
+            }
+            if *op == BinaryOperator::Modulo && (self.is_float(left) || self.is_float(right)) {
+                return Err(SemanticError::new("'%' takes int operands, but was given a float".to_string(), span));
+            }
+            if matches!(op, BinaryOperator::And | BinaryOperator::Or) {
+                for operand in [left, right] {
+                    if let Some(data_type) = self.expression_type(operand).filter(|t| !matches!(t, Type::Bool | Type::Any)) {
+                        return Err(SemanticError::new(
+                            format!("'{}' takes bool operands, but was given a '{}'", op, data_type.source_name()),
+                            span,
+                        ));
+                    }
+                }
+            }
+        }
+        if let Expression::BinaryOp { op: BinaryOperator::Add, left, right } = expr {
+            if !self.auto_stringify {
+                self.check_concatenation(left, right, span)?;
+            }
+        }
+        expr.children().into_iter().try_for_each(|child| self.check_expression(child, span))
+    }
+
+    /// Warns about `==` and `!=` with a `float` on either side: rounding can leave two results that should
+    /// be equal slightly apart, so they're better compared with a tolerance.
+    fn check_float_equality(&mut self, expr: &Expression, span: Span) {
+        if let Expression::BinaryOp { op: op @ (BinaryOperator::Equal | BinaryOperator::NotEqual), left, right } = expr {
+            if self.is_float(left) || self.is_float(right) {
+                self.warnings.push(Warning::new(
+                    FLOAT_EQUALITY,
+                    format!("Comparing floats with '{}' is unreliable, since rounding can make values that should be equal differ slightly; use 'approx_eq(a, b, eps)' to compare within a tolerance", op),
+                    span,
+                ));
+            }
+        }
+        for child in expr.children() {
+            self.check_float_equality(child, span);
+        }
+    }
+
+    /// Whether an expression is known to be a `float`. Arithmetic is a `float` if either side is, and the
+    /// math builtins whose results are usually fractional count too.
+    fn is_float(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::BinaryOp { op, left, right } if op.is_arithmetic() => self.is_float(left) || self.is_float(right),
+            Expression::Call { callee, .. } => matches!(&**callee, Expression::Identifier(name)
+                if !self.functions.contains(name)
+                    && matches!(name.as_str(), "sqrt" | "pow" | "sin" | "cos" | "tan" | "exp" | "log" | "to_float" | "random_float" | "time")),
+            _ => self.expression_type(expr) == Some(Type::Float),
+        }
+    }
+
+    /// Arithmetic on `int` constants is worked out while compiling, so a result that doesn't fit in an
+    /// `int` is an error here, rather than wrapping around (or worse) when the C++ runs.
+    fn check_constant_overflow(&self, op: &BinaryOperator, left: &Expression, right: &Expression, span: Span) -> Result<(), SemanticError> {
+        let (Some(Literal::Int(a)), Some(Literal::Int(b))) = (self.evaluate_constant(left), self.evaluate_constant(right)) else {
+            return Ok(());
+        };
+        let result = match op {
+            BinaryOperator::Add => a.checked_add(b),
+            BinaryOperator::Subtract => a.checked_sub(b),
+            BinaryOperator::Multiply => a.checked_mul(b),
+            BinaryOperator::Divide if b != 0 => a.checked_div(b),
+            BinaryOperator::Modulo if b != 0 => a.checked_rem(b),
+            _ => return Ok(()),
+        };
+        if result.is_some_and(|n| i32::try_from(n).is_ok()) {
+            return Ok(());
+        }
+        Err(SemanticError::new(format!("{} {} {} overflows an int (which holds {} to {})", a, op, b, i32::MIN, i32::MAX), span))
+    }
+
+    /// The condition of an `if` or `while` has to be a `bool`, when its type is known here. An `unless`
+    /// is an `if` with a `!` in front, so whatever is under any `!`s is what gets checked.
+    fn check_condition(&self, condition: &Expression, span: Span) -> Result<(), SemanticError> {
+        let mut inner = condition;
+        while let Expression::Unary { op: UnaryOperator::Not, operand } = inner {
+            inner = operand;
+        }
+        match self.expression_type(inner) {
+            Some(Type::Bool | Type::Any) | None => Ok(()),
+            Some(data_type) => Err(SemanticError::new(
+                format!("A condition has to be a bool, but this one is of type '{}'", data_type.source_name()),
+                span,
+            )),
+        }
+    }
+
+    /// Each pattern of a `match` is compared with the subject, so it needs the subject's type, and the
+    /// value of whichever arm is picked becomes the match's value, so every arm has to give the same type.
+    fn check_match(&self, subject: &Expression, arms: &[(Expression, Expression)], default: &Expression, span: Span) -> Result<(), SemanticError> {
+        if let Some(subject_type) = self.expression_type(subject) {
+            for (pattern, _) in arms {
+                match self.expression_type(pattern) {
+                    Some(pattern_type) if pattern_type != subject_type => return Err(SemanticError::new(
+                        format!("Can't match a value of type '{}' against a pattern of type '{}'", subject_type.source_name(), pattern_type.source_name()),
+                        span,
+                    )),
+                    _ => {}
+                }
+            }
+        }
+        let mut types = arms.iter().map(|(_, value)| value).chain([default]).filter_map(|value| self.expression_type(value));
+        if let Some(first) = types.next() {
+            if let Some(other) = types.find(|data_type| *data_type != first) {
+                return Err(SemanticError::new(
+                    format!("The arms of this match give different types, '{}' and '{}'; every arm has to give the same type", first.source_name(), other.source_name()),
+                    span,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// `x in xs` looks for `x` in a list of its type, or for a string or char in a string.
+    fn check_membership(&self, left: &Expression, right: &Expression, span: Span) -> Result<(), SemanticError> {
+        let Some(container) = self.expression_type(right) else { return Ok(()) };
+        let fits = match (&container, self.expression_type(left)) {
+            (_, None) => matches!(container, Type::List(_) | Type::String),
+            (Type::List(element), Some(value)) => **element == value,
+            (Type::String, Some(value)) => matches!(value, Type::String | Type::Char),
+            _ => false,
+        };
+        if fits {
+            return Ok(());
+        }
+        let message = match self.expression_type(left) {
+            Some(value) => format!(
+                "Can't use 'in' to look for type '{}' in type '{}'; it needs a list of that type, or a string to find a string or char in",
+                value.source_name(), container.source_name(),
+            ),
+            None => format!("Can't use 'in' to look in type '{}'; it needs a list, or a string to find a string or char in", container.source_name()),
+        };
+        Err(SemanticError::new(message, span))
+    }
+
+    /// `+` only joins strings to strings, unless `--auto-stringify` converts the other side.
+    fn check_concatenation(&self, left: &Expression, right: &Expression, span: Span) -> Result<(), SemanticError> {
+        match (self.expression_type(left), self.expression_type(right)) {
+            (Some(Type::String), Some(other)) | (Some(other), Some(Type::String)) if other.is_scalar() => Err(SemanticError::new(
+                format!("Can't add a string and a value of type '{}'; convert it with to_string() first, or compile with --auto-stringify", other.source_name()),
+                span,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// `to_string` converts a single `int`, `float`, `bool`, `char`, `string`, `any`, or enum value.
+    fn check_to_string(&self, args: &[Expression], span: Span) -> Result<(), SemanticError> {
+        let [arg] = args else {
+            return Err(SemanticError::new(format!("to_string takes 1 argument but {} were given", args.len()), span));
+        };
+        match self.expression_type(arg) {
+            Some(data_type @ (Type::List(_) | Type::Dict(..) | Type::Class(_) | Type::Void)) => Err(SemanticError::new(
+                format!("to_string can't convert a {}; it takes an int, float, bool, char, string, any, or enum value", data_type.source_name()),
+                span,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Works out a `static_assert` condition while compiling, and fails if it's false.
+    fn check_static_assert(&self, condition: &Expression, message: Option<&str>, span: Span) -> Result<(), SemanticError> {
+        match self.evaluate_constant(condition) {
+            Some(Literal::Bool(true)) => Ok(()),
+            Some(Literal::Bool(false)) => Err(SemanticError::new(
+                match message {
+                    Some(message) => format!("Static assertion failed: {}", message),
+                    None => "Static assertion failed".to_string(),
+                },
+                span,
+            )),
+            Some(value) => Err(SemanticError::new(
+                format!("static_assert needs a bool condition, but this one is of type '{}'", value.data_type().map_or("none".to_string(), |t| t.source_name())),
+                span,
+            )),
+            None => Err(SemanticError::new(
+                "static_assert needs a condition that can be worked out while compiling: only literals, 'val' constants, and operators on them".to_string(),
+                span,
+            )),
+        }
+    }
+
+    /// The value of an expression, if it's a constant. See `optimizer::evaluate_constant`.
+    fn evaluate_constant(&self, expr: &Expression) -> Option<Literal> {
+        evaluate_constant(expr, &|name| match name.split_once('.') {
+            Some((enum_name, variant)) if self.lookup(enum_name).is_none() => {
+                self.enums.get(enum_name)?.get(variant).map(|value| Literal::Int(*value))
+            }
+            Some(_) => None,
+            None => self.lookup(name).and_then(|binding| binding.constant.clone()),
+        })
+    }
+
+    /// `parse_int` and `parse_float` parse a single `string`.
+    fn check_parse(&self, name: &str, args: &[Expression], span: Span) -> Result<(), SemanticError> {
+        let [arg] = args else {
+            return Err(SemanticError::new(format!("{} takes 1 argument but {} were given", name, args.len()), span));
+        };
+        match self.expression_type(arg) {
+            Some(Type::String | Type::Any) | None => Ok(()),
+            Some(data_type) => Err(SemanticError::new(
+                format!("{} takes a string, but was given a value of type '{}'", name, data_type.source_name()),
+                span,
+            )),
+        }
+    }
+
+    /// `range` takes a start, an end, and optionally a step, all `int`s. A step of 0 would never reach the end.
+    fn check_range(&self, args: &[Expression], span: Span) -> Result<(), SemanticError> {
+        if !(2..=3).contains(&args.len()) {
+            return Err(SemanticError::new(format!("range takes 2 or 3 arguments but {} were given", args.len()), span));
+        }
+        if let Some(data_type) = args.iter().filter_map(|arg| self.expression_type(arg)).find(|data_type| *data_type != Type::Int) {
+            return Err(SemanticError::new(format!("range takes int bounds, but was given a value of type '{}'", data_type.source_name()), span));
+        }
+        if let Some(Expression::Literal(Literal::Int(0))) = args.get(2) {
+            return Err(SemanticError::new("range's step can't be 0, since it would never reach the end".to_string(), span));
+        }
+        Ok(())
+    }
+
+    /// The type of an expression, when it's evident from the expression itself: literals, names with a
+    /// declared type, string concatenations, and calls to conversion builtins like `to_string`. `None`
+    /// means it isn't known here, not that it's wrong.
+    fn expression_type(&self, expr: &Expression) -> Option<Type> {
+        match expr {
+            Expression::Literal(literal) => literal.data_type(),
+            Expression::Identifier(name) => self.lookup(name).and_then(|binding| binding.data_type.clone()),
+            Expression::Assign { target, .. } => self.expression_type(target),
+            Expression::ListLiteral(elements) => elements.iter().find_map(|element| self.expression_type(element)).map(|element| Type::List(Box::new(element))),
+            Expression::TupleLiteral(elements) => elements.iter().map(|element| self.expression_type(element)).collect::<Option<_>>().map(Type::Tuple),
+            Expression::BinaryOp { op: BinaryOperator::In | BinaryOperator::And | BinaryOperator::Or, .. }
+            | Expression::Unary { op: UnaryOperator::Not, .. } => Some(Type::Bool),
+            Expression::Unary { op: UnaryOperator::Negate, operand } => self.expression_type(operand).filter(|t| matches!(t, Type::Int | Type::Float)),
+            Expression::Match { arms, default, .. } => arms.iter().map(|(_, value)| value).chain([&**default]).find_map(|value| self.expression_type(value)),
+            Expression::BinaryOp { op: BinaryOperator::Add, left, right }
+                if self.expression_type(left) == Some(Type::String) || self.expression_type(right) == Some(Type::String) => Some(Type::String),
+            Expression::Call { callee, .. } => match &**callee {
+                Expression::Identifier(name) if self.functions.contains(name) => None,
+                Expression::Identifier(name) if matches!(name.as_str(), "to_string" | "align_left" | "align_right") => Some(Type::String),
+                Expression::Identifier(name) if name == "parse_int" => Some(Type::Optional(Box::new(Type::Int))),
+                Expression::Identifier(name) if name == "parse_float" => Some(Type::Optional(Box::new(Type::Float))),
+                Expression::Identifier(name) if name == "approx_eq" => Some(Type::Bool),
+                Expression::Identifier(name) if name == "range" => Some(Type::List(Box::new(Type::Int))),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn check_statement(&mut self, statement: &Statement) -> Result<(), SemanticError> {
+        for (expr, span) in statement_expressions(statement) {
+            if !matches!(statement, Statement::If { .. } | Statement::While { .. }) && contains_assignment(expr) {
+                return Err(SemanticError::new("An assignment can only be used as a value in an 'if' or 'while' condition".to_string(), span));
+            }
+            self.check_expression(expr, span)?;
+            self.check_float_equality(expr, span);
+        }
+        match statement {
+            Statement::Declaration { is_mutable, name, data_type, initializer, span, .. } => {
+                if let Some(initializer) = initializer {
+                    check_literal_range(data_type, initializer, *span)?;
+                }
+                // `_` discards the value, so there's nothing to bind (and it can be "declared" again).
+                if name != DISCARD {
+                    // `val x: float = 7` isn't the int 7, so only values that already have the declared type are kept.
+                    let constant = initializer.as_ref()
+                        .filter(|_| !*is_mutable)
+                        .and_then(|initializer| self.evaluate_constant(initializer))
+                        .filter(|value| value.data_type().as_ref() == Some(data_type));
+                    self.declare(name, BindingKind::Variable, *is_mutable, Some(data_type.clone()), constant);
+                }
+                Ok(())
+            }
+            Statement::Destructure { is_mutable, names, value, span } => {
+                let element_types = match self.expression_type(value) {
+                    Some(Type::Tuple(types)) if types.len() == names.len() => types.into_iter().map(Some).collect(),
+                    Some(Type::Tuple(types)) => return Err(SemanticError::new(
+                        format!("This tuple has {} elements, but there are {} names to destructure it into", types.len(), names.len()),
+                        *span,
+                    )),
+                    Some(other) => return Err(SemanticError::new(
+                        format!("Only a tuple can be destructured, but this value is of type '{}'", other.source_name()),
+                        *span,
+                    )),
+                    None => vec![None; names.len()],
+                };
+                for (i, name) in names.iter().enumerate().filter(|(_, name)| *name != DISCARD) {
+                    if names[..i].contains(name) {
+                        return Err(SemanticError::new(format!("'{}' is named twice in this destructuring", name), *span));
+                    }
+                }
+                for (name, data_type) in names.iter().zip(element_types).filter(|(name, _)| *name != DISCARD) {
+                    self.declare(name, BindingKind::Variable, *is_mutable, data_type, None);
+                }
+                Ok(())
+            }
+            Statement::Assignment { targets, op: Some(op), value, span } => {
+                let target = &targets[0];
+                if target.is_discard() {
+                    return Err(SemanticError::new(format!("'_' has no value to update, so it can't be used with '{}='", op), *span));
+                }
+                self.check_assignment(target, value, *span)?;
+                // `x += value` computes `x + value`, so it's checked like that expression.
+                self.check_expression(&Expression::BinaryOp { op: op.clone(), left: Box::new(target.clone()), right: Box::new(value.clone()) }, *span)
+            }
+            Statement::Assignment { targets, value, span, .. } => {
+                targets.iter().try_for_each(|target| self.check_assignment(target, value, *span))
+            }
+            Statement::FunctionDefinition { params, return_type, body, .. } => self.check_function(params, return_type, body),
+            Statement::Class { members, .. } => {
+                self.fields = members.iter()
+                    .filter_map(|member| match member {
+                        ClassMember::Variable(Statement::Declaration { name, is_mutable, .. }) => Some((name.clone(), *is_mutable)),
+                        _ => None,
+                    })
+                    .collect();
+                let result = self.check_methods(members);
+                self.fields.clear();
+                result
+            }
+            Statement::Enum { name, variants, .. } => check_enum(name, variants),
+            Statement::If { condition, consequence, alternative, span, else_span } => {
+                self.check_condition(condition, *span)?;
+                self.check_constant_condition(condition, *span, *else_span);
+                self.check_scoped_block(consequence)?;
+                if let Some(alt) = alternative {
+                    self.check_scoped_block(alt)?;
+                }
+                Ok(())
+            }
+            Statement::While { label, condition, body, else_block, span } => {
+                self.check_condition(condition, *span)?;
+                self.check_loop(label, body, *span)?;
+                self.check_loop_else(else_block)
+            }
+            Statement::For { label, iterator, body, else_block, span, .. } => {
+                self.scopes.push(HashMap::new());
+                self.declare(iterator, BindingKind::Variable, true, Some(Type::Int), None);
+                let result = self.check_loop(label, body, *span);
+                self.scopes.pop();
+                result?;
+                self.check_loop_else(else_block)
+            }
+            Statement::ForEach { label, iterator, iterable, body, else_block, span } => {
+                let element_type = match self.expression_type(iterable) {
+                    Some(Type::String) => Some(Type::Char),
+                    Some(Type::List(element_type)) => Some(*element_type),
+                    Some(other) => return Err(SemanticError::new(
+                        format!("A 'for' loop can run over a list or a string, not a '{}'; to count, loop over a range like '0..n'", other.source_name()),
+                        *span,
+                    )),
+                    None => None,
+                };
+                // The loop variable only reads each element, so it can't be assigned to.
+                self.scopes.push(HashMap::new());
+                self.declare(iterator, BindingKind::Variable, false, element_type, None);
+                let result = self.check_loop(label, body, *span);
+                self.scopes.pop();
+                result?;
+                self.check_loop_else(else_block)
+            }
+            Statement::TryCatch { try_block, catch_var, catch_block } => {
+                self.check_scoped_block(try_block)?;
+                self.scopes.push(HashMap::new());
+                self.declare(catch_var, BindingKind::Variable, false, None, None);
+                let result = self.check_block(catch_block);
+                self.scopes.pop();
+                result
+            }
+            Statement::StaticAssert { condition, message, span } => self.check_static_assert(condition, message.as_deref(), *span),
+            Statement::Break { label, span } => self.check_loop_control("break", label, *span),
+            Statement::Continue { label, span } => self.check_loop_control("continue", label, *span),
+            // Only a call (or a `new`, for what its constructor does) has an effect when its value is thrown away.
+            Statement::Expression { expr, span } if !matches!(expr, Expression::Call { .. } | Expression::New { .. }) => Err(SemanticError::new(
+                format!("The value of '{}' isn't used; only a call can stand on its own as a statement (assign it to '_' to discard it on purpose)", expr.to_source()),
+                *span,
+            )),
+            // The top-level code's `return` value is the program's exit code.
+            Statement::Return { value, span } if self.return_type.is_none() => match value.as_ref().map(|value| self.expression_type(value)) {
+                _ if !self.features.top_level_return => Err(SemanticError::new(unavailable("A 'return' outside of a function"), *span)),
+                Some(Some(value_type)) if value_type != Type::Int => Err(SemanticError::new(
+                    format!("A top-level 'return' gives the program's exit code, which has to be an 'int', not a '{}'", value_type.source_name()),
+                    *span,
+                )),
+                _ => Ok(()),
+            },
+            Statement::Return { value, span } => self.check_return_value(value.as_ref(), *span),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every `return` in a function has to match its declared return type: a `void` function's give no
+    /// value, and the others give a value of that type, or one that's promoted to it, like an `int`
+    /// returned from a function declared `-> float`. A value whose type isn't known here is left to the
+    /// C++ compiler.
+    fn check_return_value(&self, value: Option<&Expression>, span: Span) -> Result<(), SemanticError> {
+        let Some(declared) = &self.return_type else { return Ok(()) };
+        let message = match value {
+            Some(_) if *declared == Type::Void => "This 'return' gives a value, but the function is declared void".to_string(),
+            None if *declared == Type::Void => return Ok(()),
+            None => format!("This 'return' gives nothing, but the function is declared to return '{}'", declared.source_name()),
+            Some(value) => match self.expression_type(value) {
+                Some(actual) if !promotes_to(&actual, declared) => format!(
+                    "This function is declared to return '{}', but this 'return' gives a '{}'",
+                    declared.source_name(),
+                    actual.source_name(),
+                ),
+                _ => return Ok(()),
+            },
+        };
+        Err(SemanticError::new(message, span))
+    }
+}
+
+/// Whether a value of type `actual` can be used where `declared` is expected: it's the same type, an `int`
+/// where a `float` is expected (and so for the elements of a list or tuple), or fits an optional or `any`.
+fn promotes_to(actual: &Type, declared: &Type) -> bool {
+    match (actual, declared) {
+        _ if actual == declared => true,
+        (_, Type::Any) | (Type::Int, Type::Float) => true,
+        (Type::List(actual), Type::List(declared)) => promotes_to(actual, declared),
+        (Type::Tuple(actual), Type::Tuple(declared)) => {
+            actual.len() == declared.len() && actual.iter().zip(declared).all(|(actual, declared)| promotes_to(actual, declared))
+        }
+        (_, Type::Optional(inner)) => promotes_to(actual, inner),
+        _ => false,
+    }
+}
+
+/// The expressions a statement evaluates itself (not those in nested blocks), with the span to report them at.
+fn statement_expressions(statement: &Statement) -> Vec<(&Expression, Span)> {
+    match statement {
+        Statement::Declaration { initializer, span, .. } => initializer.iter().map(|expr| (expr, *span)).collect(),
+        Statement::Destructure { value, span, .. } => vec![(value, *span)],
+        Statement::Assignment { targets, value, span, .. } => targets.iter().chain([value]).map(|expr| (expr, *span)).collect(),
+        Statement::Print { values, span, .. } => values.iter().map(|expr| (expr, *span)).collect(),
+        Statement::Expression { expr, span }
+        | Statement::Return { value: Some(expr), span }
+        | Statement::If { condition: expr, span, .. }
+        | Statement::While { condition: expr, span, .. } => vec![(expr, *span)],
+        Statement::For { start, end, span, .. } => vec![(start, *span), (end, *span)],
+        Statement::ForEach { iterable, span, .. } => vec![(iterable, *span)],
+        Statement::StaticAssert { condition, span, .. } => vec![(condition, *span)],
+        _ => vec![],
+    }
+}
+
+fn contains_assignment(expr: &Expression) -> bool {
+    matches!(expr, Expression::Assign { .. }) || expr.children().into_iter().any(contains_assignment)
+}
+
+/// The variable a chain of indexing and field access starts from, like `grid` in `grid[y][x]` or
+/// `p` in `p.items[0]`. `None` when it starts from something else, like `this` or a call.
+fn root_variable(mut expr: &Expression) -> Option<&str> {
+    loop {
+        match expr {
+            Expression::Index { list: inner, .. } | Expression::Get { object: inner, .. } => expr = inner,
+            Expression::Identifier(name) => return Some(name),
+            _ => return None,
+        }
+    }
+}
+
+/// Checks that every class named in a type is defined, suggesting the closest defined type for a misspelling.
+/// A program that imports other modules can use their classes, which can't be seen from here, so it isn't checked.
+fn check_type_names(program: &Program) -> Result<(), SemanticError> {
+    if program.statements.iter().any(|s| matches!(s, Statement::Import(_))) {
+        return Ok(());
+    }
+    let classes: Vec<&str> = program.statements.iter()
+        .filter_map(|s| match s { Statement::Class { name, .. } => Some(name.as_str()), _ => None })
+        .collect();
+    let mut annotations = Vec::new();
+    collect_annotations(&program.statements, &mut annotations);
+    for (data_type, span) in annotations {
+        let Some(name) = unknown_class(data_type, &classes) else { continue };
+        let known = program.statements.iter()
+            .filter_map(|s| match s { Statement::Class { name, .. } | Statement::Enum { name, .. } => Some(name.as_str()), _ => None })
+            .chain(BUILTIN_TYPES.iter().copied());
+        let message = match closest_name(name, known) {
+            Some(suggestion) => format!("Unknown type '{}'; did you mean '{}'?", name, suggestion),
+            None => format!("Unknown type '{}'", name),
+        };
+        return Err(SemanticError::new(message, span));
+    }
+    Ok(())
+}
+
+const BUILTIN_TYPES: &[&str] = &["int", "float", "string", "bool", "char", "list", "dict", "any", "void"];
+
+/// The types written in declarations and function signatures, with the span of the statement they're in.
+fn collect_annotations<'p>(statements: &'p [Statement], annotations: &mut Vec<(&'p Type, Span)>) {
+    for statement in statements {
+        match statement {
+            Statement::Declaration { data_type, span, .. } => annotations.push((data_type, *span)),
+            Statement::FunctionDefinition { params, return_type, body, span, .. } => {
+                annotations.extend(params.iter().map(|p| (&p.data_type, *span)));
+                annotations.push((return_type, *span));
+                collect_annotations(body, annotations);
+            }
+            Statement::Class { members, .. } => {
+                for member in members {
+                    let (ClassMember::Variable(member) | ClassMember::Method(member) | ClassMember::Constructor(member)) = member;
+                    collect_annotations(std::slice::from_ref(member), annotations);
+                }
+            }
+            Statement::If { consequence, alternative, .. } => {
+                collect_annotations(consequence, annotations);
+                collect_annotations(alternative.as_deref().unwrap_or_default(), annotations);
+            }
+            Statement::While { body, else_block, .. } | Statement::For { body, else_block, .. } | Statement::ForEach { body, else_block, .. } => {
+                collect_annotations(body, annotations);
+                collect_annotations(else_block.as_deref().unwrap_or_default(), annotations);
+            }
+            Statement::TryCatch { try_block, catch_block, .. } => {
+                collect_annotations(try_block, annotations);
+                collect_annotations(catch_block, annotations);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The first class named in `data_type` (or in the types it's built from) that isn't one of `classes`.
+fn unknown_class<'t>(data_type: &'t Type, classes: &[&str]) -> Option<&'t str> {
+    match data_type {
+        Type::Class(name) if !classes.contains(&name.as_str()) => Some(name),
+        Type::List(inner) | Type::Optional(inner) => unknown_class(inner, classes),
+        Type::Dict(key, value) => unknown_class(key, classes).or_else(|| unknown_class(value, classes)),
+        Type::Tuple(elements) => elements.iter().find_map(|element| unknown_class(element, classes)),
+        _ => None,
+    }
+}
+
+/// The candidate closest to `name` by edit distance, if any is close enough to be a likely misspelling:
+/// within a third of the name's length, and at least one edit.
+fn closest_name<'c>(name: &str, candidates: impl Iterator<Item = &'c str>) -> Option<&'c str> {
+    let limit = (name.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The edit distance between two strings: the fewest single-character insertions, deletions,
+/// substitutions, and swaps of two neighbouring characters that turn one into the other. A swap counts
+/// as one edit, since `flaot` for `float` is as likely a typo as any single wrong letter.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut before_previous: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 0..a.len() {
+        let mut current = vec![i + 1];
+        for j in 0..b.len() {
+            let substitution = previous[j] + usize::from(a[i] != b[j]);
+            let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                distance = distance.min(before_previous[j - 1] + 1);
+            }
+            current.push(distance);
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
+/// Checks that a function's `return`s agree on whether they give a value. A function that does both
+/// has no single return type to generate.
+fn check_returns(body: &[Statement]) -> Result<(), SemanticError> {
+    let mut returns = Vec::new();
+    collect_returns(body, &mut returns);
+    let Some(&(first_span, first_has_value)) = returns.first() else { return Ok(()) };
+    match returns.iter().find(|(_, has_value)| *has_value != first_has_value) {
+        Some(&(span, has_value)) => {
+            let (valued, bare) = if has_value { (span, first_span) } else { (first_span, span) };
+            Err(SemanticError::new(
+                format!(
+                    "This function returns a value at line {} but returns nothing at line {}; either every 'return' gives a value or none does",
+                    valued.line, bare.line,
+                ),
+                span,
+            ))
+        }
+        None => Ok(()),
+    }
+}
+
+/// The `return`s in a function body, in source order, and whether each gives a value.
+fn collect_returns(statements: &[Statement], returns: &mut Vec<(Span, bool)>) {
+    for statement in statements {
+        match statement {
+            Statement::Return { value, span } => returns.push((*span, value.is_some())),
+            Statement::If { consequence, alternative, .. } => {
+                collect_returns(consequence, returns);
+                collect_returns(alternative.as_deref().unwrap_or_default(), returns);
+            }
+            Statement::While { body, else_block, .. } | Statement::For { body, else_block, .. } | Statement::ForEach { body, else_block, .. } => {
+                collect_returns(body, returns);
+                collect_returns(else_block.as_deref().unwrap_or_default(), returns);
+            }
+            Statement::TryCatch { try_block, catch_block, .. } => {
+                collect_returns(try_block, returns);
+                collect_returns(catch_block, returns);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Checks that an enum's variants have distinct names and distinct values that fit in an `int`.
+fn check_enum(name: &str, variants: &[EnumVariant]) -> Result<(), SemanticError> {
+    for (i, variant) in variants.iter().enumerate() {
+        if i32::try_from(variant.value).is_err() {
+            return Err(SemanticError::new(
+                format!("Value {} of enum variant '{}' is out of range for int (which holds {} to {})", variant.value, variant.name, i32::MIN, i32::MAX),
+                variant.span,
+            ));
+        }
+        for earlier in &variants[..i] {
+            if earlier.name == variant.name {
+                return Err(SemanticError::new(format!("Enum '{}' already has a variant named '{}'", name, variant.name), variant.span));
+            }
+            if earlier.value == variant.value {
+                return Err(SemanticError::new(
+                    format!("Enum variants '{}' and '{}' of '{}' both have the value {}", earlier.name, variant.name, name, variant.value),
+                    variant.span,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a literal that can't be stored in the type it's assigned to: `none` outside an optional,
+/// or an integer too big for an `int` (a C++ `int`, so it holds 32 bits).
+fn check_literal_range(data_type: &Type, value: &Expression, span: Span) -> Result<(), SemanticError> {
+    if let Expression::Literal(Literal::None) = value {
+        if !matches!(data_type, Type::Optional(_)) {
+            return Err(SemanticError::new(
+                format!("Can't store none in a '{0}'; only an optional type like '{0}?' can hold it", data_type.source_name()),
+                span,
+            ));
+        }
+    }
+    let data_type = match data_type {
+        Type::Optional(inner) => inner,
+        _ => data_type,
+    };
+    if let (Type::Int, Expression::Literal(Literal::Int(n))) = (data_type, value) {
+        if i32::try_from(*n).is_err() {
+            return Err(SemanticError::new(
+                format!("literal {} out of range for int (which holds {} to {})", n, i32::MIN, i32::MAX),
+                span,
+            ));
+        }
+    }
+    Ok(())
+}
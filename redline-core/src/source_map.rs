@@ -0,0 +1,83 @@
+//! Conversions between byte offsets into a source file and the line/column positions the compiler
+//! reports, for editor integrations that work in offsets.
+
+/// What a column counts. Editors and tools disagree: the Language Server Protocol counts UTF-16 code
+/// units, and plenty of tools count bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColumnMode {
+    /// Unicode scalar values, i.e. Rust `char`s (`utf8-scalar`, the default).
+    #[default]
+    Utf8Scalar,
+    /// UTF-16 code units (`utf16`), so a character outside the Basic Multilingual Plane, like most emoji, takes two.
+    Utf16,
+    /// UTF-8 bytes (`byte`), so anything that isn't ASCII takes two to four.
+    Byte,
+}
+
+impl ColumnMode {
+    /// How many columns a character takes up.
+    pub fn width(self, c: char) -> usize {
+        match self {
+            ColumnMode::Utf8Scalar => 1,
+            ColumnMode::Utf16 => c.len_utf16(),
+            ColumnMode::Byte => c.len_utf8(),
+        }
+    }
+
+    /// How many characters of `line` come before `column`, e.g. to place a caret under it.
+    pub fn chars_before(self, line: &str, column: usize) -> usize {
+        let mut at = 1;
+        line.chars().take_while(|c| {
+            at += self.width(*c);
+            at <= column
+        }).count()
+    }
+}
+
+/// A source file with the start of each of its lines, so positions can be converted without rescanning it.
+/// Lines and columns start at 1, and columns count characters, the way the lexer counts them by default.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    source: String,
+    /// The byte offset where each line starts. The first line always starts at 0.
+    line_starts: Vec<usize>,
+}
+
+impl SourceMap {
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { source: source.to_string(), line_starts }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The `(line, column)` of a byte offset. The end of the source is a valid offset; anything past it,
+    /// or in the middle of a multi-byte character, gives `None`.
+    pub fn position(&self, offset: usize) -> Option<(usize, usize)> {
+        if !self.source.is_char_boundary(offset) {
+            return None;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let column = self.source[self.line_starts[line - 1]..offset].chars().count() + 1;
+        Some((line, column))
+    }
+
+    /// The byte offset of a `(line, column)`. The column just past the end of a line is valid (it's where
+    /// the newline is); anything further, or a line that doesn't exist, gives `None`.
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let text = self.source[start..].split('\n').next().unwrap_or_default();
+        let within = match column.checked_sub(1)? {
+            0 => 0,
+            n => {
+                let (i, c) = text.char_indices().nth(n - 1)?;
+                i + c.len_utf8()
+            }
+        };
+        Some(start + within)
+    }
+}
@@ -0,0 +1,254 @@
+//! Turns an AST back into REDLINE source (`Program::to_source`). Unlike `--format`, which reprints the
+//! tokens, this prints the tree, so ordinary comments are gone and sugar like f-strings comes out in its
+//! desugared form, but parsing the output gives back the same program.
+use crate::ast::{BinaryOperator, ClassMember, Expression, Literal, Param, Program, Statement, Stream, Type, UnaryOperator};
+use crate::formatter::quote;
+use crate::lexer::is_keyword;
+
+/// How tightly a `!` binds its operand. Only postfix operators (calls, indexing, `.`) bind tighter.
+const UNARY: u8 = 6;
+/// How tightly calls, indexing, and member access bind, along with everything that can't be split up.
+const POSTFIX: u8 = 7;
+
+impl Program {
+    /// The program as REDLINE source, one statement per line and blocks indented by four spaces.
+    pub fn to_source(&self) -> String {
+        let mut out = String::new();
+        write_block(&self.statements, 0, &mut out);
+        out
+    }
+}
+
+impl Expression {
+    /// The expression as REDLINE source, with parentheses only where precedence needs them.
+    pub fn to_source(&self) -> String {
+        match self {
+            Expression::Literal(literal) => literal_source(literal),
+            Expression::ListLiteral(elements) => format!("[{}]", list(elements)),
+            Expression::TupleLiteral(elements) => format!("({})", list(elements)),
+            Expression::DictLiteral(entries) => {
+                let entries: Vec<String> = entries.iter().map(|(key, value)| format!("{}: {}", key.to_source(), value.to_source())).collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Expression::Identifier(name) => identifier(name),
+            Expression::BinaryOp { op, left, right } => {
+                // Operators group left to right, so a right operand of the same precedence needs parentheses.
+                let precedence = precedence(op);
+                format!("{} {} {}", operand(left, precedence), op, operand(right, precedence + 1))
+            }
+            Expression::Unary { op, operand: inner } => {
+                let inner = operand(inner, UNARY);
+                // `- -x` and `- -1` keep a space, so they don't read as `--`.
+                let gap = if *op == UnaryOperator::Negate && inner.starts_with('-') { " " } else { "" };
+                format!("{}{}{}", op, gap, inner)
+            }
+            Expression::Call { callee, args } => format!("{}({})", operand(callee, POSTFIX), list(args)),
+            Expression::Index { list, index } => format!("{}[{}]", operand(list, POSTFIX), index.to_source()),
+            Expression::Slice { target, start, end } => {
+                let bound = |bound: &Option<Box<Expression>>| bound.as_ref().map_or(String::new(), |b| b.to_source());
+                format!("{}[{}..{}]", operand(target, POSTFIX), bound(start), bound(end))
+            }
+            Expression::Get { object, name } => format!("{}.{}", operand(object, POSTFIX), identifier(name)),
+            Expression::This => "this".to_string(),
+            Expression::New { class_name, args } => format!("new {}({})", identifier(class_name), list(args)),
+            Expression::Assign { target, value } => format!("({} = {})", target.to_source(), value.to_source()),
+            Expression::Match { subject, arms, default } => {
+                let arms: Vec<String> = arms.iter()
+                    .map(|(pattern, value)| format!("{} => {}", pattern.to_source(), value.to_source()))
+                    .chain([format!("_ => {}", default.to_source())])
+                    .collect();
+                format!("match {}: {}", subject.to_source(), arms.join(", "))
+            }
+        }
+    }
+
+    /// How tightly the expression holds together: the precedence of its operator, `POSTFIX` for one that
+    /// can't be split up, and 0 for a `match`, whose last arm would take in anything written after it.
+    fn binding(&self) -> u8 {
+        match self {
+            Expression::BinaryOp { op, .. } => precedence(op),
+            Expression::Match { .. } => 0,
+            // A number can start with `-`, and `1.x` would lex as a float, so numbers are kept apart from a `.`.
+            Expression::Unary { .. } | Expression::Literal(Literal::Int(_) | Literal::Float(..)) => UNARY,
+            _ => POSTFIX,
+        }
+    }
+}
+
+/// Matches `Parser::get_precedence`.
+fn precedence(op: &BinaryOperator) -> u8 {
+    match op {
+        BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 5,
+        BinaryOperator::Add | BinaryOperator::Subtract => 4,
+        BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::GreaterThan | BinaryOperator::LessThan
+        | BinaryOperator::GreaterThanEqual | BinaryOperator::LessThanEqual | BinaryOperator::In => 3,
+        BinaryOperator::And => 2,
+        BinaryOperator::Or => 1,
+    }
+}
+
+/// An operand, parenthesized if it binds more loosely than `binding`.
+fn operand(expr: &Expression, binding: u8) -> String {
+    if expr.binding() < binding {
+        format!("({})", expr.to_source())
+    } else {
+        expr.to_source()
+    }
+}
+
+fn list(elements: &[Expression]) -> String {
+    elements.iter().map(Expression::to_source).collect::<Vec<_>>().join(", ")
+}
+
+fn literal_source(literal: &Literal) -> String {
+    match literal {
+        Literal::Int(n) => n.to_string(),
+        // `{:?}` writes a whole number with a `.0`, so it reads back as a float.
+        Literal::Float(n, text) => text.clone().unwrap_or_else(|| format!("{:?}", n)),
+        Literal::String(s) => quote(s, '"'),
+        Literal::Bool(b) => b.to_string(),
+        Literal::Char(c) => quote(&c.to_string(), '\''),
+        Literal::None => "none".to_string(),
+    }
+}
+
+/// A name, written as a raw identifier if it's spelled like a keyword.
+fn identifier(name: &str) -> String {
+    if is_keyword(name) { format!("r#{}", name) } else { name.to_string() }
+}
+
+fn visibility(is_public: bool) -> &'static str {
+    if is_public { "pub " } else { "" }
+}
+
+fn loop_label(label: &Option<String>) -> String {
+    label.as_ref().map_or(String::new(), |label| format!("{}: ", identifier(label)))
+}
+
+fn param(param: &Param) -> String {
+    let var = if param.is_mutable { "var " } else { "" };
+    let ellipsis = if param.is_variadic { "..." } else { "" };
+    format!("{}{}: {}{}", var, identifier(&param.name), param.data_type.source_name(), ellipsis)
+}
+
+fn write_block(statements: &[Statement], depth: usize, out: &mut String) {
+    for statement in statements {
+        write_statement(statement, depth, out);
+    }
+}
+
+/// Writes `header` on a line of its own, followed by `body` indented one level deeper.
+fn write_compound(header: String, body: &[Statement], depth: usize, out: &mut String) {
+    write_line(header, depth, out);
+    write_block(body, depth + 1, out);
+}
+
+fn write_line(text: String, depth: usize, out: &mut String) {
+    out.push_str(&"    ".repeat(depth));
+    out.push_str(&text);
+    out.push('\n');
+}
+
+fn write_statement(statement: &Statement, depth: usize, out: &mut String) {
+    match statement {
+        Statement::Import(path) => write_line(format!("import {}", quote(path, '"')), depth, out),
+        Statement::Declaration { is_public, is_mutable, name, data_type, initializer, .. } => {
+            let keyword = if *is_mutable { "var" } else { "val" };
+            let mut text = format!("{}{} {}: {}", visibility(*is_public), keyword, identifier(name), data_type.source_name());
+            if let Some(initializer) = initializer {
+                text.push_str(&format!(" = {}", initializer.to_source()));
+            }
+            write_line(text, depth, out);
+        }
+        Statement::Destructure { is_mutable, names, value, .. } => {
+            let keyword = if *is_mutable { "var" } else { "val" };
+            let names: Vec<String> = names.iter().map(|name| identifier(name)).collect();
+            write_line(format!("{} ({}) = {}", keyword, names.join(", "), value.to_source()), depth, out);
+        }
+        Statement::Assignment { targets, op, value, .. } => {
+            let sides: Vec<String> = targets.iter().chain([value]).map(Expression::to_source).collect();
+            let assign = op.as_ref().map_or("=".to_string(), |op| format!("{}=", op));
+            write_line(sides.join(&format!(" {} ", assign)), depth, out);
+        }
+        Statement::If { condition, consequence, alternative, .. } => {
+            write_compound(format!("if {}:", condition.to_source()), consequence, depth, out);
+            let mut alternative = alternative;
+            // An `else` holding just another `if` is written as an `elif`.
+            while let Some([Statement::If { condition, consequence, alternative: next, .. }]) = alternative.as_deref() {
+                write_compound(format!("elif {}:", condition.to_source()), consequence, depth, out);
+                alternative = next;
+            }
+            if let Some(alternative) = alternative {
+                write_compound("else:".to_string(), alternative, depth, out);
+            }
+        }
+        Statement::While { label, condition, body, else_block, .. } => {
+            write_compound(format!("{}while {}:", loop_label(label), condition.to_source()), body, depth, out);
+            if let Some(else_block) = else_block {
+                write_compound("else:".to_string(), else_block, depth, out);
+            }
+        }
+        Statement::For { label, iterator, start, end, body, else_block, .. } => {
+            let header = format!("{}for {} in {}..{}:", loop_label(label), identifier(iterator), start.to_source(), end.to_source());
+            write_compound(header, body, depth, out);
+            if let Some(else_block) = else_block {
+                write_compound("else:".to_string(), else_block, depth, out);
+            }
+        }
+        Statement::ForEach { label, iterator, iterable, body, else_block, .. } => {
+            let header = format!("{}for {} in {}:", loop_label(label), identifier(iterator), iterable.to_source());
+            write_compound(header, body, depth, out);
+            if let Some(else_block) = else_block {
+                write_compound("else:".to_string(), else_block, depth, out);
+            }
+        }
+        Statement::Print { values, sep, stream, .. } => {
+            let keyword = if *stream == Stream::Stdout { "print" } else { "eprint" };
+            let mut arguments: Vec<String> = values.iter().map(Expression::to_source).collect();
+            arguments.extend(sep.iter().map(|sep| format!("sep: {}", quote(sep, '"'))));
+            write_line(format!("{}({})", keyword, arguments.join(", ")), depth, out);
+        }
+        Statement::Expression { expr, .. } => write_line(expr.to_source(), depth, out),
+        Statement::FunctionDefinition { is_public, name, params, return_type, body, doc, .. } => {
+            for line in doc.iter().flat_map(|doc| doc.lines()) {
+                write_line(if line.is_empty() { "#".to_string() } else { format!("# {}", line) }, depth, out);
+            }
+            let params: Vec<String> = params.iter().map(param).collect();
+            let arrow = if *return_type == Type::Void { String::new() } else { format!(" -> {}", return_type.source_name()) };
+            let header = format!("{}def {}({}){}:", visibility(*is_public), identifier(name), params.join(", "), arrow);
+            write_compound(header, body, depth, out);
+        }
+        Statement::Return { value, .. } => match value {
+            Some(value) => write_line(format!("return {}", value.to_source()), depth, out),
+            None => write_line("return".to_string(), depth, out),
+        },
+        Statement::Class { is_public, name, members, .. } => {
+            write_line(format!("{}class {}:", visibility(*is_public), identifier(name)), depth, out);
+            for member in members {
+                let (ClassMember::Variable(statement) | ClassMember::Method(statement) | ClassMember::Constructor(statement)) = member;
+                write_statement(statement, depth + 1, out);
+            }
+        }
+        Statement::Enum { is_public, name, variants, .. } => {
+            let variants: Vec<String> = variants.iter().map(|variant| format!("{} = {}", identifier(&variant.name), variant.value)).collect();
+            write_line(format!("{}enum {}: {}", visibility(*is_public), identifier(name), variants.join(", ")), depth, out);
+        }
+        Statement::TryCatch { try_block, catch_var, catch_block } => {
+            write_compound("try:".to_string(), try_block, depth, out);
+            write_compound(format!("catch {}:", identifier(catch_var)), catch_block, depth, out);
+        }
+        Statement::Break { label, .. } => write_line(loop_control("break", label), depth, out),
+        Statement::Continue { label, .. } => write_line(loop_control("continue", label), depth, out),
+        Statement::StaticAssert { condition, message, .. } => {
+            let message = message.as_ref().map_or(String::new(), |message| format!(", {}", quote(message, '"')));
+            write_line(format!("static_assert {}{}", condition.to_source(), message), depth, out);
+        }
+    }
+}
+
+fn loop_control(keyword: &str, label: &Option<String>) -> String {
+    match label {
+        Some(label) => format!("{} {}", keyword, identifier(label)),
+        None => keyword.to_string(),
+    }
+}
@@ -0,0 +1,31 @@
+//! Tests for the control-flow graphs printed by `--emit cfg`.
+use redline_core::cfg::{self, Edge, ENTRY, EXIT};
+use redline_core::parse_source;
+
+#[test]
+fn if_else_branches_and_merges() {
+    let program = parse_source("def sign(n: int) -> string:\n    var s: string\n    if n < 0:\n        s = \"negative\"\n    else:\n        s = \"not negative\"\n    return s\n").unwrap();
+    let cfgs = cfg::build(&program);
+    let [sign] = cfgs.as_slice() else { panic!("expected one graph, got {:?}", cfgs) };
+    assert_eq!(sign.name, "sign");
+
+    // The block with the condition branches two ways...
+    let entry = &sign.blocks[ENTRY];
+    assert_eq!(entry.statements, ["2: var s", "3: if"]);
+    let [Edge { target: then_block, label: Some("true") }, Edge { target: else_block, label: Some("false") }] = entry.successors[..] else {
+        panic!("expected a true and a false branch, got {:?}", entry.successors)
+    };
+    assert_eq!(sign.blocks[then_block].statements, ["4: s = ..."]);
+    assert_eq!(sign.blocks[else_block].statements, ["6: s = ..."]);
+
+    // ...and both branches come back together before the `return`.
+    let [Edge { target: merge, label: None }] = sign.blocks[then_block].successors[..] else { panic!("the branch should only go on to the merge") };
+    assert_eq!(sign.blocks[else_block].successors, [Edge { target: merge, label: None }]);
+    assert_eq!(sign.predecessors(merge), [then_block, else_block]);
+    assert_eq!(sign.blocks[merge].statements, ["7: return ..."]);
+    assert_eq!(sign.blocks[merge].successors, [Edge { target: EXIT, label: None }]);
+
+    let dot = cfg::to_dot(&cfgs);
+    assert!(dot.starts_with("digraph cfg {"), "{}", dot);
+    assert!(dot.contains(&format!("\"sign:{}\" -> \"sign:{}\" [label=\"true\"];", ENTRY, then_block)), "{}", dot);
+}
@@ -0,0 +1,129 @@
+//! Tests for the `redline-core` command line, run against the built binary.
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Writes `source` to a temporary `.rl` file and runs the compiler on it with `flags`.
+fn run(name: &str, source: &str, flags: &[&str]) -> Output {
+    run_files(&[(name, source)], flags)
+}
+
+/// Writes each `(name, source)` to a temporary `<name>.rl` file and compiles them together with `flags`.
+fn run_files(files: &[(&str, &str)], flags: &[&str]) -> Output {
+    let dir: PathBuf = std::env::temp_dir().join(format!("redline_cli_{}_{}", files[0].0, std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    let paths: Vec<PathBuf> = files.iter().map(|(name, source)| {
+        let path = dir.join(format!("{}.rl", name));
+        std::fs::write(&path, source).expect("temp file should be writable");
+        path
+    }).collect();
+    let output = Command::new(env!("CARGO_BIN_EXE_redline-core"))
+        .args(&paths)
+        .args(flags)
+        .output()
+        .expect("compiler should run");
+    let _ = std::fs::remove_dir_all(&dir);
+    output
+}
+
+#[test]
+fn profile_prints_a_line_per_phase() {
+    let output = run("profile", "val x: int = 1\nprint(x)\n", &["--profile", "-O"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for phase in ["lex", "parse", "semantic", "optimize", "codegen", "total"] {
+        assert!(stderr.lines().any(|line| line.starts_with(phase)), "missing {} in:\n{}", phase, stderr);
+    }
+    // The generated code on stdout is unaffected.
+    assert!(String::from_utf8_lossy(&output.stdout).contains("int main("));
+}
+
+#[test]
+fn lint_levels_can_silence_or_deny_a_lint() {
+    let source = "var x: int = 1\nprint(x == x)\n";
+
+    let output = run("lint_warn", source, &[]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[self-comparison]"));
+
+    let output = run("lint_allow", source, &["--allow", "self-comparison"]);
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run("lint_deny", source, &["--deny", "warnings"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Error"));
+
+    let output = run("lint_unknown", source, &["--deny", "no-such-lint"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown lint 'no-such-lint'"));
+}
+
+#[test]
+fn several_files_compile_into_one_program() {
+    let shapes = "def area(width: int, height: int) -> int:\n    return width * height\n";
+    let output = run_files(&[("main", "print(area(3, 4))\n"), ("shapes", shapes)], &["--gen", "cpp"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let cpp = String::from_utf8_lossy(&output.stdout);
+    assert!(cpp.contains("int area(int width, int height)"), "{}", cpp);
+    assert!(cpp.contains("print(area(3, 4));"), "{}", cpp);
+
+    // Errors name the file they're in.
+    let output = run_files(&[("main", "print(area(3, 4))\n"), ("shapes", &format!("{}val sides: int = 4\nsides = 5\n", shapes))], &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Cannot assign to 'sides'") && stderr.contains("shapes.rl:4:1"), "{}", stderr);
+
+    let output = run_files(&[("main", "def area(width: int, height: int) -> int:\n    return 0\n"), ("shapes", shapes)], &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("'area' is already defined in") && stderr.contains("main.rl at line 1"), "{}", stderr);
+    assert!(stderr.contains("shapes.rl:1:1"), "{}", stderr);
+}
+
+#[test]
+fn conditional_blocks_follow_the_target() {
+    let source = "@if TARGET == \"c\":\n    def greet():\n        print(\"hello from c\")\n@else:\n    def greet():\n        print(\"hello\")\ngreet()\n";
+    let output = run("target_c", source, &["--target", "c"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let cpp = String::from_utf8_lossy(&output.stdout);
+    assert!(cpp.contains("print(\"hello from c\");") && !cpp.contains("print(\"hello\");"), "{}", cpp);
+
+    let output = run("target_default", source, &[]);
+    let cpp = String::from_utf8_lossy(&output.stdout);
+    assert!(cpp.contains("print(\"hello\");") && !cpp.contains("hello from c"), "{}", cpp);
+
+    let output = run("target_unknown_flag", "@if PLATFORM == \"c\":\n    print(1)\n", &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown build flag 'PLATFORM' in '@if'"));
+}
+
+#[test]
+fn column_mode_changes_the_reported_column() {
+    let source = "print(\"\u{1F600}\") $\n";
+    for (mode, location) in [("utf8-scalar", ".rl:1:12"), ("utf16", ".rl:1:13"), ("byte", ".rl:1:15")] {
+        let output = run(&format!("columns_{}", mode.replace('-', "_")), source, &["--column-mode", mode]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(location), "{}: {}", mode, stderr);
+        // The caret is placed by characters, so it's under the '$' whatever the mode.
+        assert!(stderr.contains("  |            ^"), "{}: {}", mode, stderr);
+    }
+}
+
+#[test]
+fn version_prints_the_package_version_without_a_file() {
+    assert!(!redline_core::version().is_empty());
+    assert_eq!(redline_core::version(), env!("CARGO_PKG_VERSION"));
+    for flag in ["--version", "-V"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_redline-core")).arg(flag).output().expect("compiler should run");
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), format!("redline-core {}\n", env!("CARGO_PKG_VERSION")));
+    }
+}
+
+#[test]
+fn codegen_errors_are_prefixed_once() {
+    let output = run("no_main_stray", "def f() -> int:\n    return 1\nprint(f())\n", &["--no-main-wrapper"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Codegen Error: --no-main-wrapper leaves top-level code nowhere to run, but there's some at line 3; move it into a function\n"
+    );
+}
@@ -0,0 +1,154 @@
+//! Tests for the codegen options that don't show up in the default golden files.
+mod common;
+
+use common::cpp_with;
+use redline_core::codegen::{CodegenOptions, GenMode, IntDivMode};
+use redline_core::compile;
+use std::path::Path;
+use std::process::Command;
+
+/// Compiles `source` with `options`, builds the C++ against the stdlib headers, and returns what it
+/// prints, or `None` when there's no C++ compiler to build it with.
+fn run_with(name: &str, source: &str, options: &CodegenOptions) -> Option<String> {
+    let dir = std::env::temp_dir().join(format!("redline_run_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    std::fs::write(dir.join("test.cpp"), cpp_with(source, options)).unwrap();
+    std::fs::write(dir.join("test.hpp"), compile(source, GenMode::Hpp, "test", options).expect("source should compile")).unwrap();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().expect("the crate is inside the repository");
+    let built = Command::new("c++")
+        .args(["-std=c++17", "-w", "-I"]).arg(root).arg("-I").arg(&dir)
+        .arg(dir.join("test.cpp")).arg("-o").arg(dir.join("test"))
+        .status();
+    let output = match built {
+        Ok(status) => {
+            assert!(status.success(), "the generated C++ should build");
+            Command::new(dir.join("test")).output().expect("the program should run")
+        }
+        Err(_) => {
+            eprintln!("skipping: no C++ compiler to build with");
+            let _ = std::fs::remove_dir_all(&dir);
+            return None;
+        }
+    };
+    let _ = std::fs::remove_dir_all(&dir);
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+fn constexpr_is_only_emitted_for_pure_functions() {
+    let source = "def square(x: int) -> int:\n    return x * x\n\ndef shout(x: int) -> int:\n    print(x)\n    return x\n";
+    let options = CodegenOptions { constexpr_functions: true, ..Default::default() };
+    let code = cpp_with(source, &options);
+    assert!(code.contains("constexpr int square(int x)"));
+    assert!(code.contains("\nint shout(int x)"));
+
+    let code = cpp_with(source, &CodegenOptions::default());
+    assert!(!code.contains("constexpr"));
+}
+
+#[test]
+fn constexpr_sees_block_locals_only_inside_their_block() {
+    // The `limit` returned is the global, which isn't constant, not the one declared inside the `if`.
+    let source = "var limit: int = 10\n\ndef capped(x: int) -> int:\n    if x > 0:\n        val limit: int = 1\n        return limit\n    return limit\n\nprint(capped(0))\n";
+    let options = CodegenOptions { constexpr_functions: true, ..Default::default() };
+    let code = cpp_with(source, &options);
+    assert!(code.contains("\nint capped(int x)"), "{}", code);
+    assert!(!code.contains("constexpr"), "{}", code);
+
+    let source = "def capped(x: int) -> int:\n    for i in 0..x:\n        val limit: int = i\n    return x\n";
+    assert!(cpp_with(source, &options).contains("constexpr int capped(int x)"));
+}
+
+#[test]
+fn doc_comments_are_emitted_as_doxygen() {
+    let source = "# Adds two numbers.\n#\n# Works on ints only.\ndef add(a: int, b: int) -> int:\n    return a + b\n\n# Not attached: a blank line follows.\n\ndef zero() -> int:\n    return 0\n";
+    let options = CodegenOptions { emit_docs: true, ..Default::default() };
+    let code = cpp_with(source, &options);
+    assert!(code.contains("/**\n * Adds two numbers.\n *\n * Works on ints only.\n */\nint add(int a, int b) {"), "{}", code);
+    assert!(!code.contains("Not attached"), "{}", code);
+
+    let code = cpp_with(source, &CodegenOptions::default());
+    assert!(!code.contains("/**"));
+}
+
+#[test]
+fn auto_stringify_converts_the_other_side_of_a_string_concatenation() {
+    let options = CodegenOptions { auto_stringify: true, ..Default::default() };
+    let code = cpp_with("val n: int = 5\nprint(\"x=\" + 5)\nprint(n + \" items\")\n", &options);
+    assert!(code.contains("print((\"x=\" + rl::to_string(5)));"), "{}", code);
+    assert!(code.contains("print((rl::to_string(n) + \" items\"));"), "{}", code);
+
+    assert!(compile("print(\"x=\" + 5)\n", GenMode::Cpp, "test", &CodegenOptions::default()).is_err());
+}
+
+#[test]
+fn enums_are_declared_in_the_header_with_their_values() {
+    let header = compile("enum Status: Ok, Err, Unknown = 255\n", GenMode::Hpp, "test", &CodegenOptions::default()).expect("source should compile");
+    assert!(header.contains("enum class Status {\n    Ok = 0,\n    Err = 1,\n    Unknown = 255,\n};"), "{}", header);
+    assert!(header.contains("case Status::Unknown: return \"Unknown\";"), "{}", header);
+}
+
+#[test]
+fn nodiscard_marks_functions_that_return_a_value() {
+    let source = "def twice(x: int) -> int:\n    return x * 2\n\ndef show(x: int):\n    print(x)\n";
+    let options = CodegenOptions { nodiscard: true, constexpr_functions: true, ..Default::default() };
+    let code = cpp_with(source, &options);
+    assert!(code.contains("[[nodiscard]] constexpr int twice(int x)"), "{}", code);
+    assert!(code.contains("\nvoid show(int x)"), "{}", code);
+
+    let code = cpp_with(source, &CodegenOptions::default());
+    assert!(!code.contains("[[nodiscard]]"));
+}
+
+#[test]
+fn noexcept_marks_functions_that_cant_throw() {
+    let source = "pub def mix(a: int, b: float) -> float:\n    return a * b + 1.5\n\ndef ask() -> int:\n    return unwrap(parse_int(input(\"n? \")))\n\ndef twice_asked() -> int:\n    return ask() * 2\n";
+    let options = CodegenOptions { noexcept: true, ..Default::default() };
+    let code = cpp_with(source, &options);
+    assert!(code.contains("double mix(int a, double b) noexcept {"), "{}", code);
+    assert!(code.contains("int ask() {"), "{}", code);
+    assert!(code.contains("int twice_asked() {"), "{}", code);
+    let header = compile(source, GenMode::Hpp, "test", &options).expect("source should compile");
+    assert!(header.contains("mix(int a, double b) noexcept;"), "{}", header);
+
+    let code = cpp_with(source, &CodegenOptions::default());
+    assert!(!code.contains("noexcept"));
+}
+
+#[test]
+fn no_main_wrapper_leaves_out_main() {
+    let options = CodegenOptions { no_main_wrapper: true, ..Default::default() };
+    let code = cpp_with("val limit: int = 10\n\ndef clamp(n: int) -> int:\n    return n\n", &options);
+    assert!(!code.contains("int main("), "{}", code);
+    assert!(code.contains("namespace rl {\n\nint limit = 10;\n"), "{}", code);
+
+    let err = compile("def greet():\n    print(1)\n\ngreet()\n", GenMode::Cpp, "test", &options).expect_err("top-level calls need a main");
+    assert!(err.to_string().contains("there's some at line 4"), "{}", err);
+}
+
+#[test]
+fn floored_division_floors_the_remainder_too() {
+    let source = "val a: int = -7\nval q: int = a / 2\nval r: int = a % 2\n";
+    let code = cpp_with(source, &CodegenOptions::default());
+    assert!(code.contains("(a / 2)") && code.contains("(a % 2)"), "{}", code);
+
+    let options = CodegenOptions { int_div: IntDivMode::Floor, ..Default::default() };
+    let code = cpp_with(source, &options);
+    assert!(code.contains("rl::floor_div(a, 2)") && code.contains("rl::floor_mod(a, 2)"), "{}", code);
+}
+
+#[test]
+fn minus_seven_halved_truncates_or_floors() {
+    let source = "val a: int = -7\nprint(a / 2, a % 2)\nprint(7 / -2, 7 % -2)\n";
+    let floor = CodegenOptions { int_div: IntDivMode::Floor, ..Default::default() };
+    assert!(cpp_with(source, &CodegenOptions::default()).contains("rl::to_string((a / 2))"));
+    assert!(cpp_with(source, &floor).contains("rl::to_string(rl::floor_div(a, 2))"));
+
+    // What `rl::floor_div` and `rl::floor_mod` give, next to C++'s own `/` and `%`.
+    if let Some(output) = run_with("trunc", source, &CodegenOptions::default()) {
+        assert_eq!(output, "-3 -1\n-3 1\n");
+    }
+    if let Some(output) = run_with("floor", source, &floor) {
+        assert_eq!(output, "-4 1\n-4 -1\n");
+    }
+}
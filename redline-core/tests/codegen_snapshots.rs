@@ -0,0 +1,55 @@
+//! Golden-file tests for codegen.
+//! Every `tests/fixtures/<name>.rl` is compiled to C++ and compared against `tests/fixtures/<name>.cpp`.
+//! Run with `REDLINE_UPDATE_SNAPSHOTS=1 cargo test` to (re)generate the golden files after an intended change.
+use redline_core::codegen::{CodegenOptions, GenMode};
+use redline_core::compile;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures");
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("tests/fixtures should exist")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rl"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn codegen_matches_golden_files() {
+    let update = std::env::var_os("REDLINE_UPDATE_SNAPSHOTS").is_some();
+    let mut failures = Vec::new();
+
+    for source_path in fixtures() {
+        let module_name = source_path.file_stem().unwrap().to_str().unwrap();
+        let source = fs::read_to_string(&source_path).unwrap();
+        let actual = match compile(&source, GenMode::Cpp, module_name, &CodegenOptions::default()) {
+            Ok(code) => code,
+            Err(e) => {
+                failures.push(format!("{}: failed to compile: {}", source_path.display(), e));
+                continue;
+            }
+        };
+
+        let golden_path = source_path.with_extension("cpp");
+        if update {
+            fs::write(&golden_path, &actual).unwrap();
+            continue;
+        }
+        match fs::read_to_string(&golden_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{}: output differs from golden file\n--- expected\n{}\n--- actual\n{}",
+                source_path.display(), expected, actual
+            )),
+            Err(_) => failures.push(format!(
+                "{}: missing golden file (run with REDLINE_UPDATE_SNAPSHOTS=1 to create it)",
+                golden_path.display()
+            )),
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
@@ -0,0 +1,20 @@
+//! Tests for the names of the temporaries that codegen introduces.
+mod common;
+
+use common::cpp;
+
+#[test]
+fn temporaries_in_one_function_never_collide() {
+    // Two loops with `else` blocks, one inside the other, each need a flag, and a user variable
+    // spelled like a temporary stays out of their way.
+    let source = "def search(n: int):\n    val _rl_tmp1: int = n\n    for i in 0..n:\n        for j in 0..n:\n            if i * j == _rl_tmp1:\n                break\n        else:\n            print(i)\n    else:\n        print(n)\n\ndef other():\n    while false:\n        print(1)\n    else:\n        print(2)\n";
+    let code = cpp(source);
+    let search = &code[code.find("void search").unwrap()..code.find("void other").unwrap()];
+    assert!(search.contains("bool _rl_tmp0 = false;") && search.contains("bool _rl_tmp1 = false;"), "{}", search);
+    assert!(search.contains("int rl__rl_tmp1 = n;"), "{}", search);
+    assert!(search.contains("if (((i * j) == rl__rl_tmp1))"), "{}", search);
+
+    // Numbering starts over in each function.
+    let other = &code[code.find("void other").unwrap()..];
+    assert!(other.contains("bool _rl_tmp0 = false;") && !other.contains("_rl_tmp1"), "{}", other);
+}
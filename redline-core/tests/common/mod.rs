@@ -0,0 +1,15 @@
+//! Helpers shared by the integration tests, which each pull them in with `mod common;`.
+#![allow(dead_code)] // Each test file uses only some of these.
+
+use redline_core::codegen::{CodegenOptions, GenMode};
+use redline_core::compile;
+
+/// The C++ generated for `source` with the default options.
+pub fn cpp(source: &str) -> String {
+    cpp_with(source, &CodegenOptions::default())
+}
+
+/// The C++ generated for `source` with `options`.
+pub fn cpp_with(source: &str, options: &CodegenOptions) -> String {
+    compile(source, GenMode::Cpp, "test", options).expect("source should compile")
+}
@@ -0,0 +1,73 @@
+//! Tests for compound assignments like `x += 1`.
+mod common;
+
+use common::cpp_with;
+use redline_core::ast::Statement;
+use redline_core::codegen::{CodegenOptions, IntDivMode};
+use redline_core::interpreter::{evaluate, Environment, Value};
+use redline_core::lexer::{Lexer, TokenType};
+use redline_core::{check_source, parse_source};
+
+#[test]
+fn each_operator_becomes_the_cpp_compound_assignment() {
+    let source = "var n: int = 10\nn += 5\nn -= 3\nn *= 2\nn /= 4\nn %= 3\nvar xs: list[int] = [1]\nxs[0] += n\n";
+    let code = cpp_with(source, &CodegenOptions::default());
+    for line in ["n += 5;", "n -= 3;", "n *= 2;", "n /= 4;", "n %= 3;", "xs.at(0) += n;"] {
+        assert!(code.contains(line), "missing {:?} in:\n{}", line, code);
+    }
+    // Floored division has no C++ operator, so it goes through a helper that still evaluates the target once.
+    let options = CodegenOptions { int_div: IntDivMode::Floor, ..Default::default() };
+    let code = cpp_with(source, &options);
+    assert!(code.contains("rl::floor_div_assign(n, 4);") && code.contains("rl::floor_mod_assign(n, 3);"), "{}", code);
+}
+
+#[test]
+fn slash_equals_is_one_token() {
+    let tokens = Lexer::new("n /= 2 # halve it\n".to_string()).tokenize().unwrap();
+    let types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+    assert_eq!(types[..3], [TokenType::Ident("n".to_string()), TokenType::OpAssign("/".to_string()), TokenType::Int(2)]);
+}
+
+#[test]
+fn the_target_is_checked_like_an_assignment_and_the_operation_like_an_expression() {
+    let error = |source: &str| parse_source(source).expect_err("source should be rejected").message;
+    assert_eq!(error("val n: int = 1\nn += 1\n"), "Cannot assign to 'n' because it was declared with 'val'");
+    assert_eq!(error("_ += 1\n"), "'_' has no value to update, so it can't be used with '+='");
+    assert_eq!(error("var x: float = 1.5\nx %= 2\n"), "'%' takes int operands, but was given a float");
+    assert!(parse_source("var s: string = \"a\"\ns += \"b\"\n").is_ok());
+
+    // Unlike `=`, a compound assignment reads its target first.
+    let (_, warnings) = check_source("var n: int\nn += 1\n").expect("source should compile");
+    assert!(warnings.iter().any(|w| w.message.contains("'n'")), "{:?}", warnings.iter().map(|w| &w.message).collect::<Vec<_>>());
+}
+
+#[test]
+fn boolean_compound_assignments_short_circuit() {
+    let source = "var ok: bool = true\nvar n: int = 0\nok &&= 10 / n > 1\nok ||= n == 0\n";
+    let program = parse_source(source).expect("source should compile");
+    let [.., Statement::Assignment { op: None, value: and_value, .. }, Statement::Assignment { op: None, value: or_value, .. }] = program.statements.as_slice()
+    else { panic!("expected two plain assignments, got {:?}", program.statements) };
+
+    // `ok &&= rhs` is `ok = ok && rhs`, so the division only happens while `ok` is still true.
+    let run = |value, ok: bool| {
+        let mut env = Environment::new();
+        env.set("ok", Value::Bool(ok));
+        env.set("n", Value::Int(0));
+        evaluate(value, &env)
+    };
+    assert_eq!(run(and_value, false).unwrap(), Value::Bool(false));
+    assert_eq!(run(and_value, true).unwrap_err().message, "Division by zero");
+    assert_eq!(run(or_value, true).unwrap(), Value::Bool(true));
+
+    let code = cpp_with(source, &CodegenOptions::default());
+    assert!(code.contains("ok = (ok && ((10 / n) > 1));") && code.contains("ok = (ok || (n == 0));"), "{}", code);
+}
+
+#[test]
+fn boolean_compound_assignments_need_a_bool_target() {
+    let error = |source: &str| parse_source(source).expect_err("source should be rejected").message;
+    assert_eq!(error("var n: int = 1\nn &&= true\n"), "'&&' takes bool operands, but was given a 'int'");
+    assert_eq!(error("var b: bool = false\nb ||= 3\n"), "'||' takes bool operands, but was given a 'int'");
+    assert_eq!(error("val b: bool = true\nb &&= false\n"), "Cannot assign to 'b' because it was declared with 'val'");
+    assert_eq!(error("_ ||= true\n"), "'_' has no value to update, so it can't be used with '||='");
+}
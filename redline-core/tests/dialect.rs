@@ -0,0 +1,66 @@
+//! Tests for `--dialect`, which leaves newer syntax out of what's accepted.
+use redline_core::codegen::{CodegenOptions, GenMode};
+use redline_core::compile;
+use redline_core::dialect::Dialect;
+use redline_core::lexer::{Lexer, TokenType};
+
+fn compile_as(source: &str, dialect: Dialect) -> Result<String, String> {
+    let options = CodegenOptions { features: dialect.features(), ..Default::default() };
+    compile(source, GenMode::Cpp, "test", &options).map_err(|e| e.message)
+}
+
+#[test]
+fn an_assignment_expression_needs_the_latest_dialect() {
+    let source = "var n: int = 0\nwhile (n = n + 1) < 3:\n    print(n)\n";
+    assert!(compile_as(source, Dialect::Latest).is_ok());
+    assert_eq!(
+        compile_as(source, Dialect::Conservative).unwrap_err(),
+        "Assigning inside an expression isn't part of the selected dialect; compile with '--dialect latest' to use it"
+    );
+}
+
+#[test]
+fn the_conservative_dialect_leaves_out_each_newer_feature() {
+    let sources = [
+        "def one() -> int:\n    1\nprint(one())\n",
+        "for c in \"ab\":\n    print(c)\n",
+        "return 1\n",
+        "var x: int = 1\nif x > 1:\n    print(1)\nelif x > 0:\n    print(2)\n",
+        "var x: int = 1\nif x > 1:\n    print(1)\nelse if x > 0:\n    print(2)\n",
+        "var x: int = 1\nunless x > 1:\n    print(x)\n",
+        "outer: while true:\n    break outer\n",
+        "var x: int = 1\nwhile x < 3:\n    x = x + 1\nelse:\n    print(x)\n",
+        "loop:\n    break\n",
+        "var x: int = 1\nx += 2\nprint(x)\n",
+        "var ok: bool = true\nok &&= false\nprint(ok)\n",
+        "print(0xff)\n",
+        "print(1_000)\n",
+        "var x: int = 1\nprint(match x: 1 => \"one\", _ => \"other\")\n",
+        "enum Status: Ok, Err\nprint(Status.Err + 1)\n",
+        "@if TARGET == \"c\":\n    print(1)\n",
+        "static_assert 1 < 2\n",
+        "def pair() -> (int, string):\n    return (1, \"a\")\nval (n, s) = pair()\nprint(n)\n",
+        "var p: int = 1\nval (a, b) = (p, 2)\nprint(a + b)\n",
+    ];
+    for source in sources {
+        assert!(compile_as(source, Dialect::Latest).is_ok(), "{}", source);
+        let message = compile_as(source, Dialect::Conservative).unwrap_err();
+        assert!(message.ends_with("isn't part of the selected dialect; compile with '--dialect latest' to use it"), "{}", message);
+    }
+    // The spelled-out forms are accepted either way.
+    assert!(compile_as("def one() -> int:\n    return 1\nfor i in 0..2:\n    print(one())\n", Dialect::Conservative).is_ok());
+    let source = "var x: int = 1\nwhile x < 3:\n    x = x + 1\n    if x > 2:\n        break\n    else:\n        print(x % 2)\n";
+    assert!(compile_as(source, Dialect::Conservative).is_ok());
+}
+
+#[test]
+fn braced_blocks_are_only_lexed_in_the_latest_dialect() {
+    let lex = |dialect: Dialect| {
+        let mut lexer = Lexer::new("if x: { y }".to_string());
+        lexer.disable_indentation();
+        lexer.set_features(dialect.features());
+        lexer.tokenize().unwrap().into_iter().any(|t| t.token_type == TokenType::Indent)
+    };
+    assert!(lex(Dialect::Latest));
+    assert!(!lex(Dialect::Conservative));
+}
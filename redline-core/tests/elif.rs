@@ -0,0 +1,56 @@
+//! Tests for `elif` and `else if`, which chain another `if` onto an `else`.
+mod common;
+
+use common::cpp;
+use redline_core::ast::Statement;
+use redline_core::parse_source;
+
+const GRADE: &str = "\
+def grade(n: int) -> string:
+    if n >= 90:
+        return \"A\"
+    elif n >= 80:
+        return \"B\"
+    else:
+        return \"C\"
+";
+
+#[test]
+fn elif_and_else_if_parse_to_the_same_chain() {
+    let program = parse_source(GRADE).expect("source should parse");
+    let Statement::FunctionDefinition { body, .. } = &program.statements[0] else { panic!("expected a function") };
+    let Statement::If { alternative: Some(alternative), .. } = &body[0] else { panic!("expected an if") };
+    let [Statement::If { alternative: Some(last), .. }] = alternative.as_slice() else { panic!("expected a chained if") };
+    assert!(matches!(last.as_slice(), [Statement::Return { .. }]));
+
+    // Both spellings, and an `else` holding only an `if`, print back out as the `elif` chain.
+    let nested = GRADE.replace("    elif n >= 80:\n        return \"B\"\n    else:\n        return \"C\"\n",
+        "    else:\n        if n >= 80:\n            return \"B\"\n        else:\n            return \"C\"\n");
+    assert_ne!(nested, GRADE);
+    for source in [GRADE.to_string(), GRADE.replace("elif", "else if"), nested] {
+        assert_eq!(parse_source(&source).unwrap().to_source(), GRADE);
+    }
+}
+
+#[test]
+fn a_chain_is_generated_without_nesting() {
+    let code = cpp(GRADE);
+    let expected = "\
+    if ((n >= 90)) {
+        return \"A\";
+    }
+    else if ((n >= 80)) {
+        return \"B\";
+    }
+    else {
+        return \"C\";
+    }
+";
+    assert!(code.contains(expected), "{}", code);
+}
+
+#[test]
+fn elif_needs_an_if_before_it() {
+    let message = parse_source("elif true:\n    print(1)\n").expect_err("source should be rejected").message;
+    assert_eq!(message, "'elif' has to follow an 'if' block");
+}
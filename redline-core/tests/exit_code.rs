@@ -0,0 +1,29 @@
+//! Tests for `return` in the top-level code, which ends the program with an exit code.
+mod common;
+
+use common::cpp;
+use redline_core::parse_source;
+
+fn main_body(source: &str) -> String {
+    let code = cpp(source);
+    code[code.find("int main").expect("main should be generated")..].to_string()
+}
+
+#[test]
+fn a_top_level_return_becomes_the_exit_code() {
+    let main = main_body("print(\"failed\")\nreturn 1\n");
+    assert!(main.contains("return 1;"), "{}", main);
+
+    // A bare `return` exits successfully.
+    let main = main_body("if true:\n    return\nprint(1)\n");
+    assert!(main.contains("return 0;") && !main.contains("return;"), "{}", main);
+}
+
+#[test]
+fn the_exit_code_has_to_be_an_int() {
+    let message = parse_source("return \"failed\"\n").expect_err("source should be rejected").message;
+    assert_eq!(message, "A top-level 'return' gives the program's exit code, which has to be an 'int', not a 'string'");
+
+    // Inside a function, `return` gives the function's value as usual.
+    assert!(parse_source("def name() -> string:\n    return \"x\"\nprint(name())\nreturn 0\n").is_ok());
+}
@@ -0,0 +1,38 @@
+// Generated by REDLINE Core for module any
+#include <iostream>
+#include <memory>
+#include <map>
+#include "any.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+rl::Any describe(int n) {
+    std::string text = "n is ";
+    text = (text + rl::to_string(n));
+    return std::move(text);
+}
+
+std::string same(std::string s) {
+    std::string copy = s;
+    return copy;
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    rl::Any value = 42;
+    value = "now a string";
+    value = 2.5;
+    print((value + 1));
+    print(describe(3));
+    print(same("unchanged"));
+    return 0;
+}
@@ -0,0 +1,16 @@
+var value: any = 42
+value = "now a string"
+value = 2.5
+print(value + 1)
+
+def describe(n: int) -> any:
+    var text: string = "n is "
+    text = text + to_string(n)
+    return text
+
+def same(s: string) -> string:
+    val copy: string = s
+    return copy
+
+print(describe(3))
+print(same("unchanged"))
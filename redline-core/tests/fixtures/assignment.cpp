@@ -0,0 +1,40 @@
+// Generated by REDLINE Core for module assignment
+#include <iostream>
+#include <memory>
+#include <map>
+#include "assignment.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    int a = 0;
+    int b = 0;
+    a = b = 5;
+    print(a);
+    print(b);
+    std::vector<int> totals = { 0, 0 };
+    a = totals.at(1) = (b + 1);
+    print(totals.at(1));
+    print(a);
+    std::string label{};
+    label = "done";
+    print(label);
+    std::string line{};
+    int lines = 0;
+    while (((line = input()) != "")) {
+        lines = (lines + 1);
+        print(line);
+    }
+    print(lines);
+    return 0;
+}
@@ -0,0 +1,21 @@
+var a: int = 0
+var b: int = 0
+a = b = 5
+print(a)
+print(b)
+
+var totals: list[int] = [0, 0]
+a = totals[1] = _ = b + 1
+print(totals[1])
+print(a)
+
+var label: string
+label = "done"
+print(label)
+
+var line: string
+var lines: int = 0
+while (line = input()) != "":
+    lines = lines + 1
+    print(line)
+print(lines)
@@ -0,0 +1,34 @@
+// Generated by REDLINE Core for module classes
+#include <iostream>
+#include <memory>
+#include <map>
+#include "classes.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+Counter::Counter(int start) {
+    this->count = start;
+}
+
+void Counter::increment() {
+    this->count = (this->count + 1);
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    std::shared_ptr<Counter> c = std::make_shared<Counter>(5);
+    c->increment();
+    print(c->count);
+    c->count = 3;
+    print(c->count);
+    return 0;
+}
@@ -0,0 +1,14 @@
+class Counter:
+    var count: int = 0
+
+    def init(start: int):
+        this.count = start
+
+    def increment():
+        this.count = this.count + 1
+
+var c: Counter = new Counter(5)
+c.increment()
+print(c.count)
+c.count = 3
+print(c.count)
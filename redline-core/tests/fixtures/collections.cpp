@@ -0,0 +1,39 @@
+// Generated by REDLINE Core for module collections
+#include <iostream>
+#include <memory>
+#include <map>
+#include "collections.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    std::vector<int> numbers = { 1, 2, 3 };
+    append(numbers, 4);
+    numbers.at(0) = 10;
+    print(numbers.at(0));
+    print(rl::index_at(numbers, -1));
+    rl::index_at(numbers, -2) = 30;
+    std::map<std::string, int> ages = { {"Alice", 30}, {"Bob", 25} };
+    rl::store_at(ages, "Bob") = 26;
+    rl::store_at(ages, "Carol") = 41;
+    print(rl::index_at(ages, "Bob"));
+    std::vector<int> middle = rl::slice(numbers, 1, 3);
+    std::vector<int> rest = rl::slice(numbers, 1);
+    std::string name = "REDLINE";
+    print(rl::slice(name, 0, 3));
+    print(rl::contains(std::vector<int>{ 1, 2, 3 }, 3));
+    if ((rl::contains(numbers, 30) && rl::contains(name, "RED"))) {
+        print(rl::contains(name, U'L'));
+    }
+    return 0;
+}
@@ -0,0 +1,20 @@
+var numbers: list[int] = [1, 2, 3]
+append(numbers, 4)
+numbers[0] = 10
+print(numbers[0])
+print(numbers[-1])
+numbers[-2] = 30
+
+var ages: dict[string, int] = {"Alice": 30, "Bob": 25}
+ages["Bob"] = 26
+ages["Carol"] = 41
+print(ages["Bob"])
+
+val middle: list[int] = numbers[1..3]
+val rest: list[int] = numbers[1..]
+val name: string = "REDLINE"
+print(name[..3])
+
+print(3 in [1, 2, 3])
+if 30 in numbers && "RED" in name:
+    print('L' in name)
@@ -0,0 +1,34 @@
+// Generated by REDLINE Core for module enums
+#include <iostream>
+#include <memory>
+#include <map>
+#include "enums.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+std::string describe(Status s) {
+    if ((s == Status::Err)) {
+        return "failed";
+    }
+    return "fine";
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    Status s = Status::Unknown;
+    print(s);
+    print(Color::Cyan);
+    print(describe(Status::Err));
+    print((static_cast<int>(Status::Err) + 1));
+    print((static_cast<int>(Color::Cyan) - static_cast<int>(Color::Blue)));
+    return 0;
+}
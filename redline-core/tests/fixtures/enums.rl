@@ -0,0 +1,18 @@
+enum Status: Ok = 0, Err = 1, Unknown = 255
+
+enum Color:
+    Red, Green
+    Blue = 10
+    Cyan
+
+def describe(s: Status) -> string:
+    if s == Status.Err:
+        return "failed"
+    return "fine"
+
+val s: Status = Status.Unknown
+print(s)
+print(Color.Cyan)
+print(describe(Status.Err))
+print(Status.Err + 1)
+print(Color.Cyan - Color.Blue)
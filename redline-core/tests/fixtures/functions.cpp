@@ -0,0 +1,73 @@
+// Generated by REDLINE Core for module functions
+#include <iostream>
+#include <memory>
+#include <map>
+#include "functions.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+int add(int a, int b) {
+    return (a + b);
+}
+
+void greet(std::string name) {
+    print(("Hello, " + name));
+}
+
+double square(double x) {
+    return (x * x);
+}
+
+int sum(std::vector<int> nums) {
+    int total = 0;
+    for (int i = 0; i < len(nums); ++i) {
+        total = (total + rl::index_at(nums, i));
+    }
+    return total;
+}
+template<typename... Rest>
+int sum(Rest... nums) {
+    return sum(std::vector<int>{nums...});
+}
+
+std::string label(std::string prefix, std::vector<std::string> parts) {
+    std::string result = prefix;
+    for (int i = 0; i < len(parts); ++i) {
+        result = ((result + " ") + rl::index_at(parts, i));
+    }
+    return result;
+}
+template<typename... Rest>
+std::string label(std::string prefix, Rest... parts) {
+    return label(prefix, std::vector<std::string>{parts...});
+}
+
+std::tuple<int, int> div_mod(int a, int b) {
+    return std::make_tuple((a / b), (a % b));
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    greet("REDLINE");
+    print(add(2, 3));
+    print(sum(1, 2, 3));
+    print(sum());
+    print(label("colors:", "red", "green"));
+    (void)(add(1, 1));
+    std::tuple<int, std::string> pair = std::make_tuple(7, std::string("seven"));
+    [[maybe_unused]] auto [_rl_tmp0, word] = pair;
+    print(word);
+    auto [quotient, remainder] = div_mod(17, 5);
+    quotient += 1;
+    print(rl::join({ rl::to_string(quotient), rl::to_string(remainder) }, " "));
+    return 0;
+}
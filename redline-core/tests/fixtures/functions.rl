@@ -0,0 +1,39 @@
+def add(a: int, b: int) -> int:
+    return a + b
+
+def greet(name: string):
+    print("Hello, " + name)
+
+pub def square(x: float) -> float:
+    return x * x
+
+greet("REDLINE")
+print(add(2, 3))
+
+def sum(nums: int...) -> int:
+    var total: int = 0
+    for i in 0..len(nums):
+        total = total + nums[i]
+    return total
+
+def label(prefix: string, parts: string...) -> string:
+    var result: string = prefix
+    for i in 0..len(parts):
+        result = result + " " + parts[i]
+    return result
+
+print(sum(1, 2, 3))
+print(sum())
+print(label("colors:", "red", "green"))
+
+_ = add(1, 1)
+
+def div_mod(a: int, b: int) -> (int, int):
+    return (a / b, a % b)
+
+val pair: (int, string) = (7, "seven")
+val (_, word) = pair
+print(word)
+var (quotient, remainder) = div_mod(17, 5)
+quotient += 1
+print(quotient, remainder)
@@ -0,0 +1,102 @@
+// Generated by REDLINE Core for module loops
+#include <iostream>
+#include <memory>
+#include <map>
+#include "loops.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    int total = 0;
+    for (int i = 0; i < 10; ++i) {
+        if ((i == 3)) {
+            continue;
+        }
+        total = (total + i);
+    }
+    int n = 0;
+    while ((n < 5)) {
+        n = (n + 1);
+        if ((n == 4)) {
+            break;
+        }
+    }
+    for (int i = 0; i < 3; ++i) {
+        {
+            for (int j = 0; j < 3; ++j) {
+                if ((j == i)) {
+                    goto outer_continue;
+                }
+                if ((i == 2)) {
+                    goto outer_break;
+                }
+                print(j);
+            }
+        }
+        outer_continue:;
+    }
+    outer_break:;
+    {
+        bool _rl_tmp0 = false;
+        for (int i = 0; true; ++i) {
+            if (!(i < 3)) { _rl_tmp0 = true; break; }
+            if ((i == 5)) {
+                break;
+            }
+        }
+        if (_rl_tmp0) {
+            print("completed");
+        }
+    }
+    {
+        bool _rl_tmp1 = false;
+        for (int i = 0; true; ++i) {
+            if (!(i < 3)) { _rl_tmp1 = true; break; }
+            if ((i == 1)) {
+                break;
+            }
+        }
+        if (_rl_tmp1) {
+            print("skipped by the break");
+        }
+    }
+    {
+        bool _rl_tmp2 = false;
+        for (int i = 0; true; ++i) {
+            if (!(i < 3)) { _rl_tmp2 = true; break; }
+            if ((i == 1)) {
+                continue;
+            }
+            print(i);
+        }
+        if (_rl_tmp2) {
+            print("reached after a continue");
+        }
+    }
+    while (true) {
+        {
+            bool _rl_tmp3 = false;
+            for (int j = 0; true; ++j) {
+                if (!(j < 3)) { _rl_tmp3 = true; break; }
+                if ((j == 1)) {
+                    goto search_break;
+                }
+            }
+            if (_rl_tmp3) {
+                print("never printed");
+            }
+        }
+    }
+    search_break:;
+    return 0;
+}
@@ -0,0 +1,45 @@
+var total: int = 0
+for i in 0..10:
+    if i == 3:
+        continue
+    total = total + i
+
+var n: int = 0
+while n < 5:
+    n = n + 1
+    if n == 4:
+        break
+
+outer: for i in 0..3:
+    for j in 0..3:
+        if j == i:
+            continue outer
+        if i == 2:
+            break outer
+        print(j)
+
+for i in 0..3:
+    if i == 5:
+        break
+else:
+    print("completed")
+
+for i in 0..3:
+    if i == 1:
+        break
+else:
+    print("skipped by the break")
+
+for i in 0..3:
+    if i == 1:
+        continue
+    print(i)
+else:
+    print("reached after a continue")
+
+search: while true:
+    for j in 0..3:
+        if j == 1:
+            break search
+    else:
+        print("never printed")
@@ -0,0 +1,29 @@
+// Generated by REDLINE Core for module match
+#include <iostream>
+#include <memory>
+#include <map>
+#include "match.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+std::string grade(int score) {
+    return [&](const auto& _rl_tmp_subject) { return ((_rl_tmp_subject == 10) ? "A" : (_rl_tmp_subject == 9) ? "A" : (_rl_tmp_subject == 8) ? "B" : (_rl_tmp_subject == 7) ? "C" : "F"); }((score / 10));
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    int c = 2;
+    std::string name = ((c == 1) ? "one" : (c == 2) ? "two" : "many");
+    print(name);
+    print(grade(85));
+    return 0;
+}
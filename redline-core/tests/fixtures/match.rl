@@ -0,0 +1,7 @@
+def grade(score: int) -> string:
+    return match score / 10: 10 => "A", 9 => "A", 8 => "B", 7 => "C", _ => "F"
+
+val c: int = 2
+val name: string = match c: 1 => "one", 2 => "two", _ => "many"
+print(name)
+print(grade(85))
@@ -0,0 +1,27 @@
+// Generated by REDLINE Core for module numbers
+#include <iostream>
+#include <memory>
+#include <map>
+#include "numbers.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    double price = 3.10;
+    double offset = -0.50;
+    int count = -3;
+    print((price + offset));
+    print(count);
+    print(approx_eq((price + offset), 2.6, 0.000001));
+    return 0;
+}
@@ -0,0 +1,6 @@
+val price: float = 3.10
+val offset: float = -0.50
+val count: int = -3
+print(price + offset)
+print(count)
+print(approx_eq(price + offset, 2.6, 0.000001))
@@ -0,0 +1,35 @@
+// Generated by REDLINE Core for module optionals
+#include <iostream>
+#include <memory>
+#include <map>
+#include "optionals.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+std::optional<int> doubled(std::string text) {
+    std::optional<int> n = rl::parse_int(text);
+    if ((n == std::nullopt)) {
+        return std::nullopt;
+    }
+    return (rl::unwrap(n) * 2);
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    print(doubled("21"));
+    print(doubled("twenty-one"));
+    print(rl::unwrap_or(rl::parse_float("2.5"), 0.0));
+    std::optional<std::string> name = std::nullopt;
+    name = "set";
+    print(name);
+    return 0;
+}
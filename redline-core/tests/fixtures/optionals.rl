@@ -0,0 +1,12 @@
+def doubled(text: string) -> int?:
+    val n: int? = parse_int(text)
+    if n == none:
+        return none
+    return unwrap(n) * 2
+
+print(doubled("21"))
+print(doubled("twenty-one"))
+print(unwrap_or(parse_float("2.5"), 0.0))
+var name: string? = none
+name = "set"
+print(name)
@@ -0,0 +1,29 @@
+// Generated by REDLINE Core for module raw_identifiers
+#include <iostream>
+#include <memory>
+#include <map>
+#include "raw_identifiers.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+int rl_print(int rl_val) {
+    return (rl_val * 2);
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    int rl_if = 1;
+    rl_if = (rl_if + 1);
+    print(rl_if);
+    print(rl_print(rl_if));
+    return 0;
+}
@@ -0,0 +1,9 @@
+# Raw identifiers let reserved words be used as names.
+var r#if: int = 1
+r#if = r#if + 1
+print(r#if)
+
+def r#print(r#val: int) -> int:
+    return r#val * 2
+
+print(r#print(r#if))
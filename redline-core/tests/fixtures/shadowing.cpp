@@ -0,0 +1,37 @@
+// Generated by REDLINE Core for module shadowing
+#include <iostream>
+#include <memory>
+#include <map>
+#include "shadowing.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+int scale(int n) {
+    int n_1 = (n * 10);
+    return n_1;
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    int x = 1;
+    if ((x > 0)) {
+        int x_1 = 2;
+        print(x_1);
+    }
+    print(x);
+    for (int i = 0; i < 2; ++i) {
+        std::string i_1 = "inner";
+        print(i_1);
+    }
+    print(scale(4));
+    return 0;
+}
@@ -0,0 +1,14 @@
+val x: int = 1
+if x > 0:
+    val x: int = 2
+    print(x)
+print(x)
+
+def scale(n: int) -> int:
+    val n: int = n * 10
+    return n
+
+for i in 0..2:
+    val i: string = "inner"
+    print(i)
+print(scale(4))
@@ -0,0 +1,42 @@
+// Generated by REDLINE Core for module strings
+#include <iostream>
+#include <memory>
+#include <map>
+#include "strings.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+std::string exclaim(std::string s) {
+    return (s + "!");
+}
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    std::string name = "world";
+    int count = 3;
+    std::string greeting = rl::concat("Hello, ", name, "!");
+    print(greeting);
+    std::string welcome = rl::concat("Hello, ", name, "! Welcome to ", "REDLINE", ".");
+    std::string hi = ("Hi " + name);
+    print(((hi + name) + exclaim(welcome)));
+    print(rl::concat(rl::to_string(name), " has ", rl::to_string(count), " letters to spare"));
+    std::string escaped = "tab\there";
+    print(escaped);
+    print("something went wrong", std::cerr);
+    char32_t initial = U'R';
+    print(initial);
+    print("tab\tand \"quotes\"");
+    std::string folder = "C:\\temp\\new";
+    print(folder);
+    print(rl::concat("[", rl::align_right(rl::to_string(count), 5), "] [", rl::align_left(rl::to_string(name), 7), "]"));
+    return 0;
+}
@@ -0,0 +1,21 @@
+val name: string = "world"
+val count: int = 3
+val greeting: string = "Hello, " + name + "!"
+print(greeting)
+val welcome: string = "Hello, " + name + "! Welcome to " + "REDLINE" + "."
+val hi: string = "Hi " + name
+def exclaim(s: string) -> string:
+    return s + "!"
+print(hi + name + exclaim(welcome))
+print(f"{name} has {count} letters to spare")
+val escaped: string = "tab\there"
+print(escaped)
+
+eprint("something went wrong")
+
+val initial: char = '\u{52}'
+print(initial)
+print("tab\tand \"quotes\"")
+val folder: string = r"C:\temp\new"
+print(folder)
+print(f"[{count:>5}] [{name:<7}]")
@@ -0,0 +1,27 @@
+// Generated by REDLINE Core for module try_catch
+#include <iostream>
+#include <memory>
+#include <map>
+#include "try_catch.hpp"
+
+std::vector<std::string> rl::args;
+
+namespace rl {
+
+
+} // namespace rl
+
+int main(int argc, char* argv[]) {
+    rl::args.assign(argv, argv + argc);
+    std::ios_base::sync_with_stdio(false);
+    std::cin.tie(NULL);
+
+    using namespace rl;
+    try {
+        std::string content = rl::read_file("missing.txt");
+        print(content);
+    } catch (const std::exception& e) {
+        print("could not read file");
+    }
+    return 0;
+}
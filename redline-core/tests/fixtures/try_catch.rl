@@ -0,0 +1,5 @@
+try:
+    val content: string = read_file("missing.txt")
+    print(content)
+catch e:
+    print("could not read file")
@@ -0,0 +1,29 @@
+//! Tests for `for x in <string or list>`, which loops over the characters or elements.
+mod common;
+
+use common::cpp;
+use redline_core::parse_source;
+
+#[test]
+fn a_string_is_looped_over_by_character() {
+    let code = cpp("for c in \"hello\":\n    print(c)\n");
+    assert!(code.contains("for (const auto& c : rl::elements(std::string(\"hello\"))) {"), "{}", code);
+
+    // Each character is a `char`, which can't be looped over in turn.
+    let message = parse_source("for c in \"ab\":\n    for d in c:\n        print(d)\n").expect_err("source should be rejected").message;
+    assert!(message.contains("not a 'char'"), "{}", message);
+}
+
+#[test]
+fn a_list_is_looped_over_by_element() {
+    let code = cpp("var total: int = 0\nfor n in [1, 2, 3]:\n    total = total + n\n");
+    assert!(code.contains("for (const auto& n : std::vector<int>{ 1, 2, 3 }) {"), "{}", code);
+    assert!(parse_source("val xs: list[float] = [1.5]\nfor x in xs:\n    val y: float = x\n").is_ok());
+}
+
+#[test]
+fn the_loop_variable_is_read_only_and_the_subject_must_be_iterable() {
+    assert!(parse_source("for c in \"ab\":\n    c = 'x'\n").is_err());
+    let message = parse_source("for x in 5:\n    print(x)\n").expect_err("source should be rejected").message;
+    assert_eq!(message, "A 'for' loop can run over a list or a string, not a 'int'; to count, loop over a range like '0..n'");
+}
@@ -0,0 +1,60 @@
+//! Tests for the source formatter behind `--format`.
+use redline_core::codegen::{CodegenOptions, GenMode};
+use redline_core::compile;
+use redline_core::formatter::format_source;
+
+const MESSY: &str = "# Adds things up.\n\n\ndef   add(a:int,b :int)->int :\n  # sum it\n\n  return a+b   # trailing\n\n\n\nval items:list[int]=[1,2 ,3]; var total:int=0\nfor i in 0 .. len(items):\n        total=total+items[ i ]*-1\nif not (total>=0) and total != -6:\n     print ( \"neg\" )\nelse:\n     print(f\"total={total}\")\nval d:dict[string,int]={ \"a\":1 ,\"b\" : 2 }\ntotal=-  -total*-d[ \"a\" ]\n";
+
+const CANONICAL: &str = "# Adds things up.
+
+def add(a: int, b: int) -> int:
+    # sum it
+
+    return a + b  # trailing
+
+val items: list[int] = [1, 2, 3]
+var total: int = 0
+for i in 0..len(items):
+    total = total + items[i] * -1
+if !(total >= 0) && total != -6:
+    print(\"neg\")
+else:
+    print(f\"total={total}\")
+val d: dict[string, int] = {\"a\": 1, \"b\": 2}
+total = - -total * -d[\"a\"]
+";
+
+#[test]
+fn messy_source_formats_to_the_canonical_layout() {
+    let formatted = format_source(MESSY).expect("source should lex");
+    assert_eq!(formatted, CANONICAL);
+    assert_eq!(format_source(&formatted).expect("formatted source should lex"), formatted);
+}
+
+#[test]
+fn formatting_doesnt_change_the_generated_code() {
+    let options = CodegenOptions::default();
+    let formatted = format_source(MESSY).expect("source should lex");
+    assert_eq!(
+        compile(MESSY, GenMode::Cpp, "test", &options).expect("source should compile"),
+        compile(&formatted, GenMode::Cpp, "test", &options).expect("formatted source should compile"),
+    );
+}
+
+#[test]
+fn literals_keep_their_value() {
+    let source = "val path: string = r\"C:\\dir\"\nval quote: char = '\\''\nval r#if: string = \"tab\\tthen \\\"quoted\\\"\"\n";
+    assert_eq!(
+        format_source(source).expect("source should lex"),
+        "val path: string = \"C:\\\\dir\"\nval quote: char = '\\''\nval r#if: string = \"tab\\tthen \\\"quoted\\\"\"\n",
+    );
+}
+
+#[test]
+fn comment_lines_are_indented_like_the_code_after_them() {
+    let source = "def f():\n# about the body\n    print(1)\n      # still in f\n# about g\ndef g():\n    print(2)\n";
+    assert_eq!(
+        format_source(source).expect("source should lex"),
+        "def f():\n    # about the body\n    print(1)\n    # still in f\n# about g\ndef g():\n    print(2)\n",
+    );
+}
@@ -0,0 +1,96 @@
+//! Tests for evaluating expressions with `interpreter::eval_expression`.
+use redline_core::interpreter::{eval_expression, Environment, Value};
+
+fn env(variables: &[(&str, Value)]) -> Environment {
+    let mut env = Environment::new();
+    for (name, value) in variables {
+        env.set(name, value.clone());
+    }
+    env
+}
+
+#[test]
+fn variables_come_from_the_environment() {
+    let env = env(&[("a", Value::Int(1)), ("b", Value::Int(3))]);
+    assert_eq!(eval_expression("a + b * 2", &env).unwrap(), Value::Int(7));
+    assert_eq!(eval_expression("(a + b) * 2 > 7 && b != 0", &env).unwrap(), Value::Bool(true));
+
+    let err = eval_expression("a + c", &env).unwrap_err();
+    assert_eq!(err.message, "'c' is not defined");
+}
+
+#[test]
+fn values_behave_like_the_generated_cpp() {
+    let env = env(&[("name", Value::String("redline".to_string())), ("scores", Value::List(vec![Value::Int(4), Value::Int(9)]))]);
+    assert_eq!(eval_expression("7 / 2", &env).unwrap(), Value::Int(3));
+    assert_eq!(eval_expression("scores[-1] / 2.0", &env).unwrap(), Value::Float(4.5));
+    assert_eq!(eval_expression("name + \"!\"", &env).unwrap(), Value::String("redline!".to_string()));
+    assert_eq!(eval_expression("name[0]", &env).unwrap(), Value::Char('r'));
+
+    assert_eq!(eval_expression("2147483647 + 1", &env).unwrap_err().message, "2147483647 + 1 overflows an int");
+    assert_eq!(eval_expression("name + true", &env).unwrap_err().message, "Can't apply '+' to a string and a bool");
+    assert_eq!(eval_expression("1 / 0", &env).unwrap_err().message, "Division by zero");
+}
+
+#[test]
+fn booleans_evaluate() {
+    let env = env(&[("ready", Value::Bool(false))]);
+    assert_eq!(eval_expression("true and false", &env).unwrap(), Value::Bool(false));
+    assert_eq!(eval_expression("!ready || ready == true", &env).unwrap(), Value::Bool(true));
+    assert_eq!(eval_expression("not (1 < 2)", &env).unwrap(), Value::Bool(false));
+
+    // The right side of `&&` isn't evaluated once the left is false.
+    assert_eq!(eval_expression("ready && missing", &env).unwrap(), Value::Bool(false));
+    assert!(eval_expression("true && 1", &env).unwrap_err().message.contains("needs bool operands"));
+}
+
+#[test]
+fn in_looks_inside_lists_and_strings() {
+    let env = env(&[("greeting", Value::String("hello".to_string()))]);
+    assert_eq!(eval_expression("3 in [1, 2, 3]", &env).unwrap(), Value::Bool(true));
+    assert_eq!(eval_expression("4 in [1, 2, 3]", &env).unwrap(), Value::Bool(false));
+    assert_eq!(eval_expression("\"ell\" in \"hello\"", &env).unwrap(), Value::Bool(true));
+    assert_eq!(eval_expression("'z' in greeting", &env).unwrap(), Value::Bool(false));
+}
+
+#[test]
+fn every_operator_category_evaluates() {
+    let env = env(&[
+        ("n", Value::Int(7)),
+        ("maybe", Value::None),
+        ("word", Value::String("redline".to_string())),
+        ("xs", Value::List(vec![Value::Int(1), Value::Int(2), Value::Int(3)])),
+    ]);
+    let eval = |src: &str| eval_expression(src, &env).unwrap_or_else(|e| panic!("{}: {}", src, e));
+    // Arithmetic, with an int meeting a float made a float.
+    assert_eq!(eval("n * 2 - 4 / 3"), Value::Int(13));
+    assert_eq!(eval("n + 0.5"), Value::Float(7.5));
+    // Comparisons on every ordered type.
+    assert_eq!(eval("n >= 7.0"), Value::Bool(true));
+    assert_eq!(eval("\"apple\" < \"banana\""), Value::Bool(true));
+    assert_eq!(eval("'a' <= 'Z'"), Value::Bool(false));
+    // Equality on lists and optionals.
+    assert_eq!(eval("xs == [1, 2, 3]"), Value::Bool(true));
+    assert_eq!(eval("xs != [1, 2]"), Value::Bool(true));
+    assert_eq!(eval("maybe == none"), Value::Bool(true));
+    assert_eq!(eval("n == none"), Value::Bool(false));
+    // Logic, negation, and membership.
+    assert_eq!(eval("!(n > 3) || 2 in xs"), Value::Bool(true));
+    // Slices, whose bounds count from the end when negative and are clamped to the ends.
+    assert_eq!(eval("xs[1..]"), Value::List(vec![Value::Int(2), Value::Int(3)]));
+    assert_eq!(eval("word[..-4]"), Value::String("red".to_string()));
+    assert_eq!(eval("word[5..100]"), Value::String("ne".to_string()));
+    assert_eq!(eval("xs[2..1]"), Value::List(vec![]));
+}
+
+#[test]
+fn operators_on_the_wrong_types_are_runtime_errors() {
+    let env = env(&[("xs", Value::List(vec![Value::Int(1)]))]);
+    let message = |src: &str| eval_expression(src, &env).expect_err(src).message;
+    assert_eq!(message("xs - 1"), "Can't apply '-' to a list and an int");
+    assert_eq!(message("true < false"), "Can't apply '<' to a bool and a bool");
+    assert_eq!(message("'a' + 'b'"), "Can't apply '+' to a char and a char");
+    assert_eq!(message("!5"), "'!' needs a bool, but was given an int");
+    assert_eq!(message("xs[\"a\"..]"), "A slice bound must be an int, not a string");
+    assert_eq!(message("5[0..1]"), "Can't slice an int");
+}
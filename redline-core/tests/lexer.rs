@@ -0,0 +1,198 @@
+//! Tests for the lexer's tokenization and indentation handling.
+use redline_core::lexer::{Lexer, TokenType};
+use redline_core::codegen::{generate, CodegenOptions, GenMode};
+use redline_core::parser::Parser;
+use redline_core::source_map::ColumnMode;
+
+#[test]
+fn indent_trace_records_stack_transitions() {
+    let mut lexer = Lexer::new("if true:\n    if true:\n        print(1)\nprint(2)\n".to_string());
+    lexer.trace_indentation();
+    lexer.tokenize().unwrap();
+    assert_eq!(lexer.indent_trace(), [
+        "line 2: indent 0 -> 4, emit Indent, stack [0, 4]",
+        "line 3: indent 4 -> 8, emit Indent, stack [0, 4, 8]",
+        "line 4: dedent 8 -> 0, emit Dedent x2, stack [0]",
+    ]);
+}
+
+#[test]
+fn end_of_file_closes_every_open_block() {
+    for source in ["if a:\n    if b:\n        print(1)", "if a:\n    if b:\n        print(1)\n", "if a:\n    if b:\n        print(1)\n  "] {
+        let tokens = Lexer::new(source.to_string()).tokenize().unwrap_or_else(|e| panic!("{:?}: {}", source, e));
+        let types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+        assert_eq!(types.iter().filter(|t| **t == TokenType::Indent).count(), 2, "{:?}", source);
+        assert!(types.ends_with(&[TokenType::Dedent, TokenType::Dedent, TokenType::Eof]), "{:?}: {:?}", source, types);
+    }
+}
+
+#[test]
+fn deep_indentation_is_a_clean_error() {
+    let source: String = (0..1_000).map(|depth| format!("{}if a:\n", " ".repeat(depth))).collect::<String>() + &" ".repeat(1_000) + "print(1)\n";
+    let err = Lexer::new(source).tokenize().expect_err("nesting should be rejected");
+    assert_eq!(err.message, "indentation nested too deeply");
+    assert_eq!(err.line, 258);
+
+    // The limit can be changed, and closing every block at once still works right up to it.
+    let source = "if a:\n  if b:\n    print(1)\nprint(2)\n";
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.set_max_indent_depth(2);
+    let types: Vec<TokenType> = lexer.tokenize().unwrap().into_iter().map(|t| t.token_type).collect();
+    assert_eq!(types.iter().filter(|t| **t == TokenType::Dedent).count(), 2, "{:?}", types);
+    let mut lexer = Lexer::new(source.to_string());
+    lexer.set_max_indent_depth(1);
+    assert_eq!(lexer.tokenize().expect_err("nesting should be rejected").line, 3);
+}
+
+#[test]
+fn raw_identifiers_bypass_keywords() {
+    let tokens = Lexer::new("r#if r#print r\n".to_string()).tokenize().unwrap();
+    let types: Vec<TokenType> = tokens.into_iter().map(|t| t.token_type).collect();
+    assert_eq!(types[..3], [
+        TokenType::Ident("if".to_string()),
+        TokenType::Ident("print".to_string()),
+        TokenType::Ident("r".to_string()),
+    ]);
+
+    let err = Lexer::new("r#1\n".to_string()).tokenize().unwrap_err();
+    assert!(err.message.contains("after 'r#'"), "{}", err.message);
+}
+
+fn first_token(source: &str) -> Result<TokenType, String> {
+    Lexer::new(source.to_string()).tokenize().map(|tokens| tokens[0].token_type.clone()).map_err(|e| e.message)
+}
+
+#[test]
+fn chars_and_strings_share_escapes() {
+    assert_eq!(first_token(r"'\u{41}'"), first_token("'A'"));
+    assert_eq!(first_token(r"'\x41'"), Ok(TokenType::Char('A')));
+    assert_eq!(first_token(r"'\''"), Ok(TokenType::Char('\'')));
+    assert_eq!(first_token(r#""\u{e9}\x21\t""#), Ok(TokenType::Str("é!\t".to_string())));
+}
+
+#[test]
+fn char_literals_hold_exactly_one_character() {
+    assert!(first_token("''").unwrap_err().contains("Empty char literal"));
+    assert!(first_token(r"'\u{41}\u{42}'").unwrap_err().contains("has 2 characters"));
+    assert!(first_token(r"'\u{110000}'").unwrap_err().contains("not a valid unicode character"));
+    assert!(first_token(r"'\xFF'").unwrap_err().contains("only go up to"));
+}
+
+#[test]
+fn an_e_is_an_exponent_only_when_digits_follow() {
+    assert_eq!(first_token("1e5"), Ok(TokenType::Float(1e5, "1e5".to_string())));
+    assert_eq!(first_token("2.5E-3"), Ok(TokenType::Float(2.5e-3, "2.5E-3".to_string())));
+
+    let types: Vec<TokenType> = Lexer::new("1 e5".to_string()).tokenize().unwrap().into_iter().map(|t| t.token_type).collect();
+    assert_eq!(types[..2], [TokenType::Int(1), TokenType::Ident("e5".to_string())]);
+
+    assert_eq!(first_token("1e").unwrap_err(), "Invalid number '1e': an exponent needs digits after the 'e'");
+    assert!(first_token("1e+").is_err());
+}
+
+#[test]
+fn integers_can_be_written_in_hex_binary_and_octal() {
+    assert_eq!(first_token("0xDEADBEEF"), Ok(TokenType::Int(0xDEAD_BEEF)));
+    assert_eq!(first_token("0xff"), Ok(TokenType::Int(255)));
+    assert_eq!(first_token("0b1011"), Ok(TokenType::Int(11)));
+    assert_eq!(first_token("0o17"), Ok(TokenType::Int(15)));
+    assert_eq!(first_token("0"), Ok(TokenType::Int(0)));
+
+    let err = Lexer::new("val n: int =\n    0b102\n".to_string()).tokenize().expect_err("'2' isn't a binary digit");
+    assert_eq!(err.message, "Invalid digit '2' in a base-2 number");
+    assert_eq!((err.line, err.column), (2, 9));
+    assert_eq!(first_token("0o8").unwrap_err(), "Invalid digit '8' in a base-8 number");
+    assert_eq!(first_token("0x").unwrap_err(), "Invalid number '0x': expected digits after the prefix");
+    assert_eq!(first_token("0x10000000000000000").unwrap_err(), "Invalid integer: 0x10000000000000000");
+}
+
+#[test]
+fn underscores_separate_digits() {
+    assert_eq!(first_token("1_000_000"), Ok(TokenType::Int(1_000_000)));
+    assert_eq!(first_token("2.718_5"), Ok(TokenType::Float(2.718_5, "2.7185".to_string())));
+    assert_eq!(first_token("6.02e2_3"), Ok(TokenType::Float(6.02e23, "6.02e23".to_string())));
+    assert_eq!(first_token("0xFF_FF"), Ok(TokenType::Int(0xFFFF)));
+
+    let error = |source: &str| {
+        let err = Lexer::new(source.to_string()).tokenize().expect_err("the '_' is misplaced");
+        (err.message, err.column)
+    };
+    assert_eq!(error("x = 5_\n"), ("Invalid number: a '_' separating digits has to be followed by a digit".to_string(), 6));
+    assert_eq!(error("x = 5__0\n"), ("Invalid number: digit separators can't be doubled, as in '1__000'".to_string(), 6));
+    assert_eq!(error("x = 1._5\n"), ("Invalid number: a '_' separating digits has to come after a digit".to_string(), 7));
+    assert_eq!(error("x = 0b_1\n"), ("Invalid number: a '_' separating digits has to come after a digit".to_string(), 7));
+    assert_eq!(error("x = _5\n"), ("Invalid number: a '_' separating digits has to come after a digit".to_string(), 5));
+    assert_eq!(error("x = _1_000\n"), ("Invalid number: a '_' separating digits has to come after a digit".to_string(), 5));
+    // A `_` followed by a letter still starts a name.
+    assert_eq!(first_token("_x5"), Ok(TokenType::Ident("_x5".to_string())));
+}
+
+#[test]
+fn raw_strings_keep_backslashes() {
+    assert_eq!(first_token(r#"r"\n""#), Ok(TokenType::Str("\\n".to_string())));
+    assert_eq!(first_token(r###"r#"say "hi""#"###), Ok(TokenType::Str("say \"hi\"".to_string())));
+    assert!(first_token(r#"r#"never closed""#).unwrap_err().contains("Unterminated raw string"));
+}
+
+#[test]
+fn stray_closing_delimiters_are_reported_where_they_are() {
+    let err = Lexer::new("print(1))\n".to_string()).tokenize().expect_err("the second ')' closes nothing");
+    assert_eq!(err.message, "Unexpected ')' with no '(' before it to close");
+    assert_eq!((err.line, err.column), (1, 9));
+
+    let err = Lexer::new("val xs: list[int] = [1,\n    2)\n".to_string()).tokenize().expect_err("')' can't close a '['");
+    assert_eq!(err.message, "Unexpected ')': the '[' at line 1, column 21 has to be closed first");
+    assert_eq!(err.line, 2);
+}
+
+#[test]
+fn without_indentation_braces_delimit_blocks() {
+    // The two parse to the same program, apart from where things are, so they generate the same C++.
+    let generate = |source: &str, indentation: bool| {
+        let mut lexer = Lexer::new(source.to_string());
+        if !indentation {
+            lexer.disable_indentation();
+        }
+        let tokens = lexer.tokenize().unwrap_or_else(|e| panic!("{:?}: {}", source, e));
+        let program = Parser::new(&tokens).parse().unwrap_or_else(|e| panic!("{:?}: {}", source, e));
+        generate(&program, GenMode::Cpp, "test", &CodegenOptions::default()).expect("should generate")
+    };
+    let indented = "def sign(x: int) -> int:\n    if x > 0:\n        return 1\n    else:\n        return 0\nval d: dict[string, int] = {\"a\": 1}\n";
+    let braced = "  def sign(x: int) -> int: {\n  if x > 0: { return 1 } else: { return 0 }\n}\n    val d: dict[string, int] = {\"a\": 1}\n";
+    assert_eq!(generate(braced, false), generate(indented, true));
+
+    // With indentation on, the leading spaces are an indent.
+    let tokens = Lexer::new("  print(1)\n".to_string()).tokenize().unwrap();
+    assert_eq!(tokens[0].token_type, TokenType::Indent);
+}
+
+#[test]
+fn columns_count_in_the_chosen_mode() {
+    // U+1F600 is one char, two UTF-16 code units, and four UTF-8 bytes.
+    let source = "val s: string = \"\u{1F600}\u{1F600}\" + t\nprint(\"\u{1F600}\") $\n";
+    for (mode, ident_column, error_column) in [(ColumnMode::Utf8Scalar, 24, 12), (ColumnMode::Utf16, 26, 13), (ColumnMode::Byte, 30, 15)] {
+        let mut lexer = Lexer::new(source.to_string());
+        lexer.set_column_mode(mode);
+        let err = lexer.tokenize().expect_err("'$' isn't a token");
+        assert_eq!((err.line, err.column), (2, error_column), "{:?}", mode);
+
+        let mut lexer = Lexer::new(source.lines().next().unwrap().to_string());
+        lexer.set_column_mode(mode);
+        let tokens = lexer.tokenize().unwrap();
+        let t = tokens.iter().find(|token| token.token_type == TokenType::Ident("t".to_string())).unwrap();
+        assert_eq!(t.column, ident_column, "{:?}", mode);
+    }
+    assert_eq!(ColumnMode::Utf16.chars_before("\u{1F600}\u{1F600} x", 6), 3);
+}
+
+#[test]
+fn comment_lines_dont_open_or_close_blocks() {
+    let source = "if a:\n    # first\n    print(1)\n        # deeper\n  # shallower\n# outermost\n    print(2)\nprint(3)\n";
+    let tokens = Lexer::new(source.to_string()).tokenize().unwrap();
+    let blocks: Vec<(TokenType, usize)> = tokens.into_iter()
+        .filter(|t| matches!(t.token_type, TokenType::Indent | TokenType::Dedent))
+        .map(|t| (t.token_type, t.line))
+        .collect();
+    assert_eq!(blocks, [(TokenType::Indent, 3), (TokenType::Dedent, 8)]);
+    assert!(redline_core::parse_source(source).is_ok());
+}
@@ -0,0 +1,22 @@
+//! Tests for `loop:`, which runs until a `break`.
+mod common;
+
+use common::cpp;
+use redline_core::parse_source;
+
+#[test]
+fn a_loop_runs_until_a_break() {
+    let source = "var n: int = 0\nloop:\n    n = n + 1\n    if n == 3:\n        break\nprint(n)\n";
+    assert_eq!(parse_source(source).unwrap(), parse_source(&source.replace("loop:", "while true:")).unwrap());
+    let code = cpp(source);
+    assert!(code.contains("while (true) {"), "{}", code);
+    assert!(code.contains("if ((n == 3)) {\n            break;"), "{}", code);
+}
+
+#[test]
+fn a_loop_can_be_labeled_but_has_no_else() {
+    let code = cpp("var n: int = 0\nouter: loop:\n    loop:\n        n = n + 1\n        if n > 5:\n            break outer\n");
+    assert!(code.contains("goto outer_break;"), "{}", code);
+    let message = parse_source("loop:\n    break\nelse:\n    print(1)\n").expect_err("source should be rejected").message;
+    assert_eq!(message, "A 'loop' can't have an 'else' block, since it only ends with a 'break'");
+}
@@ -0,0 +1,207 @@
+//! Tests for the `-O` optimizer passes.
+mod common;
+
+use common::cpp_with;
+use redline_core::codegen::CodegenOptions;
+
+fn compile_optimized(source: &str) -> String {
+    cpp_with(source, &CodegenOptions { optimize: true, ..Default::default() })
+}
+
+#[test]
+fn vals_are_propagated_and_folded() {
+    let code = compile_optimized("val a: int = 2\nprint(a * 3)\n");
+    assert!(code.contains("print(6);"), "{}", code);
+}
+
+#[test]
+fn negations_of_constants_are_folded() {
+    let code = compile_optimized("val a: int = 2\nval x: float = 1.50\nprint(-a * 3)\nprint(-x)\nprint(- -2147483648)\n");
+    assert!(code.contains("print(-6);"), "{}", code);
+    assert!(code.contains("print(-1.50);"), "{}", code);
+    // The result doesn't fit in an `int`, so it's left as written, apart from a space so it isn't a C++ `--`.
+    assert!(code.contains("print((- -2147483648));"), "{}", code);
+}
+
+#[test]
+fn remainders_fold_when_both_sides_are_non_negative() {
+    let code = compile_optimized("val a: int = 7\nval b: int = -7\nprint(1 + a % 3)\nprint(b % 3)\n");
+    assert!(code.contains("print(2);"), "{}", code);
+    // Truncated and floored remainders differ for a negative side, so it's left to the runtime.
+    assert!(code.contains("print((-7 % 3));"), "{}", code);
+}
+
+#[test]
+fn vars_and_shadowed_names_are_not_propagated() {
+    let source = "var a: int = 2\nprint(a * 3)\n\nval b: int = 1\ndef f(b: int) -> int:\n    return b + 1\n";
+    let code = compile_optimized(source);
+    assert!(code.contains("print((a * 3));"), "{}", code);
+    assert!(code.contains("return (b + 1);"), "{}", code);
+}
+
+#[test]
+fn literals_of_another_type_are_not_propagated() {
+    let code = compile_optimized("val x: float = 7\nprint(x / 2)\n");
+    assert!(code.contains("print((x / 2));"), "{}", code);
+}
+
+#[test]
+fn boolean_expressions_fold() {
+    let code = compile_optimized("print(true and false)\nprint(!true)\nprint(1 < 2 || false)\nprint(not (true == false))\n");
+    assert!(code.contains("print(false);\n    print(false);\n    print(true);\n    print(true);"), "{}", code);
+
+    let code = compile_optimized("val debug: bool = true\nif !debug:\n    print(1)\n");
+    assert!(!code.contains("if (") && !code.contains("print(1);"), "{}", code);
+}
+
+#[test]
+fn constant_conditions_prune_the_dead_branch() {
+    let code = compile_optimized("if true:\n    print(1)\nelse:\n    print(2)\n");
+    assert!(code.contains("print(1);") && !code.contains("print(2);"), "{}", code);
+
+    let code = compile_optimized("if false:\n    print(1)\nelse:\n    print(2)\n");
+    assert!(!code.contains("print(1);") && code.contains("print(2);"), "{}", code);
+
+    let code = compile_optimized("val debug: bool = false\nif debug:\n    print(1)\nprint(3)\n");
+    assert!(!code.contains("if (") && !code.contains("print(1);"), "{}", code);
+}
+
+#[test]
+fn if_true_is_replaced_by_its_body() {
+    let code = compile_optimized("if true:\n    print(1)\nprint(2)\n");
+    assert!(code.contains("    print(1);\n    print(2);\n") && !code.contains("if ("), "{}", code);
+
+    // Declarations keep their own scope so they can't clash with the code around them.
+    let code = compile_optimized("val x: int = 1\nif true:\n    val x: int = 2\n    print(x)\n");
+    assert!(code.contains("if (true) {"), "{}", code);
+}
+
+#[test]
+fn while_false_disappears() {
+    let code = compile_optimized("while false:\n    print(1)\nprint(2)\n");
+    assert!(!code.contains("while") && !code.contains("print(1);") && code.contains("print(2);"), "{}", code);
+
+    // Its `else` block still runs.
+    let code = compile_optimized("while false:\n    print(1)\nelse:\n    print(3)\n");
+    assert!(!code.contains("while") && code.contains("    print(3);\n"), "{}", code);
+}
+
+#[test]
+fn a_for_over_an_empty_range_disappears() {
+    let code = compile_optimized("val n: int = 3\nfor i in n..3:\n    print(i)\nelse:\n    print(4)\n");
+    assert!(!code.contains("for (") && !code.contains("print(i);") && code.contains("    print(4);\n"), "{}", code);
+}
+
+#[test]
+fn conditions_with_calls_are_kept() {
+    // `ready` has a loop, so it isn't inlined either.
+    let code = compile_optimized("def ready() -> bool:\n    for i in 0..1:\n        print(i)\n    return true\n\nif ready():\n    if false:\n        print(1)\n");
+    assert!(code.contains("if (ready()) {"), "{}", code);
+}
+
+#[test]
+fn char_comparisons_fold() {
+    let code = compile_optimized("print('\\u{41}' == 'A')\n");
+    assert!(code.contains("print(true);"), "{}", code);
+}
+
+#[test]
+fn folded_floats_are_spelled_to_round_trip() {
+    let code = compile_optimized("val a: float = 3.10\nprint(a)\nprint(a * 2.0)\n");
+    assert!(code.contains("print(3.10);"), "{}", code);
+    assert!(code.contains("print(6.2);"), "{}", code);
+}
+
+#[test]
+fn small_functions_are_inlined() {
+    let code = compile_optimized("def inc(x: int) -> int:\n    return x + 1\n\nprint(inc(5))\n");
+    let main = &code[code.find("int main(").unwrap()..];
+    assert!(!main.contains("inc("), "{}", code);
+    assert!(main.contains("print(6);"), "{}", code);
+}
+
+#[test]
+fn inlined_early_returns_store_into_a_temporary() {
+    let source = "def sign(n: int) -> int:\n    if n < 0:\n        return -1\n    return 1\n\nvar y: int = 3\nprint(sign(y))\n";
+    let code = compile_optimized(source);
+    assert!(code.contains("int sign_n_1 = y;"), "{}", code);
+    assert!(code.contains("sign_result_2 = -1;") && code.contains("sign_result_2 = 1;"), "{}", code);
+    assert!(code.contains("print(sign_result_2);"), "{}", code);
+}
+
+#[test]
+fn recursive_functions_are_not_inlined() {
+    let source = "def fact(n: int) -> int:\n    if n <= 1:\n        return 1\n    return n * fact(n - 1)\n\nprint(fact(5))\n";
+    let code = compile_optimized(source);
+    assert!(code.contains("print(fact(5));"), "{}", code);
+}
+
+#[test]
+fn to_string_of_a_literal_folds() {
+    let code = compile_optimized("print(to_string(42))\nprint(to_string(true))\nprint(to_string(3.5))\n");
+    assert!(code.contains("print(\"42\");"), "{}", code);
+    assert!(code.contains("print(\"true\");"), "{}", code);
+    // Floats are formatted at runtime, the same way `print` formats them.
+    assert!(code.contains("print(rl::to_string(3.5));"), "{}", code);
+}
+
+#[test]
+fn len_of_a_list_literal_folds() {
+    let code = compile_optimized("print(len([10, 20]))\n");
+    assert!(code.contains("print(2);"), "{}", code);
+
+    // The list has to be built to call `random_int`, so it stays.
+    let code = compile_optimized("print(len([random_int(1, 6), 1]))\n");
+    assert!(code.contains("print(len({ rl::random_int(1, 6), 1 }));"), "{}", code);
+
+    // The length is also known to `static_assert`, without `-O`.
+    assert!(redline_core::check_source("val a: int = 1\nstatic_assert len([a, 2, 3]) == 3\n").is_ok());
+}
+
+#[test]
+fn a_short_range_with_literal_bounds_is_written_out() {
+    let code = compile_optimized("val xs: list[int] = range(0, 3)\nval down: list[int] = range(5, -1, -2)\nprint(len(range(2, 5)))\n");
+    assert!(code.contains("std::vector<int> xs = { 0, 1, 2 };"), "{}", code);
+    assert!(code.contains("std::vector<int> down = { 5, 3, 1 };"), "{}", code);
+    assert!(code.contains("print(3);"), "{}", code);
+
+    // Empty, long, and non-literal ranges are built at runtime.
+    let code = compile_optimized("var n: int = 3\nprint(len(range(3, 0)))\nprint(len(range(0, 1000)))\nprint(len(range(0, n)))\n");
+    assert!(code.contains("rl::range(3, 0)") && code.contains("rl::range(0, 1000)") && code.contains("rl::range(0, n)"), "{}", code);
+}
+
+#[test]
+fn in_on_string_literals_folds() {
+    let code = compile_optimized("print(\"ell\" in \"hello\")\nprint('z' in \"hello\")\n");
+    assert!(code.contains("print(true);\n    print(false);"), "{}", code);
+}
+
+#[test]
+fn enum_discriminants_fold_in_arithmetic() {
+    let code = compile_optimized("enum Status: Ok, Err\nprint(Status.Err + 1)\nprint(Status.Err == Status.Ok)\n");
+    assert!(code.contains("print(2);"), "{}", code);
+    // Comparing two variants compares the enums themselves, which is left to C++.
+    assert!(code.contains("print((Status::Err == Status::Ok));"), "{}", code);
+}
+
+#[test]
+fn results_that_overflow_an_int_are_not_folded() {
+    let code = compile_optimized("def square(x: int) -> int:\n    return x * x\nprint(square(100000))\nprint(square(1000))\n");
+    assert!(code.contains("= (100000 * 100000);"), "{}", code);
+    assert!(code.contains("print(1000000);"), "{}", code);
+}
+
+#[test]
+fn logical_operators_fold_around_a_constant_side() {
+    // The left side decides the result, so the right side never runs and can go.
+    let code = compile_optimized("print(1 > 2 and exists(\"lock\"))\nprint(2 > 1 or exists(\"lock\"))\n");
+    assert!(code.contains("print(false);\n    print(true);"), "{}", code);
+
+    // The left side doesn't decide it, so the result is the right side.
+    let code = compile_optimized("var ready: bool = true\nprint(1 < 2 and ready)\nprint(1 > 2 or ready)\n");
+    assert!(code.contains("print(ready);\n    print(ready);"), "{}", code);
+
+    // A constant right side only replaces a left side that does nothing observable.
+    let code = compile_optimized("var ready: bool = true\nprint(ready and false)\nprint(exists(\"lock\") and false)\nprint(exists(\"lock\") or true)\n");
+    assert!(code.contains("print(false);\n    print((rl::exists(\"lock\") && false));\n    print((rl::exists(\"lock\") || true));"), "{}", code);
+}
@@ -0,0 +1,360 @@
+//! Tests for the shape of the AST the parser produces.
+mod common;
+
+use common::{cpp, cpp_with};
+use redline_core::ast::{BinaryOperator, Expression, Literal, Program, Statement, Type, UnaryOperator};
+use redline_core::codegen::CodegenOptions;
+use redline_core::lexer::{Lexer, TokenType};
+use redline_core::parse_source;
+use redline_core::parser::Parser;
+
+fn parse(source: &str) -> Program {
+    parse_source(source).unwrap_or_else(|e| panic!("failed to parse {:?}: {}", source, e))
+}
+
+#[test]
+fn word_operators_match_symbols() {
+    let pairs = [
+        ("val r: bool = not x\n", "val r: bool = !x\n"),
+        ("val r: bool = a and b\n", "val r: bool = a && b\n"),
+        ("val r: bool = a or b\n", "val r: bool = a || b\n"),
+        ("val r: bool = not a or b and c\n", "val r: bool = !a || b && c\n"),
+    ];
+    for (words, symbols) in pairs {
+        assert_eq!(parse(words), parse(symbols), "{:?} vs {:?}", words, symbols);
+    }
+}
+
+#[test]
+fn not_binds_tighter_than_comparison() {
+    assert_eq!(parse("val r: bool = !a == b\n"), parse("val r: bool = (!a) == b\n"));
+    assert_ne!(parse("val r: bool = !a == b\n"), parse("val r: bool = !(a == b)\n"));
+    // `!=` is still one operator.
+    let program = parse("val r: bool = a != b\n");
+    assert!(matches!(&program.statements[0], Statement::Declaration { initializer: Some(Expression::BinaryOp { op: BinaryOperator::NotEqual, .. }), .. }));
+
+    let code = cpp("val a: bool = true\nval r: bool = !a == false\n");
+    assert!(code.contains("((!a) == false)"), "{}", code);
+}
+
+#[test]
+fn logical_operators_bind_looser_than_comparison() {
+    assert_eq!(parse("val r: bool = a == b && c == d\n"), parse("val r: bool = (a == b) && (c == d)\n"));
+    assert_eq!(parse("val r: bool = a || b && c\n"), parse("val r: bool = a || (b && c)\n"));
+    assert_eq!(parse("val r: bool = x > 0 && x < 10 || y\n"), parse("val r: bool = ((x > 0) && (x < 10)) || y\n"));
+
+    let code = cpp("val x: int = 5\nval r: bool = x > 0 && x < 10\n");
+    assert!(code.contains("((x > 0) && (x < 10))"), "{}", code);
+}
+
+#[test]
+fn modulo_binds_like_multiplication() {
+    assert_eq!(parse("val r: int = 1 + 7 % 3\n"), parse("val r: int = 1 + (7 % 3)\n"));
+    assert_eq!(parse("val r: int = 7 % 3 * 2\n"), parse("val r: int = (7 % 3) * 2\n"));
+    let program = parse("val r: int = 7 % 3\n");
+    assert!(matches!(&program.statements[0], Statement::Declaration { initializer: Some(Expression::BinaryOp { op: BinaryOperator::Modulo, .. }), .. }));
+}
+
+#[test]
+fn unless_is_an_if_with_a_negated_condition() {
+    assert_eq!(
+        parse("unless x > 0:\n    print(1)\nelse:\n    print(2)\n"),
+        parse("if !(x > 0):\n    print(1)\nelse:\n    print(2)\n"),
+    );
+    let err = parse_source("unless x > 0\n    print(1)\n").expect_err("a missing ':' should be rejected");
+    assert_eq!(err.message, "Missing ':' after the unless header");
+}
+
+#[test]
+fn word_operators_are_reserved() {
+    assert!(parse_source("val and: int = 1\n").is_err());
+}
+
+#[test]
+fn deep_nesting_is_a_clean_error() {
+    // On a stack of a known size rather than whatever `RUST_MIN_STACK` gives the test thread, so the
+    // nesting limit is shown to leave room to spare.
+    let check = || {
+        let source = format!("val x: int = {}1{}\n", "(".repeat(10_000), ")".repeat(10_000));
+        let err = parse_source(&source).expect_err("nesting should be rejected");
+        assert!(err.message.contains("expression nesting too deep"), "{}", err.message);
+
+        let source = format!("val x: int = {}1{}\n", "(".repeat(100), ")".repeat(100));
+        parse(&source);
+    };
+    std::thread::Builder::new().stack_size(1024 * 1024).spawn(check).unwrap().join().unwrap();
+}
+
+#[test]
+fn parentheses_with_commas_make_a_tuple() {
+    let program = parse("val pair: (int, (string, bool)) = (1, (\"a\", true))\nval (_, inner) = pair\nval one: int = (1)\n");
+    let Statement::Declaration { data_type, initializer: Some(Expression::TupleLiteral(elements)), .. } = &program.statements[0] else {
+        panic!("expected a tuple declaration")
+    };
+    assert_eq!(*data_type, Type::Tuple(vec![Type::Int, Type::Tuple(vec![Type::String, Type::Bool])]));
+    assert!(matches!(elements[..], [Expression::Literal(Literal::Int(1)), Expression::TupleLiteral(_)]));
+    assert!(matches!(&program.statements[1], Statement::Destructure { is_mutable: false, names, .. } if names[..] == ["_", "inner"]));
+    assert!(matches!(&program.statements[2], Statement::Declaration { initializer: Some(Expression::Literal(Literal::Int(1))), .. }));
+
+    let err = parse_source("val (a) = pair\n").expect_err("one name isn't a destructuring");
+    assert!(err.message.starts_with("Destructuring needs a name for each of at least two elements"), "{}", err.message);
+    let err = parse_source("val p: (int) = 1\n").expect_err("one type isn't a tuple");
+    assert!(err.message.starts_with("A tuple type needs at least two element types"), "{}", err.message);
+}
+
+#[test]
+fn max_depth_is_configurable() {
+    let tokens = Lexer::new("val x: int = (1)\nval y: int = ((1))\n".to_string()).tokenize().unwrap();
+    assert!(Parser::new(&tokens).parse().is_ok());
+
+    let mut parser = Parser::new(&tokens);
+    parser.set_max_depth(2);
+    let err = parser.parse().expect_err("second parenthesis should be too deep");
+    assert!(err.message.contains("expression nesting too deep"), "{}", err.message);
+}
+
+#[test]
+fn semicolons_separate_statements() {
+    let program = parse("var a: int = 0\nvar b: int = 0\na = 1; b = 2;\nif a == 1:\n    a = 3; return\n");
+    assert_eq!(program.statements.len(), 5);
+    assert!(matches!(&program.statements[2], Statement::Assignment { targets, .. } if targets[..] == [Expression::Identifier("a".to_string())]));
+    assert!(matches!(&program.statements[3], Statement::Assignment { targets, .. } if targets[..] == [Expression::Identifier("b".to_string())]));
+    let Statement::If { consequence, .. } = &program.statements[4] else { panic!("expected an if") };
+    assert!(matches!(consequence[..], [Statement::Assignment { .. }, Statement::Return { value: None, .. }]));
+}
+
+#[test]
+fn chained_assignment_lists_every_target() {
+    let program = parse("var a: int = 0\nvar b: int = 0\na = b = 5\n");
+    let Statement::Assignment { targets, value, .. } = &program.statements[2] else { panic!("expected an assignment") };
+    assert_eq!(targets, &[Expression::Identifier("a".to_string()), Expression::Identifier("b".to_string())]);
+    assert_eq!(value, &Expression::Literal(Literal::Int(5)));
+}
+
+#[test]
+fn a_parenthesized_assignment_is_an_expression() {
+    let program = parse("var n: int = 0\nwhile (n = n + 1) < 5:\n    print(n)\n");
+    let Statement::While { condition: Expression::BinaryOp { left, .. }, .. } = &program.statements[1] else { panic!("expected a comparison") };
+    let Expression::Assign { target, value } = &**left else { panic!("expected an assignment, got {:?}", left) };
+    assert_eq!(**target, Expression::Identifier("n".to_string()));
+    assert!(matches!(**value, Expression::BinaryOp { .. }));
+}
+
+#[test]
+fn a_file_can_end_inside_nested_blocks() {
+    let program = parse("def f(n: int):\n    if n > 0:\n        print(n)");
+    let Statement::FunctionDefinition { body, .. } = &program.statements[0] else { panic!("expected a function") };
+    let Statement::If { consequence, .. } = &body[0] else { panic!("expected an if") };
+    assert!(matches!(consequence[..], [Statement::Print { .. }]));
+}
+
+#[test]
+fn only_the_last_parameter_can_be_variadic() {
+    let program = parse("def sum(first: int, rest: int...) -> int:\n    return first\n");
+    let Statement::FunctionDefinition { params, .. } = &program.statements[0] else { panic!("expected a function") };
+    assert!(!params[0].is_variadic && params[1].is_variadic);
+
+    let err = parse_source("def f(rest: int..., last: int):\n    return\n").expect_err("variadic must be last");
+    assert!(err.message.contains("Only the last parameter can be variadic"), "{}", err.message);
+}
+
+#[test]
+fn minus_before_a_number_is_a_negative_literal() {
+    let program = parse("print(a[-1])\nprint(a - 1)\n");
+    let Statement::Print { values, .. } = &program.statements[0] else { panic!("expected a print") };
+    let [Expression::Index { index, .. }] = &values[..] else { panic!("expected an index") };
+    assert_eq!(**index, Expression::Literal(Literal::Int(-1)));
+    let Statement::Print { values, .. } = &program.statements[1] else { panic!("expected a print") };
+    assert!(matches!(values[..], [Expression::BinaryOp { .. }]), "{:?}", values);
+}
+
+#[test]
+fn minus_before_anything_else_negates_it() {
+    let program = parse("print(-a * b)\nprint(-a + b)\nprint(- -a)\n");
+    let negated = |name: &str| Expression::Unary { op: UnaryOperator::Negate, operand: Box::new(Expression::Identifier(name.to_string())) };
+    for (statement, op) in program.statements[..2].iter().zip([BinaryOperator::Multiply, BinaryOperator::Add]) {
+        let Statement::Print { values, .. } = statement else { panic!("expected a print") };
+        let [Expression::BinaryOp { op: found, left, .. }] = &values[..] else { panic!("expected a binary operation, got {:?}", values) };
+        assert_eq!((found, &**left), (&op, &negated("a")));
+    }
+    let Statement::Print { values, .. } = &program.statements[2] else { panic!("expected a print") };
+    assert_eq!(values[0], Expression::Unary { op: UnaryOperator::Negate, operand: Box::new(negated("a")) });
+
+    let code = cpp("val a: int = 1\nval b: int = -a * 2\n");
+    assert!(code.contains("((-a) * 2)"), "{}", code);
+}
+
+#[test]
+fn slices_can_leave_out_either_bound() {
+    let program = parse("val a: list[int] = xs[1..3]\nval b: list[int] = xs[1..]\nval c: list[int] = xs[..3]\n");
+    let bounds: Vec<(bool, bool)> = program.statements.iter().map(|s| match s {
+        Statement::Declaration { initializer: Some(Expression::Slice { start, end, .. }), .. } => (start.is_some(), end.is_some()),
+        other => panic!("expected a slice, got {:?}", other),
+    }).collect();
+    assert_eq!(bounds, [(true, true), (true, false), (false, true)]);
+}
+
+#[test]
+fn enum_variants_count_up_from_the_previous_value() {
+    let program = parse("enum Status: Ok = 0, Err = 1, Unknown = 255\nenum Color:\n    Red, Green\n    Blue = 10\n    Cyan\n");
+    let values = |statement: &Statement| match statement {
+        Statement::Enum { variants, .. } => variants.iter().map(|v| (v.name.clone(), v.value)).collect::<Vec<_>>(),
+        other => panic!("expected an enum, got {:?}", other),
+    };
+    let pairs = |list: &[(&str, i64)]| list.iter().map(|(n, v)| (n.to_string(), *v)).collect::<Vec<_>>();
+    assert_eq!(values(&program.statements[0]), pairs(&[("Ok", 0), ("Err", 1), ("Unknown", 255)]));
+    assert_eq!(values(&program.statements[1]), pairs(&[("Red", 0), ("Green", 1), ("Blue", 10), ("Cyan", 11)]));
+}
+
+#[test]
+fn enum_names_are_enum_types_even_before_their_definition() {
+    let program = parse("val s: Status = Status.Ok\nenum Status: Ok, Err\n");
+    assert!(matches!(&program.statements[0], Statement::Declaration { data_type: Type::Enum(name), .. } if name == "Status"));
+}
+
+#[test]
+fn match_is_an_expression_ending_in_a_default_arm() {
+    let program = parse("val x: string = match c: 1 => \"a\", 2 => \"b\", _ => \"c\"\n");
+    let Statement::Declaration { initializer: Some(Expression::Match { subject, arms, default }), .. } = &program.statements[0] else {
+        panic!("expected a match, got {:?}", program.statements[0])
+    };
+    assert_eq!(**subject, Expression::Identifier("c".to_string()));
+    assert_eq!(arms.len(), 2);
+    assert_eq!(**default, Expression::Literal(Literal::String("c".to_string())));
+
+    let err = parse_source("val x: int = match c: 1 => 2\n").expect_err("the '_' arm is required");
+    assert!(err.message.contains("final '_ => value' arm"), "{}", err.message);
+}
+
+#[test]
+fn fstring_widths_pad_the_placeholder() {
+    assert_eq!(parse("print(f\"{count:>5}\")\n"), parse("print(align_right(to_string(count), 5))\n"));
+    assert_eq!(parse("print(f\"{count:5}\")\n"), parse("print(align_right(to_string(count), 5))\n"));
+    assert_eq!(parse("print(f\"{name:<8}|\")\n"), parse("print(align_left(to_string(name), 8) + \"|\")\n"));
+
+    let err = parse_source("print(f\"{count:>five}\")\n").expect_err("the width must be a number");
+    assert!(err.message.contains("Invalid format specifier '>five'"), "{}", err.message);
+}
+
+#[test]
+fn a_trailing_expression_is_the_return_value() {
+    assert_eq!(parse("def square(x: int) -> int:\n    x * x\n"), parse("def square(x: int) -> int:\n    return x * x\n"));
+    let source = "def sign(x: int) -> int:\n    if x < 0:\n        return 0 - 1\n    1\n";
+    assert_eq!(parse(source), parse(&source.replace("    1\n", "    return 1\n")));
+
+    // A function that returns nothing keeps its last call as a statement.
+    let program = parse("def greet():\n    print(\"hi\")\n    helper()\n\ndef helper():\n    print(\"!\")\n");
+    let Statement::FunctionDefinition { body, .. } = &program.statements[0] else { panic!("expected a function") };
+    assert!(matches!(body.last(), Some(Statement::Expression { .. })), "{:?}", body);
+
+    let code = cpp("def square(x: int) -> int:\n    x * x\n");
+    assert!(code.contains("return (x * x);"), "{}", code);
+}
+
+#[test]
+fn only_the_picked_conditional_block_is_kept() {
+    let program = parse("@if OPTIMIZE > 0:\n    print(1)\n@else:\n    print(2)\n    print(3)\n");
+    assert_eq!(program.statements.len(), 2);
+
+    // The block that isn't picked is never checked, so it can use things that don't exist here.
+    let source = "@if OPTIMIZE == 1 && TARGET == \"cpp\":\n    val x: int = 1\n@else:\n    missing()\nprint(x)\n";
+    cpp_with(source, &CodegenOptions { optimize: true, ..Default::default() });
+
+    let err = parse_source("@if TARGET:\n    print(1)\n").expect_err("a condition has to be a bool");
+    assert!(err.message.contains("An '@if' condition has to compare the build flags"), "{}", err);
+}
+
+#[test]
+fn every_operator_with_a_precedence_converts_to_a_binary_operator() {
+    // Every operator and punctuation token the lexer can produce.
+    let tokens = Lexer::new("+ - * / % == != > < >= <= && || ! and or not in . .. ... = -> => : ? @ , ;".to_string())
+        .tokenize()
+        .unwrap();
+    for token in tokens.iter().filter(|t| !matches!(t.token_type, TokenType::Eof | TokenType::Newline)) {
+        let precedence = Parser::get_precedence(&token.token_type);
+        let op = Parser::token_to_binary_op(&token.token_type);
+        assert_eq!(precedence > 0, op.is_some(), "{:?} has precedence {} but converts to {:?}", token.token_type, precedence, op);
+    }
+
+    // And every binary operator can be written, parsed back from its spelling, and has a precedence.
+    use BinaryOperator::*;
+    let all = [Add, Subtract, Multiply, Divide, Modulo, Equal, NotEqual, GreaterThan, LessThan, GreaterThanEqual, LessThanEqual, And, Or, In];
+    for op in all {
+        // Adding an operator without listing it above fails to compile here.
+        match op {
+            Add | Subtract | Multiply | Divide | Modulo | Equal | NotEqual | GreaterThan | LessThan | GreaterThanEqual | LessThanEqual | And | Or | In => {}
+        }
+        let token = &Lexer::new(op.to_string()).tokenize().unwrap()[0];
+        assert!(Parser::get_precedence(&token.token_type) > 0, "{} has no precedence", op);
+        assert_eq!(Parser::token_to_binary_op(&token.token_type), Some(op));
+    }
+}
+
+#[test]
+fn a_while_loop_needs_an_indented_body() {
+    let program = parse("var i: int = 0\nwhile i < 10:\n    i = i + 1\n");
+    assert!(matches!(&program.statements[1], Statement::While { body, .. } if body.len() == 1));
+
+    for source in ["var i: int = 0\nwhile i < 10:\nprint(i)\n", "while true:\n"] {
+        let err = parse_source(source).expect_err("an empty body should be rejected");
+        assert_eq!(err.message, "Expected an indented block after the ':'; a block can't be empty");
+    }
+}
+
+#[test]
+fn a_for_loop_without_an_iterator_names_what_it_found() {
+    assert!(matches!(&parse("for i in 0..10:\n    print(i)\n").statements[0], Statement::For { iterator, .. } if iterator == "i"));
+
+    let err = parse_source("for in 0..10:\n    print(1)\n").expect_err("a missing iterator should be rejected");
+    assert_eq!(err.message, "Expected iterator name after 'for', got 'in'");
+    assert_eq!((err.line, err.column), (1, 5));
+    let err = parse_source("for\n").expect_err("a missing iterator should be rejected");
+    assert_eq!(err.message, "Expected iterator name after 'for', got the end of the line");
+}
+
+#[test]
+fn true_and_false_are_bool_literals() {
+    let program = parse("val flag: bool = true\nval bigger: bool = 3 > 2\n");
+    assert!(matches!(&program.statements[0], Statement::Declaration { data_type: Type::Bool, initializer: Some(Expression::Literal(Literal::Bool(true))), .. }));
+    assert!(matches!(&program.statements[1], Statement::Declaration { initializer: Some(Expression::BinaryOp { op: BinaryOperator::GreaterThan, .. }), .. }));
+
+    let code = cpp("val flag: bool = false\nprint(flag)\n");
+    assert!(code.contains("bool flag = false;"), "{}", code);
+}
+
+#[test]
+fn a_header_missing_its_colon_is_reported_where_the_colon_belongs() {
+    let cases = [
+        ("if ready\n    print(1)\n", "if", (3, 9)),
+        ("unless ready\n    print(1)\n", "unless", (3, 13)),
+        ("if ready:\n    print(1)\nelif done\n    print(2)\n", "elif", (5, 10)),
+        ("if ready:\n    print(1)\nelse\n    print(2)\n", "else", (5, 5)),
+        ("while ready\n    print(1)\n", "while", (3, 12)),
+        ("loop\n    break\n", "loop", (3, 5)),
+        ("for i in 0..3\n    print(i)\n", "for", (3, 14)),
+        ("def run() -> int\n    return 1\n", "function", (3, 17)),
+    ];
+    for (source, header, location) in cases {
+        let source = format!("val ready: bool = true\nval done: bool = false\n{}", source);
+        let error = parse_source(&source).expect_err("source should be rejected");
+        assert_eq!(error.message, format!("Missing ':' after the {} header", header));
+        assert_eq!((error.line, error.column), location, "{}", header);
+    }
+    // Anything else after the header is still the general error.
+    let message = parse_source("if true print(1)\n").expect_err("source should be rejected").message;
+    assert!(message.starts_with("Expected ':' after if condition"), "{}", message);
+}
+
+#[test]
+fn recovery_parses_past_missing_colons() {
+    let tokens = Lexer::new("def f()\n    while true\n        break\nprint(1)\n".to_string()).tokenize().unwrap();
+    let mut parser = Parser::new(&tokens);
+    parser.set_recovery(true);
+    let program = parser.parse().expect("the missing colons should be recovered from");
+    let messages: Vec<String> = parser.take_recovered().into_iter().map(|e| e.message).collect();
+    assert_eq!(messages, ["Missing ':' after the function header", "Missing ':' after the while header"]);
+    let [Statement::FunctionDefinition { body, .. }, Statement::Print { .. }] = program.statements.as_slice() else {
+        panic!("expected the function and the print, got {:?}", program.statements)
+    };
+    assert!(matches!(body.as_slice(), [Statement::While { .. }]));
+}
@@ -0,0 +1,27 @@
+//! Tests for `print` and `eprint` with several values, optionally joined by `sep: "..."`.
+mod common;
+
+use common::cpp;
+use redline_core::parse_source;
+
+#[test]
+fn several_values_are_joined_by_the_separator() {
+    let code = cpp("val n: int = 2\nprint(1, n, \"three\", sep: \", \")\n");
+    assert!(code.contains("print(rl::join({ rl::to_string(1), rl::to_string(n), rl::to_string(\"three\") }, \", \"));"), "{}", code);
+}
+
+#[test]
+fn the_separator_defaults_to_a_space_and_a_single_value_prints_as_before() {
+    let code = cpp("eprint(1, 2)\nprint(3)\nprint(4, sep: \"-\")\n");
+    assert!(code.contains("print(rl::join({ rl::to_string(1), rl::to_string(2) }, \" \"), std::cerr);"), "{}", code);
+    assert!(code.contains("print(3);"), "{}", code);
+    assert!(code.contains("print(4);"), "{}", code);
+}
+
+#[test]
+fn the_separator_has_to_be_a_string_literal() {
+    let message = parse_source("val s: string = \",\"\nprint(1, 2, sep: s)\n").expect_err("source should be rejected").message;
+    assert_eq!(message, "print's 'sep' has to be a string literal, like sep: \", \"");
+    // `sep` ends the arguments.
+    assert!(parse_source("print(1, sep: \",\", 2)\n").is_err());
+}
@@ -0,0 +1,441 @@
+//! Tests for the checks the semantic pass performs between parsing and codegen.
+use redline_core::{check_source, check_source_with, parse_source};
+use redline_core::semantic::{symbols, SemanticOptions};
+
+fn error_message(source: &str) -> String {
+    parse_source(source).expect_err("source should be rejected").message
+}
+
+fn warnings(source: &str) -> Vec<(String, usize)> {
+    let (_, warnings) = check_source(source).expect("source should compile");
+    warnings.into_iter().map(|w| (w.message, w.line)).collect()
+}
+
+#[test]
+fn assigning_to_a_parameter_requires_var() {
+    let message = error_message("def bump(n: int) -> int:\n    n = n + 1\n    return n\n");
+    assert!(message.contains("Cannot assign to parameter 'n'"), "{}", message);
+
+    assert!(parse_source("def bump(var n: int) -> int:\n    n = n + 1\n    return n\n").is_ok());
+}
+
+#[test]
+fn assigning_to_a_val_is_rejected() {
+    let message = error_message("val x: int = 1\nx = 2\n");
+    assert!(message.contains("declared with 'val'"), "{}", message);
+}
+
+#[test]
+fn assigning_into_a_list_or_object_requires_a_var() {
+    assert!(parse_source("var arr: list[int] = [1, 2]\narr[0] = 5\n").is_ok());
+    let message = error_message("val arr: list[int] = [1, 2]\narr[0] = 5\n");
+    assert_eq!(message, "Cannot assign to an element of 'arr' because it was declared with 'val'; declare it with 'var' to allow this");
+
+    let class = "class Point:\n    var x: int = 0\n";
+    assert!(parse_source(&format!("{}var p: Point = new Point()\np.x = 3\n", class)).is_ok());
+    let message = error_message(&format!("{}val p: Point = new Point()\np.x = 3\n", class));
+    assert!(message.contains("Cannot assign to a field of 'p'"), "{}", message);
+
+    let message = error_message("def clear(grid: list[list[int]]):\n    grid[0][0] = 0\n");
+    assert!(message.contains("of parameter 'grid' because parameters are immutable"), "{}", message);
+
+    let message = error_message("def make() -> list[int]:\n    return [1]\nmake() = [2]\n");
+    assert!(message.starts_with("Cannot assign to 'make()'"), "{}", message);
+    let message = error_message("var x: int = 1\nx + 1 = 2\n");
+    assert_eq!(message, "Cannot assign to 'x + 1'; only a variable, a list or dict element, or a field can be assigned to");
+}
+
+#[test]
+fn constant_if_conditions_warn_about_the_dead_branch() {
+    let found = warnings("if true:\n    print(1)\nelse:\n    print(2)\n");
+    assert_eq!(found.len(), 1);
+    assert!(found[0].0.contains("'else' branch is unreachable"), "{}", found[0].0);
+    assert_eq!(found[0].1, 3);
+
+    let found = warnings("print(0)\nif false:\n    print(1)\n");
+    assert_eq!(found.len(), 1);
+    assert!(found[0].0.contains("body of this 'if' is unreachable"), "{}", found[0].0);
+    assert_eq!(found[0].1, 2);
+
+    assert!(warnings("if true:\n    print(1)\n").is_empty());
+}
+
+#[test]
+fn comparing_a_value_with_itself_warns() {
+    let found = warnings("var x: int = 1\nvar y: int = 2\nprint(x == x)\n");
+    assert_eq!(found.len(), 1);
+    assert!(found[0].0.contains("Comparing 'x' with itself is always true"), "{}", found[0].0);
+    assert_eq!(found[0].1, 3);
+
+    let found = warnings("var x: int = 1\nif x < x:\n    print(x)\n");
+    assert!(found[0].0.contains("always false"), "{}", found[0].0);
+
+    assert!(warnings("var x: int = 1\nvar y: int = 2\nprint(x == y)\n").is_empty());
+}
+
+#[test]
+fn comparing_with_a_bool_literal_warns() {
+    let found = warnings("var done: bool = false\nif done == true:\n    print(1)\n");
+    assert_eq!(found.len(), 1);
+    assert!(found[0].0.contains("use 'done' directly"), "{}", found[0].0);
+
+    let found = warnings("var done: bool = false\nwhile done != true:\n    done = true\n");
+    assert!(found[0].0.contains("use '!done' instead"), "{}", found[0].0);
+}
+
+#[test]
+fn naming_something_after_a_builtin_warns() {
+    let found = warnings("def len(items: list[int]) -> int:\n    return 0\n");
+    assert_eq!(found, [("The function 'len' has the same name as a builtin, which it hides wherever it's in scope".to_string(), 1)]);
+
+    let found = warnings("def area(sqrt: float) -> float:\n    val max: float = sqrt\n    return max\n");
+    let messages: Vec<&str> = found.iter().map(|(message, _)| message.as_str()).collect();
+    assert_eq!(messages, ["The parameter 'sqrt' has the same name as a builtin, which it hides wherever it's in scope",
+        "The variable 'max' has the same name as a builtin, which it hides wherever it's in scope"]);
+
+    // A method is always called through its object, so it can share a builtin's name.
+    assert!(warnings("class Stack:\n    var size: int = 0\n    def len() -> int:\n        return this.size\n").is_empty());
+}
+
+#[test]
+fn comparing_floats_for_equality_warns() {
+    let found = warnings("val total: float = 0.1 + 0.2\nprint(total == 0.3)\n");
+    assert_eq!(found, [("Comparing floats with '==' is unreliable, since rounding can make values that should be equal differ slightly; use 'approx_eq(a, b, eps)' to compare within a tolerance".to_string(), 2)]);
+
+    // Arithmetic with a float in it is a float, and so are the results of math builtins like `sqrt`.
+    assert_eq!(warnings("def check(n: int, x: float) -> bool:\n    return n * x != 1\n").len(), 1);
+    assert_eq!(warnings("val root: float = sqrt(2.0)\nprint(root * root == 2.0)\n").len(), 1);
+
+    assert!(warnings("val n: int = 3\nprint(n == 3)\nprint(approx_eq(sqrt(2.0) * sqrt(2.0), 2.0, 0.000001))\n").is_empty());
+}
+
+#[test]
+fn reading_a_var_before_it_has_a_value_warns() {
+    let found = warnings("var x: int\nprint(x)\nx = 1\n");
+    assert_eq!(found, [("'x' was declared without a value and might not have been assigned one yet".to_string(), 2)]);
+
+    assert!(warnings("var x: int\nx = 1\nprint(x)\n").is_empty());
+}
+
+#[test]
+fn a_var_is_only_assigned_after_an_if_when_every_branch_assigns_it() {
+    let found = warnings("def f(c: bool) -> int:\n    var x: int\n    if c:\n        x = 1\n    return x\n");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].1, 5);
+
+    assert!(warnings("def f(c: bool) -> int:\n    var x: int\n    if c:\n        x = 1\n    else:\n        x = 2\n    return x\n").is_empty());
+    // A branch that returns never reaches the read.
+    assert!(warnings("def f(c: bool) -> int:\n    var x: int\n    if c:\n        return 0\n    x = 1\n    return x\n").is_empty());
+    // The loop might not run.
+    assert_eq!(warnings("def f(n: int) -> int:\n    var x: int\n    for i in 0..n:\n        x = i\n    return x\n").len(), 1);
+}
+
+#[test]
+fn enum_values_must_be_unique() {
+    let message = error_message("enum Status: Ok = 1, Err = 0, Unknown\n");
+    assert!(message.contains("'Ok' and 'Unknown' of 'Status' both have the value 1"), "{}", message);
+
+    let message = error_message("enum Status: Ok, Ok\n");
+    assert!(message.contains("already has a variant named 'Ok'"), "{}", message);
+
+    assert!(check_source("enum Status: Ok = 0, Err = 1, Unknown = 255\n").is_ok());
+}
+
+#[test]
+fn to_string_takes_one_plain_value() {
+    for source in ["print(to_string(42))\n", "print(to_string(true))\n", "print(to_string(3.5))\n", "val c: char = 'x'\nprint(to_string(c))\n"] {
+        assert!(check_source(source).is_ok(), "{}", source);
+    }
+
+    let message = error_message("print(to_string(1, 2))\n");
+    assert!(message.contains("to_string takes 1 argument but 2 were given"), "{}", message);
+
+    let message = error_message("val xs: list[int] = [1]\nprint(to_string(xs))\n");
+    assert!(message.contains("to_string can't convert a list[int]"), "{}", message);
+}
+
+#[test]
+fn adding_a_number_to_a_string_needs_auto_stringify() {
+    let message = error_message("print(\"x=\" + 5)\n");
+    assert!(message.contains("Can't add a string and a value of type 'int'"), "{}", message);
+
+    let relaxed = SemanticOptions { auto_stringify: true, ..Default::default() };
+    assert!(check_source_with("print(\"x=\" + 5)\n", &relaxed).is_ok());
+    assert!(check_source("print(\"x=\" + to_string(5))\n").is_ok());
+}
+
+#[test]
+fn parse_builtins_take_one_string() {
+    assert!(check_source("val n: int? = parse_int(\"42\")\nval x: float? = parse_float(\"oops\")\n").is_ok());
+
+    let message = error_message("val n: int? = parse_int(42)\n");
+    assert!(message.contains("parse_int takes a string, but was given a value of type 'int'"), "{}", message);
+
+    let message = error_message("val x: float? = parse_float()\n");
+    assert!(message.contains("parse_float takes 1 argument but 0 were given"), "{}", message);
+}
+
+#[test]
+fn only_optionals_can_be_none() {
+    assert!(check_source("var n: int? = none\nn = 3\nn = none\n").is_ok());
+
+    let message = error_message("val n: int = none\n");
+    assert!(message.contains("Can't store none in a 'int'; only an optional type like 'int?' can hold it"), "{}", message);
+}
+
+#[test]
+fn every_target_of_a_chained_assignment_must_be_assignable() {
+    assert!(check_source("var a: int = 0\nvar b: int = 0\na = b = 5\n").is_ok());
+
+    let message = error_message("var a: int = 0\nval b: int = 0\na = b = 5\n");
+    assert!(message.contains("Cannot assign to 'b' because it was declared with 'val'"), "{}", message);
+}
+
+#[test]
+fn an_assignment_in_a_condition_needs_a_declared_var() {
+    assert!(check_source("var line: string\nwhile (line = input()) != \"\":\n    print(line)\n").is_ok());
+
+    let message = error_message("val line: string = \"\"\nwhile (line = input()) != \"\":\n    print(line)\n");
+    assert!(message.contains("Cannot assign to 'line' because it was declared with 'val'"), "{}", message);
+
+    let message = error_message("while (line = input()) != \"\":\n    print(line)\n");
+    assert!(message.contains("Cannot assign to 'line' because it isn't declared"), "{}", message);
+
+    let message = error_message("var n: int = 0\nprint((n = 1))\n");
+    assert!(message.contains("can only be used as a value in an 'if' or 'while' condition"), "{}", message);
+}
+
+#[test]
+fn static_assert_checks_its_condition_while_compiling() {
+    assert!(check_source("val size: int = 4\nstatic_assert size * 2 == 8, \"size must be 4\"\n").is_ok());
+
+    let message = error_message("val size: int = 5\nstatic_assert size * 2 == 8, \"size must be 4\"\n");
+    assert!(message.contains("Static assertion failed: size must be 4"), "{}", message);
+
+    assert!(check_source("enum Status: Ok, Err = 4\nstatic_assert Status.Err + 1 == 5\n").is_ok());
+
+    let message = error_message("var size: int = 4\nstatic_assert size == 4\n");
+    assert!(message.contains("static_assert needs a condition that can be worked out while compiling"), "{}", message);
+}
+
+#[test]
+fn int_literals_must_fit_in_32_bits() {
+    let message = error_message("val big: int = 3000000000\n");
+    assert!(message.contains("literal 3000000000 out of range for int"), "{}", message);
+
+    let message = error_message("var x: int = 0\nx = 2147483648\n");
+    assert!(message.contains("literal 2147483648 out of range for int"), "{}", message);
+
+    assert!(parse_source("val max: int = 2147483647\n").is_ok());
+}
+
+#[test]
+fn constant_arithmetic_must_fit_in_an_int() {
+    let err = parse_source("val x: int = 1\nprint(2000000000 + 2000000000)\n").expect_err("the sum overflows");
+    assert!(err.message.contains("2000000000 + 2000000000 overflows an int"), "{}", err.message);
+    assert_eq!(err.line, 2);
+
+    let message = error_message("val side: int = 50000\nval area: int = side * side\n");
+    assert!(message.contains("50000 * 50000 overflows an int"), "{}", message);
+
+    assert!(parse_source("val side: int = 46340\nval area: int = side * side\nvar n: int = 50000\nprint(n * n)\n").is_ok());
+}
+
+#[test]
+fn symbols_list_top_level_definitions() {
+    let program = parse_source("val limit: int = 10\n\ndef add(a: int, var b: int) -> int:\n    val local: int = 1\n    return a + b\n").unwrap();
+    let dump: Vec<String> = symbols(&program).iter().map(|s| s.to_string()).collect();
+    assert_eq!(dump, [
+        "variable  limit: int  at 1:1",
+        "function  add(a: int, var b: int) -> int  at 3:1",
+    ]);
+}
+
+#[test]
+fn loop_else_is_outside_the_loop() {
+    let message = error_message("while true:\n    break\nelse:\n    break\n");
+    assert!(message.contains("'break' outside of a loop"), "{}", message);
+
+    assert!(parse_source("for i in 0..3:\n    while true:\n        break\n    else:\n        continue\n").is_ok());
+}
+
+#[test]
+fn underscore_discards_instead_of_binding() {
+    let source = "def f() -> int:\n    return 1\n\nval _: int = f()\nval _: int = f()\n_ = f()\n";
+    let (program, _) = check_source(source).expect("discards should be accepted");
+    assert!(symbols(&program).iter().all(|s| s.name != "_"));
+
+    // Even a `val _` doesn't make `_` read-only.
+    assert!(parse_source("val _: int = 1\n_ = 2\n").is_ok());
+}
+
+#[test]
+fn tuples_destructure_into_a_name_per_element() {
+    let source = "def f() -> int:\n    return 1\n\nval pair: (int, string) = (f(), \"one\")\nval (_, y) = pair\nval (_, _) = pair\n_ = f()\nprint(y)\n";
+    let (program, _) = check_source(source).expect("destructuring with discards should be accepted");
+    assert!(symbols(&program).iter().all(|s| s.name != "_"));
+
+    // Each name gets the type of its element, and is a `val` or `var` like the declaration.
+    assert_eq!(
+        error_message("val (n, word) = (1, \"one\")\nunless word:\n    print(n)\n"),
+        "A condition has to be a bool, but this one is of type 'string'"
+    );
+    assert_eq!(error_message("val (a, b) = (1, 2)\na = 3\n"), "Cannot assign to 'a' because it was declared with 'val'");
+    assert!(parse_source("var (a, b) = (1, 2)\na = b\n").is_ok());
+
+    assert_eq!(error_message("val (a, b, c) = (1, 2)\n"), "This tuple has 2 elements, but there are 3 names to destructure it into");
+    assert_eq!(error_message("val n: int = 1\nval (a, b) = n\n"), "Only a tuple can be destructured, but this value is of type 'int'");
+    assert_eq!(error_message("val (a, a) = (1, 2)\n"), "'a' is named twice in this destructuring");
+}
+
+#[test]
+fn tuple_returns_promote_element_by_element() {
+    assert!(parse_source("def f() -> (float, int):\n    return (1, 2)\n").is_ok());
+    assert_eq!(
+        error_message("def f() -> (float, int):\n    return (1, \"x\")\n"),
+        "This function is declared to return '(float, int)', but this 'return' gives a '(int, string)'"
+    );
+}
+
+#[test]
+fn slices_cannot_be_assigned_to() {
+    let message = error_message("var xs: list[int] = [1, 2, 3]\nxs[0..2] = [4, 5]\n");
+    assert!(message.contains("Cannot assign to a slice"), "{}", message);
+}
+
+#[test]
+fn match_arms_must_give_the_same_type() {
+    let message = error_message("val c: int = 2\nval x: string = match c: 1 => \"one\", _ => 0\n");
+    assert!(message.contains("different types, 'string' and 'int'"), "{}", message);
+
+    let message = error_message("val c: int = 2\nval x: string = match c: \"1\" => \"one\", _ => \"many\"\n");
+    assert!(message.contains("Can't match a value of type 'int' against a pattern of type 'string'"), "{}", message);
+}
+
+#[test]
+fn returns_must_agree_on_giving_a_value() {
+    let message = error_message("def f(n: int) -> int:\n    if n > 0:\n        return\n    return n\n");
+    assert!(message.contains("returns a value at line 4 but returns nothing at line 3"), "{}", message);
+
+    assert!(parse_source("def f(n: int):\n    if n > 0:\n        return\n    print(n)\n").is_ok());
+}
+
+#[test]
+fn in_needs_a_list_of_the_same_type_or_a_string() {
+    let message = error_message("print(\"a\" in [1, 2])\n");
+    assert!(message.contains("Can't use 'in' to look for type 'string' in type 'list[int]'"), "{}", message);
+
+    assert!(parse_source("val xs: list[int] = [1, 2, 3]\nprint(3 in xs)\nprint(\"ell\" in \"hello\")\n").is_ok());
+}
+
+#[test]
+fn unknown_types_suggest_a_close_name() {
+    let message = error_message("class Widget:\n    var size: int = 0\n\nval w: Widgt = new Widget()\n");
+    assert_eq!(message, "Unknown type 'Widgt'; did you mean 'Widget'?");
+
+    let message = error_message("enum Color: Red, Green\ndef paint(colors: list[Colour]):\n    print(1)\n");
+    assert_eq!(message, "Unknown type 'Colour'; did you mean 'Color'?");
+
+    let message = error_message("val n: Int = 1\n");
+    assert_eq!(message, "Unknown type 'Int'; did you mean 'int'?");
+
+    // Two swapped letters count as a single typo.
+    let message = error_message("val x: flaot = 1.0\n");
+    assert_eq!(message, "Unknown type 'flaot'; did you mean 'float'?");
+
+    let message = error_message("val s: Spaceship = 1\n");
+    assert_eq!(message, "Unknown type 'Spaceship'");
+
+    // Classes from an imported module can't be seen, so they're trusted.
+    assert!(parse_source("import \"shapes.rl\"\nval s: Shape = new Shape()\n").is_ok());
+}
+
+#[test]
+fn range_takes_two_or_three_int_arguments() {
+    assert!(parse_source("val xs: list[int] = range(0, 5)\nfor i in range(10, 0, -2):\n    val j: int = i\n").is_ok());
+    assert_eq!(error_message("print(range(5))\n"), "range takes 2 or 3 arguments but 1 were given");
+    assert_eq!(error_message("print(range(0, 2.5))\n"), "range takes int bounds, but was given a value of type 'float'");
+    assert!(error_message("print(range(0, 5, 0))\n").starts_with("range's step can't be 0"));
+}
+
+#[test]
+fn only_calls_stand_alone_as_statements() {
+    assert!(parse_source("def greet(name: string):\n    print(name)\ngreet(\"hi\")\n").is_ok());
+    assert_eq!(
+        error_message("val x: int = 1\nx + 1\n"),
+        "The value of 'x + 1' isn't used; only a call can stand on its own as a statement (assign it to '_' to discard it on purpose)"
+    );
+    assert!(error_message("5\n").starts_with("The value of '5' isn't used"));
+    // A function's last expression is its return value, so it isn't thrown away.
+    assert!(parse_source("def one() -> int:\n    1\n_ = one()\n").is_ok());
+}
+
+#[test]
+fn loops_that_never_run_warn() {
+    let found = warnings("while false:\n    print(1)\n");
+    assert_eq!(found, [("This loop body never executes because its condition is always false".to_string(), 1)]);
+
+    let found = warnings("val n: int = 5\nfor i in n..5:\n    print(i)\n");
+    assert_eq!(found, [("This loop body never executes because the range 5..5 is empty".to_string(), 2)]);
+
+    assert!(warnings("var n: int = 5\nfor i in 0..n:\n    print(i)\nwhile n > 0:\n    n = n - 1\n").is_empty());
+    // A parameter or inner declaration with the same name hides the constant.
+    assert!(warnings("val n: int = 0\ndef count(n: int):\n    for i in 0..n:\n        print(i)\n").is_empty());
+    assert!(warnings("val n: int = 0\nif true:\n    var n: int = 3\n    for i in 0..n:\n        print(i)\n").is_empty());
+
+    let found = warnings("enum Size:\n    Small = 2\nfor i in 0..Size.Small - 2:\n    print(i)\n");
+    assert_eq!(found, [("This loop body never executes because the range 0..0 is empty".to_string(), 3)]);
+}
+
+#[test]
+fn conditions_have_to_be_bools() {
+    assert_eq!(error_message("val n: int = 1\nif n:\n    print(n)\n"), "A condition has to be a bool, but this one is of type 'int'");
+    assert_eq!(error_message("unless \"yes\":\n    print(1)\n"), "A condition has to be a bool, but this one is of type 'string'");
+    assert_eq!(error_message("while 1.5:\n    print(1)\n"), "A condition has to be a bool, but this one is of type 'float'");
+    assert!(parse_source("val done: bool = false\nunless done:\n    print(1)\n").is_ok());
+}
+
+#[test]
+fn modulo_takes_ints() {
+    assert_eq!(error_message("val x: float = 2.5\nprint(x % 2)\n"), "'%' takes int operands, but was given a float");
+    assert!(parse_source("val n: int = 7\nprint(n % 2)\n").is_ok());
+}
+
+#[test]
+fn returns_are_unified_against_the_declared_type() {
+    // An `int` is promoted where a `float` is returned, including inside an optional or a list.
+    let promoted = "def half(n: int) -> float:\n    if n % 2 == 0:\n        return n / 2\n    elif n < 0:\n        return 0\n    return n / 2.0\n";
+    assert!(parse_source(promoted).is_ok());
+    assert!(parse_source("def maybe(n: int) -> float?:\n    if n > 0:\n        return n\n    return none\n").is_ok());
+    assert!(parse_source("def ones() -> list[float]:\n    return [1, 1]\n").is_ok());
+
+    let err = parse_source("def label(n: int) -> float:\n    if n > 0:\n        return n\n    return \"none\"\n").expect_err("a string isn't a float");
+    assert_eq!(err.message, "This function is declared to return 'float', but this 'return' gives a 'string'");
+    assert_eq!(err.line, 4);
+    // There's no narrowing the other way.
+    assert_eq!(
+        error_message("def whole(x: float) -> int:\n    return x\n"),
+        "This function is declared to return 'int', but this 'return' gives a 'float'",
+    );
+
+    // A `void` function gives nothing back, and every other function gives something.
+    assert_eq!(error_message("def f():\n    return 5\n"), "This 'return' gives a value, but the function is declared void");
+    assert_eq!(error_message("def f() -> int:\n    return\n"), "This 'return' gives nothing, but the function is declared to return 'int'");
+    assert!(parse_source("def f(n: int):\n    if n > 0:\n        return\n    print(n)\n").is_ok());
+}
+
+#[test]
+fn a_label_has_to_name_an_enclosing_loop_and_be_unique() {
+    assert_eq!(error_message("while true:\n    break outer\n"), "'break outer' does not refer to an enclosing loop");
+    // A loop that has already ended doesn't enclose the `continue`.
+    assert_eq!(
+        error_message("outer: for i in 0..3:\n    print(i)\nfor j in 0..3:\n    continue outer\n"),
+        "'continue outer' does not refer to an enclosing loop",
+    );
+    assert_eq!(
+        error_message("outer: while true:\n    break\nouter: for i in 0..3:\n    break outer\n"),
+        "Loop label 'outer' is already used in this function",
+    );
+    // C++ labels only have to be unique within a function.
+    assert!(parse_source("def a():\n    outer: while true:\n        break outer\ndef b():\n    outer: while true:\n        break outer\n").is_ok());
+}
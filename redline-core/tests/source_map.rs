@@ -0,0 +1,41 @@
+//! Tests for converting between source offsets and line/column positions.
+use redline_core::lexer::Lexer;
+use redline_core::source_map::SourceMap;
+
+const SOURCE: &str = "val a: int = 1\nval é: char = 'ü'\n\nprint(a)\n";
+
+#[test]
+fn offsets_convert_to_positions_and_back() {
+    let map = SourceMap::new(SOURCE);
+    assert_eq!(map.line_count(), 5);
+
+    let known = [(0, (1, 1)), (4, (1, 5)), (14, (1, 15)), (15, (2, 1)), (19, (2, 5)), (21, (2, 6)), (33, (2, 17)), (34, (2, 18)), (35, (3, 1)), (36, (4, 1)), (45, (5, 1))];
+    for (offset, position) in known {
+        assert_eq!(map.position(offset), Some(position), "offset {}", offset);
+        assert_eq!(map.offset(position.0, position.1), Some(offset), "position {:?}", position);
+    }
+}
+
+#[test]
+fn out_of_range_positions_are_none() {
+    let map = SourceMap::new(SOURCE);
+    assert_eq!(map.position(SOURCE.len() + 1), None);
+    // The second byte of `é`.
+    assert_eq!(map.position(20), None);
+    assert_eq!(map.offset(0, 1), None);
+    assert_eq!(map.offset(1, 0), None);
+    assert_eq!(map.offset(1, 16), None);
+    assert_eq!(map.offset(6, 1), None);
+}
+
+#[test]
+fn positions_match_the_lexer() {
+    let map = SourceMap::new(SOURCE);
+    let tokens = Lexer::new(SOURCE.to_string()).tokenize().unwrap();
+    for token in tokens.iter().filter(|token| token.line <= 4) {
+        let offset = map.offset(token.line, token.column).expect("token positions are in the source");
+        assert_eq!(map.position(offset), Some((token.line, token.column)));
+    }
+    let print = tokens.iter().find(|token| token.line == 4).unwrap();
+    assert!(SOURCE[map.offset(print.line, print.column).unwrap()..].starts_with("print"));
+}
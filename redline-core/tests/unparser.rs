@@ -0,0 +1,57 @@
+//! Tests for `Program::to_source`, which prints an AST back out as REDLINE source.
+use redline_core::parse_source;
+use serde_json::Value;
+
+/// The program's AST as JSON, without the spans, which move when the source is reprinted.
+fn ast_without_spans(source: &str) -> Value {
+    fn strip(value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                fields.retain(|key, _| key != "span" && key != "else_span");
+                fields.values_mut().for_each(strip);
+            }
+            Value::Array(items) => items.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let program = parse_source(source).unwrap_or_else(|e| panic!("failed to parse {:?}: {}", source, e));
+    let mut value = serde_json::to_value(&program).unwrap();
+    strip(&mut value);
+    value
+}
+
+#[test]
+fn printed_source_parses_back_to_the_same_program() {
+    let programs = [
+        // Declarations, operators, and literals.
+        "val a: int = 1 + 2 * 3\nval b: int = (1 + 2) * 3\nval c: int = a - (b - 1)\nvar d: float = -2.50\nvar e: int\ne = 4\n\
+         val f: bool = !(a < b) && (c == 1 || d >= 0.5)\nval g: string = \"tab\\t \\\"quoted\\\" back\\\\slash\"\nval h: char = '\\''\n\
+         val i: int? = none\nval j: bool = 'x' in \"xyz\"\nprint(f\"a is {a}, b is {b:>4}\")\neprint(a)\n\
+         print(a, b, c)\neprint(a, \"b\", sep: \", \")\n\
+         print(-a * b)\nprint(-(a + b))\nprint(- -a)\nprint(- -1)\nprint(a - -b)\n",
+        // Collections, slices, and chained assignment.
+        "var xs: list[int] = [1, 2, 3]\nval ds: dict[string, list[int]] = {\"a\": [1], \"b\": []}\nprint(xs[1..])\nprint(xs[..2])\n\
+         print(xs[-1])\nvar y: int = 0\nvar z: int = 0\ny = z = xs[0]\n_ = len(xs)\ny += 1\nxs[0] %= z - 1\n\
+         val pair: (int, list[string]) = (1, [\"a\"])\nval (_, names) = pair\nvar (q, r) = (y / 2, (y, z))\n",
+        // Functions, with docs, variadics, raw identifiers, and a trailing expression.
+        "# Adds things up.\n#\n#  Indented doc line.\npub def sum(var first: int, rest: int...) -> int:\n    for n in 0..len(rest):\n        first = first + rest[n]\n    first\n\
+         def r#match(r#if: int):\n    return\nprint(sum(1, 2, 3))\nr#match(1)\n",
+        // Control flow.
+        "var n: int = 10\nouter: while n > 0:\n    for i in 0..n:\n        if i == 3:\n            continue outer\n        else:\n            if i > 5:\n                break\n    n = n - 1\n\
+         else:\n    print(\"done\")\nwhile (n = n + 1) < 3:\n    print(n)\ntry:\n    print(1 / n)\ncatch e:\n    print(e)\nstatic_assert 1 < 2, \"math\"\nstatic_assert true\n\
+         for c in \"abc\":\n    print(c)\nfor x in [1, 2]:\n    print(x)\nelse:\n    print(0)\n",
+        // Classes, enums, and match.
+        "pub enum Color: Red, Green = 5, Blue\nclass Point:\n    pub var x: int = 0\n    val y: int = 0\n    def init(x: int):\n        this.x = x\n    def moved(by: int) -> Point:\n        return new Point(this.x + by)\n\
+         val p: Point = new Point(1).moved(2)\nval name: string = match p.x: 1 => \"one\", 2 => \"two\", _ => \"many\"\nprint((match p.x: 3 => 1, _ => 2) + 1)\n",
+    ];
+    for source in programs {
+        let printed = parse_source(source).unwrap().to_source();
+        assert_eq!(ast_without_spans(&printed), ast_without_spans(source), "reprinted as:\n{}", printed);
+    }
+}
+
+#[test]
+fn blocks_are_indented_and_parentheses_kept_where_needed() {
+    let source = "def f(a: int, b: int) -> int:\n    if !(a == b):\n        return (a - b) * (a + b)\n    return a - (b - 1) - 1\n";
+    assert_eq!(parse_source(source).unwrap().to_source(), source);
+}
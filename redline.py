@@ -71,7 +71,9 @@ class Compiler:
 
     def __init__(self, core_bin_path):
         self.core_bin_path = core_bin_path
-        self.modules = {} # Cache for compiled modules: path -> Module
+        # Every module parsed so far in this build, keyed by canonical path, so a module that's
+        # imported from several files (or through different relative paths) is only parsed once.
+        self.modules = {}
 
     def get_ast(self, source_file):
         """Runs the core parser and returns the AST as a JSON object."""
@@ -80,6 +82,8 @@ class Compiler:
                 [str(self.core_bin_path), str(source_file), "--json-ast"],
                 capture_output=True, text=True, check=True,
             )
+            # Warnings are reported once here, while parsing, rather than on every codegen pass.
+            if result.stderr: print(result.stderr, file=sys.stderr)
             return json.loads(result.stdout)
         except (subprocess.CalledProcessError, json.JSONDecodeError) as e:
             print(f"Error: Failed to parse {source_file.name}.")
@@ -88,11 +92,14 @@ class Compiler:
 
     def compile_module_recursive(self, source_path):
         """Recursively compiles a module and its dependencies."""
+        source_path = Path(source_path).resolve()
+        # A module is cached before its imports are followed, so an import cycle ends here too.
+        # Cycles are fine otherwise: headers don't include each other, only the .cpp files do.
         if source_path in self.modules:
             return self.modules[source_path]
 
         print(f"  -> Analyzing module: {source_path.name}")
-        ast = self.get_ast(source_file)
+        ast = self.get_ast(source_path)
         if not ast:
             return None
 
@@ -0,0 +1,129 @@
+#ifndef RL_ANY_HPP
+#define RL_ANY_HPP
+
+#include <stdexcept>
+#include <string>
+#include <variant>
+#include "rl_io.hpp"
+#include "rl_stdlib.hpp"
+
+namespace rl {
+    // The runtime representation of REDLINE's `any` type.
+    // It can hold an int, float, bool, or string, and every operation checks the held types at runtime.
+    // That check (and the variant storage) makes `any` noticeably slower than a statically typed value.
+    class Any {
+    public:
+        using Value = std::variant<std::monostate, int, double, bool, std::string>;
+
+        Any() = default;
+        Any(int v) : value(v) {}
+        Any(double v) : value(v) {}
+        Any(bool v) : value(v) {}
+        // Without this, string literals would quietly turn into bools.
+        Any(const char* v) : value(std::string(v)) {}
+        Any(std::string v) : value(std::move(v)) {}
+
+        const Value& get() const { return value; }
+
+        bool is_number() const {
+            return std::holds_alternative<int>(value) || std::holds_alternative<double>(value);
+        }
+
+        double as_double() const {
+            if (auto i = std::get_if<int>(&value)) return *i;
+            return std::get<double>(value);
+        }
+
+        std::string type_name() const {
+            switch (value.index()) {
+                case 1: return "int";
+                case 2: return "float";
+                case 3: return "bool";
+                case 4: return "string";
+                default: return "empty";
+            }
+        }
+
+    private:
+        Value value;
+    };
+
+    inline std::runtime_error any_type_error(const char* op, const Any& a, const Any& b) {
+        return std::runtime_error(std::string("unsupported operand types for ") + op + ": " + a.type_name() + " and " + b.type_name());
+    }
+
+    // Applies an arithmetic operator: int op int stays an int, any other pair of numbers becomes a float.
+    template<typename IntOp, typename FloatOp>
+    Any any_arithmetic(const char* op, const Any& a, const Any& b, IntOp int_op, FloatOp float_op) {
+        auto ai = std::get_if<int>(&a.get());
+        auto bi = std::get_if<int>(&b.get());
+        if (ai && bi) return int_op(*ai, *bi);
+        if (a.is_number() && b.is_number()) return float_op(a.as_double(), b.as_double());
+        throw any_type_error(op, a, b);
+    }
+
+    inline Any operator+(const Any& a, const Any& b) {
+        auto as = std::get_if<std::string>(&a.get());
+        auto bs = std::get_if<std::string>(&b.get());
+        if (as && bs) return *as + *bs;
+        return any_arithmetic("+", a, b, [](int x, int y) { return x + y; }, [](double x, double y) { return x + y; });
+    }
+
+    inline Any operator-(const Any& a, const Any& b) {
+        return any_arithmetic("-", a, b, [](int x, int y) { return x - y; }, [](double x, double y) { return x - y; });
+    }
+
+    inline Any operator*(const Any& a, const Any& b) {
+        return any_arithmetic("*", a, b, [](int x, int y) { return x * y; }, [](double x, double y) { return x * y; });
+    }
+
+    inline Any operator/(const Any& a, const Any& b) {
+        return any_arithmetic("/", a, b, [](int x, int y) {
+            if (y == 0) throw std::runtime_error("division by zero");
+            return x / y;
+        }, [](double x, double y) { return x / y; });
+    }
+
+    inline bool operator==(const Any& a, const Any& b) {
+        if (a.is_number() && b.is_number()) return a.as_double() == b.as_double();
+        return a.get() == b.get();
+    }
+
+    inline bool operator!=(const Any& a, const Any& b) {
+        return !(a == b);
+    }
+
+    inline bool operator<(const Any& a, const Any& b) {
+        if (a.is_number() && b.is_number()) return a.as_double() < b.as_double();
+        auto as = std::get_if<std::string>(&a.get());
+        auto bs = std::get_if<std::string>(&b.get());
+        if (as && bs) return *as < *bs;
+        throw any_type_error("<", a, b);
+    }
+
+    inline bool operator>(const Any& a, const Any& b) { return b < a; }
+    inline bool operator<=(const Any& a, const Any& b) { return !(b < a); }
+    inline bool operator>=(const Any& a, const Any& b) { return !(a < b); }
+
+    inline std::string to_string(const Any& a) {
+        return std::visit([](const auto& v) -> std::string {
+            if constexpr (std::is_same_v<std::decay_t<decltype(v)>, std::monostate>) {
+                return "";
+            } else {
+                return to_string(v);
+            }
+        }, a.get());
+    }
+
+    inline void print(const Any& a, std::ostream& out = std::cout) {
+        std::visit([&out](const auto& v) {
+            if constexpr (std::is_same_v<std::decay_t<decltype(v)>, std::monostate>) {
+                print("", out);
+            } else {
+                print(v, out);
+            }
+        }, a.get());
+    }
+}
+
+#endif // RL_ANY_HPP
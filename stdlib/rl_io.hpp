@@ -5,31 +5,54 @@
 #include <iostream>
 #include <string>
 #include <vector>
+#include <optional>
+#include "rl_stdlib.hpp" // For to_string on chars
 
 namespace rl {
+    // Every print overload writes to stdout unless given another stream (`eprint` passes std::cerr).
+
     // Overload for printing std::string
-    inline void print(const std::string& msg) {
-        std::cout << msg << std::endl;
+    inline void print(const std::string& msg, std::ostream& out = std::cout) {
+        out << msg << std::endl;
     }
 
     // Overload for printing string literals to prevent implicit bool conversion
-    inline void print(const char* msg) {
-        std::cout << msg << std::endl;
+    inline void print(const char* msg, std::ostream& out = std::cout) {
+        out << msg << std::endl;
     }
 
     // Overload for printing integers
-    inline void print(int val) {
-        std::cout << val << std::endl;
+    inline void print(int val, std::ostream& out = std::cout) {
+        out << val << std::endl;
     }
 
     // Overload for printing floating-point numbers
-    inline void print(double val) {
-        std::cout << val << std::endl;
+    inline void print(double val, std::ostream& out = std::cout) {
+        out << val << std::endl;
     }
 
     // Overload for printing booleans
-    inline void print(bool val) {
-        std::cout << (val ? "true" : "false") << std::endl;
+    inline void print(bool val, std::ostream& out = std::cout) {
+        out << (val ? "true" : "false") << std::endl;
+    }
+
+    // Overloads for printing characters rather than their numeric codes
+    inline void print(char32_t val, std::ostream& out = std::cout) {
+        out << to_string(val) << std::endl;
+    }
+
+    inline void print(char val, std::ostream& out = std::cout) {
+        out << val << std::endl;
+    }
+
+    // Overload for printing optionals: the value, or "none"
+    template<typename T>
+    void print(const std::optional<T>& val, std::ostream& out = std::cout) {
+        if (val) {
+            print(*val, out);
+        } else {
+            out << "none" << std::endl;
+        }
     }
 
     // Function to read a line of input from the user
@@ -2,6 +2,7 @@
 #define RL_MATH_HPP
 
 #include <cmath>
+#include <type_traits>
 
 namespace rl {
     // Basic math functions
@@ -22,6 +23,45 @@ namespace rl {
     inline double min(double a, double b) { return a < b ? a : b; }
     inline double max(double a, double b) { return a > b ? a : b; }
 
+    // Whether two floats are within `eps` of each other, for comparing results that rounding may have nudged apart.
+    inline bool approx_eq(double a, double b, double eps) { return std::abs(a - b) < eps; }
+
+    // Floored (Python-style) division: the quotient rounds toward negative infinity, so -7 / 2 == -4.
+    // Used for `/` when compiling with `--int-div floor`. Floating-point operands divide normally.
+    template<typename A, typename B>
+    constexpr auto floor_div(A a, B b) {
+        if constexpr (std::is_integral_v<A> && std::is_integral_v<B>) {
+            auto q = a / b;
+            if ((a % b != 0) && ((a < 0) != (b < 0))) {
+                --q;
+            }
+            return q;
+        } else {
+            return a / b;
+        }
+    }
+
+    // Floored modulo, the partner of floor_div: the result takes the sign of the divisor, so -7 % 2 == 1.
+    template<typename A, typename B>
+    constexpr auto floor_mod(A a, B b) {
+        auto r = a % b;
+        if (r != 0 && ((r < 0) != (b < 0))) {
+            r += b;
+        }
+        return r;
+    }
+
+    // `a /= b` and `a %= b` under `--int-div floor`. The target is a reference, so it's only evaluated once.
+    template<typename A, typename B>
+    constexpr A& floor_div_assign(A& a, B b) {
+        return a = floor_div(a, b);
+    }
+
+    template<typename A, typename B>
+    constexpr A& floor_mod_assign(A& a, B b) {
+        return a = floor_mod(a, b);
+    }
+
     // Constants
     constexpr double PI = 3.14159265358979323846;
     constexpr double E = 2.71828182845904523536;
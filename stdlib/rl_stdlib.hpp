@@ -3,7 +3,12 @@
 
 #include <vector>
 #include <string>
+#include <map>
 #include <algorithm> // For sort, reverse, find
+#include <sstream> // For to_string(double)
+#include <optional>
+#include <stdexcept>
+#include <tuple>
 
 namespace rl {
     // Global command line arguments
@@ -46,6 +51,134 @@ namespace rl {
         return -1;
     }
 
+    // Checks if a vector holds a value, for `value in list`. The value only has to compare
+    // with the elements, so a string literal can be looked for in a list of strings.
+    template<typename T, typename U>
+    bool contains(const std::vector<T>& vec, const U& value) {
+        return std::find(vec.begin(), vec.end(), value) != vec.end();
+    }
+
+    // The ints from `start` up to (not including) `end`, counting by `step`, for `range(start, end, step)`.
+    // A negative step counts down. A step of 0 would never reach the end, so it throws.
+    inline std::vector<int> range(int start, int end, int step = 1) {
+        if (step == 0) throw std::invalid_argument("range step can't be 0");
+        std::vector<int> values;
+        for (long long i = start; step > 0 ? i < end : i > end; i += step) {
+            values.push_back(static_cast<int>(i));
+        }
+        return values;
+    }
+
+    // Element access for `list[i]` and `text[i]`. A negative index counts back from the end,
+    // so -1 is the last element. Anything still out of range throws std::out_of_range.
+    template<typename T>
+    decltype(auto) index_at(std::vector<T>& vec, long long i) {
+        return vec.at(static_cast<std::size_t>(i < 0 ? static_cast<long long>(vec.size()) + i : i));
+    }
+
+    template<typename T>
+    decltype(auto) index_at(const std::vector<T>& vec, long long i) {
+        return vec.at(static_cast<std::size_t>(i < 0 ? static_cast<long long>(vec.size()) + i : i));
+    }
+
+    inline char& index_at(std::string& s, long long i) {
+        return s.at(static_cast<std::size_t>(i < 0 ? static_cast<long long>(s.size()) + i : i));
+    }
+
+    inline const char& index_at(const std::string& s, long long i) {
+        return s.at(static_cast<std::size_t>(i < 0 ? static_cast<long long>(s.size()) + i : i));
+    }
+
+    // Dictionary keys are looked up as written; a negative key is just a key.
+    template<typename K, typename V>
+    V& index_at(std::map<K, V>& map, const typename std::map<K, V>::key_type& key) {
+        return map.at(key);
+    }
+
+    template<typename K, typename V>
+    const V& index_at(const std::map<K, V>& map, const typename std::map<K, V>::key_type& key) {
+        return map.at(key);
+    }
+
+    // The element an assignment like `xs[i] = value` stores into. Lists and strings are indexed like
+    // `index_at`, but storing to a dict key it doesn't have yet adds the key instead of throwing.
+    template<typename T>
+    decltype(auto) store_at(std::vector<T>& vec, long long i) {
+        return index_at(vec, i);
+    }
+
+    inline char& store_at(std::string& s, long long i) {
+        return index_at(s, i);
+    }
+
+    template<typename K, typename V>
+    V& store_at(std::map<K, V>& map, const typename std::map<K, V>::key_type& key) {
+        return map[key];
+    }
+
+    // What `for x in items:` loops over. A list is used as it is; a temporary one is moved in, so it
+    // lives as long as the loop.
+    template<typename T>
+    const std::vector<T>& elements(const std::vector<T>& items) {
+        return items;
+    }
+
+    template<typename T>
+    std::vector<T> elements(std::vector<T>&& items) {
+        return std::move(items);
+    }
+
+    // The characters of a string for `for c in text:`, decoded from UTF-8 so that each is a whole
+    // REDLINE char rather than a byte.
+    inline std::u32string elements(const std::string& s) {
+        std::u32string chars;
+        for (std::size_t i = 0; i < s.size();) {
+            unsigned char lead = static_cast<unsigned char>(s[i]);
+            int extra = lead < 0x80 ? 0 : lead < 0xE0 ? 1 : lead < 0xF0 ? 2 : 3;
+            char32_t c = extra == 0 ? lead : lead & (0x3F >> extra);
+            for (int k = 1; k <= extra && i + k < s.size(); ++k) {
+                c = (c << 6) | (static_cast<unsigned char>(s[i + k]) & 0x3F);
+            }
+            chars += c;
+            i += extra + 1;
+        }
+        return chars;
+    }
+
+    // Turns a slice bound into a position: negative bounds count from the end, and anything
+    // past either end is clamped to it.
+    inline std::size_t slice_bound(long long i, std::size_t size) {
+        long long n = static_cast<long long>(size);
+        return static_cast<std::size_t>(std::clamp(i < 0 ? n + i : i, 0LL, n));
+    }
+
+    // A copy of the elements from `start` up to (not including) `end`, for `list[start..end]`.
+    // Out-of-range bounds are clamped rather than thrown, so the result may just be shorter.
+    template<typename T>
+    std::vector<T> slice(const std::vector<T>& vec, long long start, long long end) {
+        std::size_t from = slice_bound(start, vec.size());
+        std::size_t to = slice_bound(end, vec.size());
+        if (from >= to) return {};
+        return std::vector<T>(vec.begin() + from, vec.begin() + to);
+    }
+
+    template<typename T>
+    std::vector<T> slice(const std::vector<T>& vec, long long start) {
+        return slice(vec, start, static_cast<long long>(vec.size()));
+    }
+
+    // The same for `text[start..end]`.
+    inline std::string slice(const std::string& s, long long start, long long end) {
+        std::size_t from = slice_bound(start, s.size());
+        std::size_t to = slice_bound(end, s.size());
+        if (from >= to) return "";
+        return s.substr(from, to - from);
+    }
+
+    inline std::string slice(const std::string& s, long long start) {
+        return slice(s, start, static_cast<long long>(s.size()));
+    }
+
     // --- Type Conversion Helpers ---
 
     // Identity function for strings.
@@ -53,20 +186,94 @@ namespace rl {
         return s;
     }
 
+    // Overload for string literals, which would otherwise convert to bool
+    inline std::string to_string(const char* s) {
+        return s;
+    }
+
     // Wrapper for std::to_string (int)
     inline std::string to_string(int val) {
         return std::to_string(val);
     }
 
-    // Wrapper for std::to_string (double)
+    // Formats a double the same way print does (3.5 rather than std::to_string's 3.500000)
     inline std::string to_string(double val) {
-        return std::to_string(val);
+        std::ostringstream out;
+        out << val;
+        return out.str();
     }
 
     // Wrapper for std::to_string (bool)
     inline std::string to_string(bool val) {
         return val ? "true" : "false";
     }
+
+    // A REDLINE char, encoded as UTF-8.
+    inline std::string to_string(char32_t c) {
+        std::string out;
+        if (c < 0x80) {
+            out += static_cast<char>(c);
+        } else if (c < 0x800) {
+            out += static_cast<char>(0xC0 | (c >> 6));
+            out += static_cast<char>(0x80 | (c & 0x3F));
+        } else if (c < 0x10000) {
+            out += static_cast<char>(0xE0 | (c >> 12));
+            out += static_cast<char>(0x80 | ((c >> 6) & 0x3F));
+            out += static_cast<char>(0x80 | (c & 0x3F));
+        } else {
+            out += static_cast<char>(0xF0 | (c >> 18));
+            out += static_cast<char>(0x80 | ((c >> 12) & 0x3F));
+            out += static_cast<char>(0x80 | ((c >> 6) & 0x3F));
+            out += static_cast<char>(0x80 | (c & 0x3F));
+        }
+        return out;
+    }
+
+    // A single byte of a string, e.g. from `text[0]`.
+    inline std::string to_string(char c) {
+        return std::string(1, c);
+    }
+
+    // An optional shows its value, or "none" when it has none.
+    template<typename T>
+    std::string to_string(const std::optional<T>& value) {
+        return value ? to_string(*value) : std::string("none");
+    }
+
+    // --- Optionals ---
+
+    // The whole string as an int, or none if it isn't one ("12abc" and "" are none, not 12 and 0).
+    inline std::optional<int> parse_int(const std::string& s) {
+        try {
+            std::size_t used = 0;
+            int value = std::stoi(s, &used);
+            if (used == s.size()) return value;
+        } catch (const std::exception&) {}
+        return std::nullopt;
+    }
+
+    // The whole string as a float, or none if it isn't one.
+    inline std::optional<double> parse_float(const std::string& s) {
+        try {
+            std::size_t used = 0;
+            double value = std::stod(s, &used);
+            if (used == s.size()) return value;
+        } catch (const std::exception&) {}
+        return std::nullopt;
+    }
+
+    // The value inside an optional. Throws if there isn't one, which a try/catch can handle.
+    template<typename T>
+    T unwrap(const std::optional<T>& value) {
+        if (!value) throw std::runtime_error("unwrap of none");
+        return *value;
+    }
+
+    // The value inside an optional, or `fallback` if there isn't one.
+    template<typename T, typename U>
+    T unwrap_or(const std::optional<T>& value, const U& fallback) {
+        return value ? *value : T(fallback);
+    }
 }
 
 #endif // RL_STDLIB_HPP
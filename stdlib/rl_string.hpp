@@ -4,6 +4,8 @@
 #include <string>
 #include <vector>
 #include <sstream>
+#include <iomanip>
+#include "rl_stdlib.hpp" // For to_string(char32_t)
 
 namespace rl {
 
@@ -13,6 +15,40 @@ namespace rl {
         return haystack.find(needle) != std::string::npos;
     }
 
+    // Checks if a string contains a character, for `'c' in text`.
+    inline bool contains(const std::string& haystack, char32_t needle) {
+        return contains(haystack, to_string(needle));
+    }
+
+    inline std::size_t concat_size(const std::string& s) { return s.size(); }
+    inline std::size_t concat_size(const char* s) { return std::char_traits<char>::length(s); }
+
+    // Joins the strings of a `+` chain in one go. `a + b + c + d` would build and copy a longer
+    // string at every step, while this sizes the result once and appends each piece to it.
+    template<typename... Parts>
+    std::string concat(const Parts&... parts) {
+        std::string out;
+        out.reserve((concat_size(parts) + ...));
+        (out += ... += parts);
+        return out;
+    }
+
+    // Pads text with spaces to `width` characters, for f-string placeholders like `{count:>5}`.
+    // `std::setw` counts bytes, so it's widened by the extra bytes of any multi-byte UTF-8 characters.
+    // The stream is a fresh one, so its width and alignment don't leak into anything printed later.
+    inline std::string align(const std::string& text, int width, std::ios_base& (*alignment)(std::ios_base&)) {
+        std::size_t characters = 0;
+        for (unsigned char c : text) {
+            if ((c & 0xC0) != 0x80) characters++;
+        }
+        std::ostringstream out;
+        out << alignment << std::setw(width + static_cast<int>(text.size() - characters)) << text;
+        return out.str();
+    }
+
+    inline std::string align_left(const std::string& text, int width) { return align(text, width, std::left); }
+    inline std::string align_right(const std::string& text, int width) { return align(text, width, std::right); }
+
     // Splits a string into pieces based on a delimiter.
     // It's like taking a hammer to a vase, but for text.
     inline std::vector<std::string> split(const std::string& s, const std::string& delimiter) {
@@ -0,0 +1,57 @@
+"""Tests for the build driver in redline.py. Run with `python3 -m unittest discover tests`."""
+import sys
+import tempfile
+import unittest
+from pathlib import Path
+
+sys.path.insert(0, str(Path(__file__).resolve().parent.parent))
+import redline
+
+
+class FakeCompiler(redline.Compiler):
+    """Reads imports straight from the source instead of running the core, and counts parses."""
+
+    def __init__(self):
+        super().__init__(core_bin_path=None)
+        self.parsed = []
+
+    def get_ast(self, source_file):
+        self.parsed.append(source_file.name)
+        imports = [line.split('"')[1] for line in source_file.read_text().splitlines() if line.startswith("import ")]
+        return {"statements": [{"Import": path} for path in imports] + [{"Expression": None}]}
+
+
+class ImportResolutionTest(unittest.TestCase):
+    def write_modules(self, root, sources):
+        for name, source in sources.items():
+            (root / name).write_text(source)
+
+    def test_diamond_import_parses_shared_module_once(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            root = Path(tmp)
+            (root / "sub").mkdir()
+            self.write_modules(root, {
+                "main.rl": 'import "left.rl"\nimport "sub/right.rl"\n',
+                "left.rl": 'import "shared.rl"\n',
+                "sub/right.rl": 'import "../shared.rl"\n',
+                "shared.rl": "pub def helper():\n    print(1)\n",
+            })
+            compiler = FakeCompiler()
+            self.assertIsNotNone(compiler.compile_module_recursive(root / "main.rl"))
+            self.assertEqual(compiler.parsed.count("shared.rl"), 1)
+            self.assertEqual(len(compiler.modules), 4)
+
+    def test_import_cycle_terminates(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            root = Path(tmp)
+            self.write_modules(root, {
+                "a.rl": 'import "b.rl"\n',
+                "b.rl": 'import "a.rl"\n',
+            })
+            compiler = FakeCompiler()
+            self.assertIsNotNone(compiler.compile_module_recursive(root / "a.rl"))
+            self.assertEqual(sorted(compiler.parsed), ["a.rl", "b.rl"])
+
+
+if __name__ == "__main__":
+    unittest.main()